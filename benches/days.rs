@@ -0,0 +1,285 @@
+//! Times the input generator and solver of every registered solution,
+//! including alternates, against the real inputs in a local directory.
+//!
+//!     cargo bench --bench days -- [--input <DIR>] [--day <N>] [--samples <N>]
+//!                                 [--json <FILE>] [--csv <FILE>] [--baseline <CSV>]
+//!
+//! Inputs are read from DIR/day<N>.txt, where DIR defaults to input/2023, and
+//! days without an input are skipped. A summary table goes to stderr. Results
+//! are written as JSON and/or CSV, or as CSV on stdout if neither is given. A
+//! CSV from an earlier run can be passed as `--baseline` to print the change
+//! in median times next to each result.
+//!
+//! Run without `--bench` (as `cargo test --benches` does), every solution is
+//! run once as a smoke test and nothing is written.
+
+use std::collections::HashMap;
+use std::fmt::Write as _;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use anyhow::Context;
+use anyhow::Result;
+use anyhow::anyhow;
+use anyhow::bail;
+
+use aoc_2023::registry;
+use aoc_2023::registry::Solution;
+
+#[derive(Debug)]
+struct Args {
+    bench: bool,
+    input: PathBuf,
+    day: Option<u32>,
+    samples: usize,
+    json: Option<PathBuf>,
+    csv: Option<PathBuf>,
+    baseline: Option<PathBuf>,
+}
+
+fn parse_args(mut args: impl Iterator<Item = String>) -> Result<Args> {
+    let mut parsed = Args {
+        bench: false,
+        input: PathBuf::from("input/2023"),
+        day: None,
+        samples: 10,
+        json: None,
+        csv: None,
+        baseline: None,
+    };
+
+    while let Some(arg) = args.next() {
+        let mut value = |name: &str| args.next().ok_or_else(|| anyhow!("{} needs a value", name));
+
+        match arg.as_str() {
+            "--bench" => parsed.bench = true,
+            "--input" => parsed.input = value("--input")?.into(),
+            "--day" => parsed.day = Some(value("--day")?.parse()?),
+            "--samples" => parsed.samples = value("--samples")?.parse()?,
+            "--json" => parsed.json = Some(value("--json")?.into()),
+            "--csv" => parsed.csv = Some(value("--csv")?.into()),
+            "--baseline" => parsed.baseline = Some(value("--baseline")?.into()),
+            // Passed through by `cargo bench` and `cargo test`
+            "--nocapture" | "--quiet" | "-q" => {},
+            _ => bail!("Unknown argument {:?}", arg),
+        }
+    }
+
+    if parsed.samples == 0 {
+        bail!("--samples must be at least 1");
+    }
+
+    Ok(parsed)
+}
+
+/// Summary statistics over the samples of one step, in nanoseconds.
+#[derive(Debug, Clone, Copy)]
+struct Stats {
+    min: u128,
+    median: u128,
+    mean: u128,
+    max: u128,
+}
+
+impl Stats {
+    fn new(samples: &[Duration]) -> Stats {
+        let mut nanos: Vec<_> = samples.iter().map(Duration::as_nanos).collect();
+        nanos.sort();
+
+        let len = nanos.len();
+        let median = if len % 2 == 1 {
+            nanos[len / 2]
+        } else {
+            (nanos[len / 2 - 1] + nanos[len / 2]) / 2
+        };
+
+        Stats {
+            min: nanos[0],
+            median,
+            mean: nanos.iter().sum::<u128>() / len as u128,
+            max: nanos[len - 1],
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Measurement {
+    day: u32,
+    part: u32,
+    name: &'static str,
+    answer: String,
+    samples: usize,
+    parse: Stats,
+    solve: Stats,
+}
+
+impl Measurement {
+    fn key(&self) -> String {
+        format!("{}/{}/{}", self.day, self.part, self.name)
+    }
+}
+
+fn measure(solution: &Solution, input: &str, samples: usize) -> Result<Measurement> {
+    // One untimed run to warm caches and check that the solution works at all
+    let first = (solution.run)(input)?;
+
+    let mut parse_times = Vec::with_capacity(samples);
+    let mut solve_times = Vec::with_capacity(samples);
+    for _ in 0..samples {
+        let run = (solution.run)(input)?;
+        if run.answer != first.answer {
+            bail!("Answer changed between runs: {} then {}", first.answer, run.answer);
+        }
+        parse_times.push(run.parse_time);
+        solve_times.push(run.solve_time);
+    }
+
+    Ok(Measurement {
+        day: solution.day,
+        part: solution.part,
+        name: solution.name.unwrap_or("-"),
+        answer: first.answer,
+        samples,
+        parse: Stats::new(&parse_times),
+        solve: Stats::new(&solve_times),
+    })
+}
+
+const CSV_HEADER: &str = "day,part,name,samples,parse_min_ns,parse_median_ns,parse_mean_ns,parse_max_ns,solve_min_ns,solve_median_ns,solve_mean_ns,solve_max_ns";
+
+fn to_csv(measurements: &[Measurement]) -> String {
+    let mut csv = String::new();
+    writeln!(csv, "{}", CSV_HEADER).unwrap();
+
+    for m in measurements {
+        writeln!(csv, "{},{},{},{},{},{},{},{},{},{},{},{}",
+            m.day, m.part, m.name, m.samples,
+            m.parse.min, m.parse.median, m.parse.mean, m.parse.max,
+            m.solve.min, m.solve.median, m.solve.mean, m.solve.max).unwrap();
+    }
+
+    csv
+}
+
+fn json_stats(stats: &Stats) -> String {
+    format!(r#"{{"min_ns": {}, "median_ns": {}, "mean_ns": {}, "max_ns": {}}}"#, stats.min, stats.median, stats.mean, stats.max)
+}
+
+/// Answers and names are plain ASCII, but escape them properly anyway.
+fn json_string(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len() + 2);
+    escaped.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            c if (c as u32) < 0x20 => write!(escaped, "\\u{:04x}", c as u32).unwrap(),
+            c => escaped.push(c),
+        }
+    }
+    escaped.push('"');
+    escaped
+}
+
+fn to_json(measurements: &[Measurement]) -> String {
+    let entries: Vec<_> = measurements.iter()
+        .map(|m| {
+            format!(r#"    {{"day": {}, "part": {}, "name": {}, "answer": {}, "samples": {}, "parse": {}, "solve": {}}}"#,
+                m.day, m.part, json_string(m.name), json_string(&m.answer), m.samples,
+                json_stats(&m.parse), json_stats(&m.solve))
+        })
+        .collect();
+
+    format!("[\n{}\n]\n", entries.join(",\n"))
+}
+
+/// Reads the (parse, solve) medians of each solution from an earlier CSV.
+fn read_baseline(path: &PathBuf) -> Result<HashMap<String, (u128, u128)>> {
+    let csv = std::fs::read_to_string(path).with_context(|| format!("Couldn't read {}", path.display()))?;
+    let mut lines = csv.lines();
+
+    if lines.next() != Some(CSV_HEADER) {
+        bail!("{} isn't a benchmark CSV", path.display());
+    }
+
+    lines.filter(|line| !line.is_empty())
+        .map(|line| {
+            let fields: Vec<_> = line.split(',').collect();
+            if fields.len() != 12 {
+                bail!("Malformed line in {}: {:?}", path.display(), line);
+            }
+            let key = format!("{}/{}/{}", fields[0], fields[1], fields[2]);
+            Ok((key, (fields[5].parse()?, fields[9].parse()?)))
+        })
+        .collect()
+}
+
+fn millis(nanos: u128) -> String {
+    format!("{:.3}", nanos as f64 / 1_000_000.0)
+}
+
+fn change(baseline: u128, current: u128) -> String {
+    if baseline == 0 {
+        return "-".to_owned();
+    }
+    format!("{:+.1}%", (current as f64 / baseline as f64 - 1.0) * 100.0)
+}
+
+fn main() -> Result<()> {
+    let args = parse_args(std::env::args().skip(1))?;
+    let baseline = args.baseline.as_ref().map(read_baseline).transpose()?;
+    let samples = if args.bench { args.samples } else { 1 };
+
+    let mut measurements = Vec::new();
+    let mut inputs: HashMap<u32, Option<String>> = HashMap::new();
+    let mut failed = false;
+
+    for solution in registry::all().filter(|solution| args.day.is_none_or(|day| solution.day == day)) {
+        let input = inputs.entry(solution.day)
+            .or_insert_with(|| std::fs::read_to_string(args.input.join(format!("day{}.txt", solution.day))).ok());
+        let Some(input) = input else {
+            continue;
+        };
+
+        match measure(solution, input, samples) {
+            Ok(measurement) => measurements.push(measurement),
+            Err(e) => {
+                eprintln!("day {} part {} {}: {:#}", solution.day, solution.part, solution.name.unwrap_or("-"), e);
+                failed = true;
+            },
+        }
+    }
+
+    if measurements.is_empty() && !failed {
+        eprintln!("No inputs found in {}, nothing to benchmark", args.input.display());
+        return Ok(());
+    }
+
+    eprintln!("{:>3} {:>4} {:<12} {:>12} {:>12} {:>9} {:>9}", "day", "part", "name", "parse_ms", "solve_ms", "parse", "solve");
+    for m in &measurements {
+        let (parse_change, solve_change) = match baseline.as_ref().and_then(|baseline| baseline.get(&m.key())) {
+            Some(&(parse, solve)) => (change(parse, m.parse.median), change(solve, m.solve.median)),
+            None => ("-".to_owned(), "-".to_owned()),
+        };
+        eprintln!("{:>3} {:>4} {:<12} {:>12} {:>12} {:>9} {:>9}",
+            m.day, m.part, m.name, millis(m.parse.median), millis(m.solve.median), parse_change, solve_change);
+    }
+
+    if args.bench {
+        if let Some(path) = &args.json {
+            std::fs::write(path, to_json(&measurements)).with_context(|| format!("Couldn't write {}", path.display()))?;
+        }
+        if let Some(path) = &args.csv {
+            std::fs::write(path, to_csv(&measurements)).with_context(|| format!("Couldn't write {}", path.display()))?;
+        }
+        if args.json.is_none() && args.csv.is_none() {
+            print!("{}", to_csv(&measurements));
+        }
+    }
+
+    if failed {
+        bail!("Some solutions failed");
+    }
+
+    Ok(())
+}
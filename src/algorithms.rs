@@ -0,0 +1,10 @@
+//! The pure puzzle algorithms, which only need `core` and `alloc` so that
+//! they can be used without the standard library. Everything else in the
+//! crate, from parsing to the registry, needs the `std` feature.
+
+pub mod day1;
+pub mod day6;
+pub mod day7;
+pub mod day9;
+pub mod day12;
+pub mod day15;
@@ -0,0 +1,255 @@
+use alloc::collections::BTreeMap;
+use alloc::collections::VecDeque;
+use alloc::vec;
+use alloc::vec::Vec;
+
+/// The digits 1 to 9, written as digits.
+pub const DIGITS: &[(&str, u32)] = &[
+    ("1", 1), ("2", 2), ("3", 3), ("4", 4), ("5", 5), ("6", 6), ("7", 7), ("8", 8), ("9", 9),
+];
+
+/// The digits 1 to 9, spelled out in English.
+pub const ENGLISH: &[(&str, u32)] = &[
+    ("one", 1), ("two", 2), ("three", 3), ("four", 4), ("five", 5), ("six", 6), ("seven", 7), ("eight", 8), ("nine", 9),
+];
+
+/// The digits 1 to 9, spelled out in German.
+pub const GERMAN: &[(&str, u32)] = &[
+    ("eins", 1), ("zwei", 2), ("drei", 3), ("vier", 4), ("fünf", 5), ("sechs", 6), ("sieben", 7), ("acht", 8), ("neun", 9),
+];
+
+/// The digits 1 to 9, spelled out in French.
+pub const FRENCH: &[(&str, u32)] = &[
+    ("un", 1), ("deux", 2), ("trois", 3), ("quatre", 4), ("cinq", 5), ("six", 6), ("sept", 7), ("huit", 8), ("neuf", 9),
+];
+
+/// The digits 1 to 9, spelled out in Spanish.
+pub const SPANISH: &[(&str, u32)] = &[
+    ("uno", 1), ("dos", 2), ("tres", 3), ("cuatro", 4), ("cinco", 5), ("seis", 6), ("siete", 7), ("ocho", 8), ("nueve", 9),
+];
+
+/// The numbers 10 to 19, spelled out in English, to go with `ENGLISH`.
+pub const ENGLISH_TEENS: &[(&str, u32)] = &[
+    ("ten", 10), ("eleven", 11), ("twelve", 12), ("thirteen", 13), ("fourteen", 14),
+    ("fifteen", 15), ("sixteen", 16), ("seventeen", 17), ("eighteen", 18), ("nineteen", 19),
+];
+
+/// The calibration value for a line whose first number is `first` and whose
+/// last is `last`: the first digit of one followed by the last digit of the
+/// other, so that a word for a number with several digits counts as those
+/// digits written out.
+pub fn calibration_value(first: u32, last: u32) -> u32 {
+    let mut leading = first;
+    while leading >= 10 {
+        leading /= 10;
+    }
+
+    leading * 10 + last % 10
+}
+
+/// Finds the first and last of a set of words in a line, each in a single
+/// pass, even where words overlap like the "eight" and "two" in "eightwo".
+///
+/// When two words start at the same place, the longer one is first, and when
+/// two words end at the same place, the longer one is last.
+#[derive(Debug, Clone)]
+pub struct Matcher {
+    forward: Automaton,
+    backward: Automaton,
+}
+
+impl Matcher {
+    /// A matcher for `words`, each with the value it stands for. Empty words
+    /// never match.
+    pub fn new<S: AsRef<str>>(words: impl IntoIterator<Item = (S, u32)>) -> Matcher {
+        let words: Vec<(Vec<u8>, u32)> = words.into_iter()
+            .map(|(word, value)| (word.as_ref().as_bytes().to_vec(), value))
+            .filter(|(word, _)| !word.is_empty())
+            .collect();
+
+        let reversed = words.iter().map(|(word, value)| (word.iter().rev().copied().collect(), *value));
+
+        Matcher {
+            forward: Automaton::new(words.iter().cloned()),
+            backward: Automaton::new(reversed),
+        }
+    }
+
+    /// The value of the word that starts first in `text`.
+    pub fn first(&self, text: &str) -> Option<u32> {
+        self.forward.leftmost(text.bytes())
+    }
+
+    /// The value of the word that ends last in `text`.
+    pub fn last(&self, text: &str) -> Option<u32> {
+        self.backward.leftmost(text.bytes().rev())
+    }
+}
+
+/// An Aho-Corasick automaton: a trie of the words where each node also links
+/// to the longest suffix of its path that's a prefix of some word, so that
+/// every word ending at each byte is found without backtracking.
+#[derive(Debug, Clone)]
+struct Automaton {
+    nodes: Vec<Node>,
+    /// The length and value of each word.
+    words: Vec<(usize, u32)>,
+    longest: usize,
+}
+
+#[derive(Debug, Clone, Default)]
+struct Node {
+    children: BTreeMap<u8, usize>,
+    fail: usize,
+    /// The words ending at this node, including through its fail links.
+    matches: Vec<usize>,
+}
+
+impl Automaton {
+    fn new(words: impl IntoIterator<Item = (Vec<u8>, u32)>) -> Automaton {
+        let mut nodes = vec![Node::default()];
+        let mut lengths = Vec::new();
+
+        for (index, (word, value)) in words.into_iter().enumerate() {
+            let mut current = 0;
+            for &byte in &word {
+                current = match nodes[current].children.get(&byte) {
+                    Some(&child) => child,
+                    None => {
+                        nodes.push(Node::default());
+                        let child = nodes.len() - 1;
+                        nodes[current].children.insert(byte, child);
+                        child
+                    },
+                };
+            }
+            nodes[current].matches.push(index);
+            lengths.push((word.len(), value));
+        }
+
+        // Parents come before children in breadth-first order, so each fail
+        // link's target is complete by the time it's needed.
+        let mut queue: VecDeque<usize> = nodes[0].children.values().copied().collect();
+        while let Some(current) = queue.pop_front() {
+            let children: Vec<_> = nodes[current].children.iter().map(|(&byte, &child)| (byte, child)).collect();
+            for (byte, child) in children {
+                let mut fail = nodes[current].fail;
+                let fail = loop {
+                    if let Some(&next) = nodes[fail].children.get(&byte) {
+                        break next;
+                    }
+                    if fail == 0 {
+                        break 0;
+                    }
+                    fail = nodes[fail].fail;
+                };
+
+                nodes[child].fail = fail;
+                let inherited = nodes[fail].matches.clone();
+                nodes[child].matches.extend(inherited);
+                queue.push_back(child);
+            }
+        }
+
+        let longest = lengths.iter().map(|&(length, _)| length).max().unwrap_or(0);
+
+        Automaton {
+            nodes,
+            words: lengths,
+            longest,
+        }
+    }
+
+    fn step(&self, mut current: usize, byte: u8) -> usize {
+        loop {
+            if let Some(&next) = self.nodes[current].children.get(&byte) {
+                return next;
+            }
+            if current == 0 {
+                return 0;
+            }
+            current = self.nodes[current].fail;
+        }
+    }
+
+    /// The value of the word starting earliest in `bytes`, preferring the
+    /// longest of those starting at the same place.
+    fn leftmost(&self, bytes: impl Iterator<Item = u8>) -> Option<u32> {
+        let mut current = 0;
+        // The start, length and value of the best match so far.
+        let mut best: Option<(usize, usize, u32)> = None;
+
+        for (i, byte) in bytes.enumerate() {
+            if best.is_some_and(|(start, _, _)| i >= start + self.longest) {
+                break;
+            }
+
+            current = self.step(current, byte);
+            for &word in &self.nodes[current].matches {
+                let (length, value) = self.words[word];
+                let start = i + 1 - length;
+                if best.is_none_or(|(best_start, best_length, _)| start < best_start || (start == best_start && length > best_length)) {
+                    best = Some((start, length, value));
+                }
+            }
+        }
+
+        best.map(|(_, _, value)| value)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::Matcher;
+
+    fn english() -> Matcher {
+        Matcher::new(super::DIGITS.iter().chain(super::ENGLISH).copied())
+    }
+
+    #[test]
+    fn test_first_and_last() {
+        let matcher = english();
+
+        assert_eq!(matcher.first("two1nine"), Some(2));
+        assert_eq!(matcher.last("two1nine"), Some(9));
+        assert_eq!(matcher.first("xtwone3four"), Some(2));
+        assert_eq!(matcher.last("xtwone3four"), Some(4));
+        assert_eq!(matcher.first("7pqrstsixteen"), Some(7));
+        assert_eq!(matcher.last("7pqrstsixteen"), Some(6));
+        assert_eq!(matcher.first("nothing"), None);
+        assert_eq!(matcher.last(""), None);
+    }
+
+    #[test]
+    fn test_overlapping_words() {
+        let matcher = english();
+
+        assert_eq!(matcher.first("eightwo"), Some(8));
+        assert_eq!(matcher.last("eightwo"), Some(2));
+        assert_eq!(matcher.first("oneight"), Some(1));
+        assert_eq!(matcher.last("oneight"), Some(8));
+        assert_eq!(matcher.last("sevenine"), Some(9));
+        // "nin" leads into a second "nine" that has to be found through a fail link.
+        assert_eq!(matcher.first("ninine"), Some(9));
+        assert_eq!(matcher.first("thrthree"), Some(3));
+    }
+
+    #[test]
+    fn test_calibration_value() {
+        assert_eq!(super::calibration_value(8, 2), 82);
+        assert_eq!(super::calibration_value(17, 17), 17);
+        assert_eq!(super::calibration_value(123, 45), 15);
+        assert_eq!(super::calibration_value(0, 10), 0);
+    }
+
+    #[test]
+    fn test_longest_at_same_start() {
+        let matcher = Matcher::new([("seven", 7), ("seventeen", 17), ("teen", 0), ("een", 100)]);
+
+        assert_eq!(matcher.first("xseventeen"), Some(17));
+        assert_eq!(matcher.last("xseventeen"), Some(17));
+        assert_eq!(matcher.last("xteen"), Some(0));
+        assert_eq!(matcher.first("xsevenx"), Some(7));
+        assert_eq!(matcher.first("s"), None);
+    }
+}
@@ -0,0 +1,101 @@
+use alloc::vec;
+
+/// The state of one spring in a condition record.
+#[derive(Debug, Clone, Copy)]
+#[derive(PartialEq, Eq)]
+#[derive(Hash)]
+pub enum SpringCondition {
+    Operational,
+    Damaged,
+    Unknown,
+}
+
+/// How many ways the unknown springs in `row` can be filled in so that its
+/// runs of damaged springs have the lengths in `groups`, by plain recursion.
+/// Every group has to be at least one spring long.
+pub fn possible_arrangements(row: &[SpringCondition], groups: &[usize]) -> usize {
+    if groups.is_empty() {
+        if row.iter().all(|&condition| condition != SpringCondition::Damaged) {
+            1
+        } else {
+            0
+        }
+    } else {
+        if minimum_group_length(groups) > row.len() {
+            0
+        } else {
+            let mut total = 0;
+            if group_fits(groups[0], row) {
+                if row.len() == groups[0] {
+                    total += possible_arrangements(&[], &groups[1..]);
+                } else {
+                    total += possible_arrangements(&row[groups[0] + 1..], &groups[1..]);
+                }
+            }
+
+            if row[0] != SpringCondition::Damaged {
+                total += possible_arrangements(&row[1..], groups);
+            }
+
+            total
+        }
+    }
+}
+
+fn minimum_group_length(groups: &[usize]) -> usize {
+    groups.iter().cloned().sum::<usize>() + groups.len() - 1
+}
+
+fn group_fits(n: usize, row: &[SpringCondition]) -> bool {
+    if row.len() < n {
+        false
+    } else if row.len() == n {
+        row.iter().take(n).all(|&condition| condition != SpringCondition::Operational)
+    } else {
+        row.iter().take(n).all(|&condition| condition != SpringCondition::Operational) &&
+            row[n] != SpringCondition::Damaged
+    }
+}
+
+/// Counts the same arrangements as `possible_arrangements`, remembering the
+/// count for each subproblem. The recursion only ever looks at suffixes of
+/// `row` and `groups`, so their lengths are enough to index a table.
+pub fn possible_arrangements_memoized(row: &[SpringCondition], groups: &[usize]) -> usize {
+    let mut subproblems = vec![None; (row.len() + 1) * (groups.len() + 1)];
+    count_suffixes(row, groups, groups.len() + 1, &mut subproblems)
+}
+
+fn count_suffixes(row: &[SpringCondition], groups: &[usize], stride: usize, subproblems: &mut [Option<usize>]) -> usize {
+    let index = row.len() * stride + groups.len();
+    if let Some(count) = subproblems[index] {
+        return count;
+    }
+    let result = if groups.is_empty() {
+        if row.iter().all(|&condition| condition != SpringCondition::Damaged) {
+            1
+        } else {
+            0
+        }
+    } else {
+        if minimum_group_length(groups) > row.len() {
+            0
+        } else {
+            let mut total = 0;
+            if group_fits(groups[0], row) {
+                if row.len() == groups[0] {
+                    total += count_suffixes(&[], &groups[1..], stride, subproblems);
+                } else {
+                    total += count_suffixes(&row[groups[0] + 1..], &groups[1..], stride, subproblems);
+                }
+            }
+
+            if row[0] != SpringCondition::Damaged {
+                total += count_suffixes(&row[1..], groups, stride, subproblems);
+            }
+
+            total
+        }
+    };
+    subproblems[index] = Some(result);
+    result
+}
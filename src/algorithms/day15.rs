@@ -0,0 +1,13 @@
+/// The Holiday ASCII String Helper algorithm, which maps `s` to a box number
+/// from 0 to 255.
+pub fn hash_algorithm(s: &str) -> usize {
+    let mut current: usize = 0;
+
+    for c in s.chars() {
+        current += c as usize;
+        current *= 17;
+        current %= 256;
+    }
+
+    current
+}
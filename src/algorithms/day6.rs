@@ -0,0 +1,47 @@
+/// How many whole-millisecond button holds beat `distance` in a race lasting
+/// `time` milliseconds.
+pub fn ways_to_win_the_race(time: u64, distance: u64) -> u64 {
+    // Holding for n travels (time - n) * n, which beats the record strictly
+    // between the roots of n^2 - time * n + distance. The integer square
+    // root of the discriminant gets within one of the lower root.
+    let (time, distance) = (time as u128, distance as u128);
+    let beats = |n: u128| n <= time && (time - n) * n > distance;
+
+    let Some(discriminant) = (time * time).checked_sub(4 * distance) else {
+        return 0;
+    };
+
+    let mut lowest = (time - discriminant.isqrt()) / 2;
+    while lowest > 0 && beats(lowest - 1) {
+        lowest -= 1;
+    }
+    while lowest <= time / 2 && !beats(lowest) {
+        lowest += 1;
+    }
+
+    if lowest > time / 2 {
+        return 0;
+    }
+
+    // The winning holds are symmetric about time / 2.
+    (time - 2 * lowest + 1) as u64
+}
+
+#[cfg(test)]
+mod test {
+    #[test]
+    fn test_ways_to_win() {
+        assert_eq!(super::ways_to_win_the_race(7, 9), 4);
+        assert_eq!(super::ways_to_win_the_race(15, 40), 8);
+        assert_eq!(super::ways_to_win_the_race(30, 200), 9);
+        assert_eq!(super::ways_to_win_the_race(71530, 940200), 71503);
+
+        // The record is the best possible distance, so it can't be beaten.
+        assert_eq!(super::ways_to_win_the_race(10, 25), 0);
+        assert_eq!(super::ways_to_win_the_race(10, 100), 0);
+        assert_eq!(super::ways_to_win_the_race(0, 0), 0);
+
+        // Too large to solve exactly with floating point.
+        assert_eq!(super::ways_to_win_the_race(u64::MAX, 0), u64::MAX - 1);
+    }
+}
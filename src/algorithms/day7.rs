@@ -0,0 +1,77 @@
+use alloc::vec::Vec;
+
+pub type Hand = [Card; 5];
+
+/// A Camel Cards card, from strongest to weakest.
+#[derive(Debug, Clone, Copy)]
+#[derive(PartialEq, Eq, PartialOrd, Ord)]
+pub enum Card {
+    Ace,
+    King,
+    Queen,
+    Jack,
+    Ten,
+    Nine,
+    Eight,
+    Seven,
+    Six,
+    Five,
+    Four,
+    Three,
+    Two,
+}
+
+/// The kind of a hand, from strongest to weakest.
+#[derive(Debug, Clone, Copy)]
+#[derive(PartialEq, Eq, PartialOrd, Ord)]
+pub enum HandType {
+    FiveOfAKind,
+    FourOfAKind,
+    FullHouse,
+    ThreeOfAKind,
+    TwoPair,
+    OnePair,
+    HighCard,
+}
+
+pub const CARD_VALUES: [Card; 13] = [
+    Card::Ace,
+    Card::King,
+    Card::Queen,
+    Card::Jack,
+    Card::Ten,
+    Card::Nine,
+    Card::Eight,
+    Card::Seven,
+    Card::Six,
+    Card::Five,
+    Card::Four,
+    Card::Three,
+    Card::Two,
+];
+
+pub fn hand_type(hand: &Hand) -> Option<HandType> {
+    let mut counts: Vec<_> = CARD_VALUES.iter()
+        .map(|card_value| {
+            hand.iter().filter(|&card| card == card_value).count()
+        })
+        .filter(|&count| count > 0)
+        .collect();
+
+    counts.sort();
+
+    use HandType::*;
+
+    let hand_type = match counts.as_slice() {
+        [5] => FiveOfAKind,
+        [1, 4] => FourOfAKind,
+        [2, 3] => FullHouse,
+        [1, 1, 3] => ThreeOfAKind,
+        [1, 2, 2] => TwoPair,
+        [1, 1, 1, 2] => OnePair,
+        [1, 1, 1, 1, 1] => HighCard,
+        _ => return None,
+    };
+
+    Some(hand_type)
+}
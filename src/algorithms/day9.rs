@@ -0,0 +1,33 @@
+use alloc::vec::Vec;
+
+/// The value that comes after `sequence`, found by extrapolating its
+/// differences until they're all zero. `None` if `sequence` is empty.
+pub fn predict_next(sequence: &[i64]) -> Option<i64> {
+    let difference_sequence: Vec<_> = sequence.windows(2)
+        .map(|window| window[1] - window[0])
+        .collect();
+
+    let next_difference = if difference_sequence.iter().all(|&n| n == 0) {
+        0
+    } else {
+        predict_next(&difference_sequence)?
+    };
+
+    Some(sequence.last()? + next_difference)
+}
+
+/// The value that comes before `sequence`, extrapolated the same way as
+/// `predict_next`.
+pub fn predict_prev(sequence: &[i64]) -> Option<i64> {
+    let difference_sequence: Vec<_> = sequence.windows(2)
+        .map(|window| window[1] - window[0])
+        .collect();
+
+    let prev_difference = if difference_sequence.iter().all(|&n| n == 0) {
+        0
+    } else {
+        predict_prev(&difference_sequence)?
+    };
+
+    Some(sequence.first()? - prev_difference)
+}
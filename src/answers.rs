@@ -0,0 +1,210 @@
+//! Checks every registered solution against known answers for the real
+//! inputs, so that refactors of shared code can't silently change them.
+//!
+//! Answers live in a TOML file with one table per day and one key per part:
+//!
+//! ```toml
+//! [1]
+//! 1 = 54940
+//! 2 = "54208"
+//! ```
+//!
+//! Values can be integers or strings. Alternate implementations, which can be
+//! far slower or hungrier than the main ones, are only checked when asked
+//! for, against the answer for their day and part.
+
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use anyhow::Context;
+use anyhow::Result;
+use anyhow::anyhow;
+use anyhow::bail;
+
+use crate::registry;
+use crate::registry::Solution;
+
+#[derive(Debug, Clone, Default)]
+#[derive(PartialEq, Eq)]
+pub struct Answers {
+    expected: BTreeMap<(u32, u32), String>,
+}
+
+impl Answers {
+    pub fn parse(text: &str) -> Result<Answers> {
+        let table: toml::Table = text.parse()?;
+        let mut expected = BTreeMap::new();
+
+        for (day_key, parts) in &table {
+            let day = day_key.parse()
+                .map_err(|_| anyhow!("Day {:?} isn't a number", day_key))?;
+            let parts = parts.as_table()
+                .ok_or_else(|| anyhow!("Day {} should be a table of parts", day))?;
+
+            for (part_key, value) in parts {
+                let part = part_key.parse()
+                    .map_err(|_| anyhow!("Day {} part {:?} isn't a number", day, part_key))?;
+                let answer = match value {
+                    toml::Value::Integer(n) => n.to_string(),
+                    toml::Value::String(s) => s.clone(),
+                    _ => bail!("Day {} part {} should be an integer or a string", day, part),
+                };
+
+                expected.insert((day, part), answer);
+            }
+        }
+
+        Ok(Answers { expected })
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> Result<Answers> {
+        let path = path.as_ref();
+        let text = std::fs::read_to_string(path).with_context(|| format!("Couldn't read {}", path.display()))?;
+
+        Answers::parse(&text).with_context(|| format!("Couldn't parse {}", path.display()))
+    }
+
+    pub fn get(&self, day: u32, part: u32) -> Option<&str> {
+        self.expected.get(&(day, part)).map(String::as_str)
+    }
+}
+
+#[derive(Debug, Clone)]
+#[derive(PartialEq, Eq)]
+pub enum Status {
+    Pass,
+    Mismatch { expected: String, actual: String },
+    Error(String),
+    /// There's an answer to check against, but no input to run on.
+    NoInput,
+    /// There's nothing to check the solution against.
+    NoAnswer,
+}
+
+impl Status {
+    pub fn is_failure(&self) -> bool {
+        matches!(self, Status::Mismatch { .. } | Status::Error(_) | Status::NoInput)
+    }
+
+    /// A short label for the matrix.
+    pub fn label(&self) -> &'static str {
+        match self {
+            Status::Pass => "pass",
+            Status::Mismatch { .. } => "FAIL",
+            Status::Error(_) => "ERROR",
+            Status::NoInput => "no input",
+            Status::NoAnswer => "-",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Check {
+    pub solution: &'static Solution,
+    pub status: Status,
+}
+
+/// Runs every main solution whose day has an input, along with the alternates
+/// if `alternates` is set, fetching each day's input at most once.
+pub fn verify(answers: &Answers, alternates: bool, mut input_for: impl FnMut(u32) -> Option<String>) -> Vec<Check> {
+    let mut inputs: BTreeMap<u32, Option<String>> = BTreeMap::new();
+
+    registry::all()
+        .filter(|solution| alternates || solution.name.is_none())
+        .map(|solution| {
+            let status = match answers.get(solution.day, solution.part) {
+                None => Status::NoAnswer,
+                Some(expected) => {
+                    let input = inputs.entry(solution.day).or_insert_with(|| input_for(solution.day));
+                    match input {
+                        None => Status::NoInput,
+                        Some(input) => check(solution, input, expected),
+                    }
+                },
+            };
+
+            Check { solution, status }
+        })
+        .collect()
+}
+
+fn check(solution: &Solution, input: &str, expected: &str) -> Status {
+    match (solution.run)(input) {
+        Ok(run) if run.answer == expected => Status::Pass,
+        Ok(run) => Status::Mismatch { expected: expected.to_owned(), actual: run.answer },
+        Err(e) => Status::Error(e.to_string()),
+    }
+}
+
+/// The worst status of several checks, so one failing alternate fails its cell.
+pub fn combine<'a>(statuses: impl IntoIterator<Item = &'a Status>) -> Option<&'a Status> {
+    let rank = |status: &Status| match status {
+        Status::NoAnswer => 0,
+        Status::Pass => 1,
+        Status::NoInput => 2,
+        Status::Mismatch { .. } => 3,
+        Status::Error(_) => 4,
+    };
+
+    statuses.into_iter().max_by_key(|status| rank(status))
+}
+
+#[cfg(test)]
+mod test {
+    use super::Answers;
+    use super::Status;
+
+    const DAY9_INPUT: &'static str =
+r#"
+0 3 6 9 12 15
+1 3 6 10 15 21
+10 13 16 21 30 45
+"#;
+
+    #[test]
+    fn test_parse() {
+        let answers = Answers::parse("[9]\n1 = 114\n2 = \"2\"\n\n[10]\n1 = 8\n").unwrap();
+
+        assert_eq!(answers.get(9, 1), Some("114"));
+        assert_eq!(answers.get(9, 2), Some("2"));
+        assert_eq!(answers.get(10, 1), Some("8"));
+        assert_eq!(answers.get(10, 2), None);
+
+        assert!(Answers::parse("[nine]\n1 = 114\n").is_err());
+        assert!(Answers::parse("[9]\n1 = 1.5\n").is_err());
+    }
+
+    #[test]
+    fn test_verify() {
+        let answers = Answers::parse("[9]\n1 = 114\n2 = 3\n\n[10]\n1 = 8\n").unwrap();
+        let checks = super::verify(&answers, false, |day| (day == 9).then(|| DAY9_INPUT.to_owned()));
+
+        let status = |day, part| {
+            let statuses = checks.iter()
+                .filter(|check| check.solution.day == day && check.solution.part == part)
+                .map(|check| &check.status);
+            super::combine(statuses).cloned()
+        };
+
+        assert_eq!(status(9, 1), Some(Status::Pass));
+        assert_eq!(status(9, 2), Some(Status::Mismatch { expected: "3".to_owned(), actual: "2".to_owned() }));
+        assert_eq!(status(10, 1), Some(Status::NoInput));
+        assert_eq!(status(11, 1), Some(Status::NoAnswer));
+        assert_eq!(status(26, 1), None);
+    }
+
+    #[test]
+    fn test_verify_alternates() {
+        let answers = Answers::parse("[5]\n2 = 46\n").unwrap();
+        let input = "seeds: 79 14 55 13\n\nseed-to-location map:\n50 98 2\n52 50 48\n";
+        let names = |alternates| {
+            super::verify(&answers, alternates, |_| Some(input.to_owned())).into_iter()
+                .filter(|check| check.solution.day == 5 && check.solution.part == 2)
+                .map(|check| check.solution.name)
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(names(false), [None]);
+        assert_eq!(names(true), [None, Some("memory_hog")]);
+    }
+}
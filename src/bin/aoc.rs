@@ -0,0 +1,332 @@
+use std::fs::File;
+use std::io::BufReader;
+use std::io::Read;
+use std::path::Path;
+use std::path::PathBuf;
+use std::process::ExitCode;
+use std::time::Duration;
+use std::time::Instant;
+
+use anyhow::Context;
+use anyhow::Result;
+use anyhow::anyhow;
+use anyhow::bail;
+
+use aoc_2023::answers;
+use aoc_2023::answers::Answers;
+use aoc_2023::gen;
+use aoc_2023::registry;
+use aoc_2023::registry::Solution;
+
+const USAGE: &str = "\
+Usage: aoc --day <N> [--part <N>] [--alt <NAME>] [--input <FILE>]
+       aoc --day <N> --stream [--part <N>] [--input <FILE>]
+       aoc --all [--input <DIR>]
+       aoc --verify <ANSWERS> [--alts] [--input <DIR>]
+       aoc --gen <DAY> [--seed <N>] [--size <N>]
+
+Runs the registered solutions, reading the puzzle input from FILE or stdin.
+With --all, every main solution is run against DIR/day<N>.txt, where DIR
+defaults to input/2023.
+
+With --stream, the input is read a line at a time rather than all at once,
+for inputs too big to fit in memory. Only days 1, 2, 4, 6, 7, 9, 12, 15 and
+22 support it, and reading from stdin needs --part as it can only be read once.
+Day 2 needs its game IDs in increasing order when streamed, and day 22 still
+keeps every brick, just not the text they were read from.
+
+With --verify, every main solution is run against DIR/day<N>.txt and checked
+against the answers in the TOML file ANSWERS, which has a [<day>] table per
+day with a <part> = <answer> entry per part. With --alts, the alternates are
+checked too, though some of them need far more time or memory on a real
+input. A pass/fail matrix is printed, and any mismatch makes the exit status 1.
+
+With --gen, a random input for DAY is written to stdout instead. The same
+seed (default 0) always gives the same input, and size scales it; what it
+counts depends on the day, and it defaults to about a real input's size.
+
+Each result is printed as one line of space-separated key=value fields.
+
+Set AOC_RENDER_DIR to save images of the grids in days 14, 16 and 17 there,
+and AOC_RENDER_FORMAT to ppm (the default), pgm or svg to pick their format.
+
+Set AOC_DAY1_VOCABULARY to english (the default), english-teens, german,
+french or spanish to pick the spelled-out numbers day 1 part 2 looks for, or
+to the path of a TOML file of words and the numbers they stand for.";
+
+#[derive(Debug, Default)]
+struct Args {
+    day: Option<u32>,
+    part: Option<u32>,
+    alt: Option<String>,
+    input: Option<PathBuf>,
+    all: bool,
+    verify: Option<PathBuf>,
+    alts: bool,
+    stream: bool,
+    gen: Option<u32>,
+    seed: Option<u64>,
+    size: Option<usize>,
+}
+
+fn parse_args(mut args: impl Iterator<Item = String>) -> Result<Args> {
+    let mut parsed = Args::default();
+
+    while let Some(arg) = args.next() {
+        let mut value = |name: &str| args.next().ok_or_else(|| anyhow!("{} needs a value", name));
+
+        match arg.as_str() {
+            "--day" => parsed.day = Some(value("--day")?.parse()?),
+            "--part" => parsed.part = Some(value("--part")?.parse()?),
+            "--alt" => parsed.alt = Some(value("--alt")?),
+            "--input" => parsed.input = Some(value("--input")?.into()),
+            "--all" => parsed.all = true,
+            "--verify" => parsed.verify = Some(value("--verify")?.into()),
+            "--alts" => parsed.alts = true,
+            "--stream" => parsed.stream = true,
+            "--gen" => parsed.gen = Some(value("--gen")?.parse()?),
+            "--seed" => parsed.seed = Some(value("--seed")?.parse()?),
+            "--size" => parsed.size = Some(value("--size")?.parse()?),
+            _ => bail!("Unknown argument {:?}", arg),
+        }
+    }
+
+    let solving = parsed.all || parsed.day.is_some() || parsed.part.is_some() || parsed.alt.is_some() || parsed.input.is_some() || parsed.verify.is_some() || parsed.alts || parsed.stream;
+    match parsed.gen {
+        Some(_) if solving => bail!("--gen can only be combined with --seed and --size"),
+        Some(_) => return Ok(parsed),
+        None if parsed.seed.is_some() || parsed.size.is_some() => bail!("--seed and --size need --gen"),
+        None => {},
+    }
+
+    match (parsed.all, parsed.day, &parsed.verify) {
+        (_, _, Some(_)) if parsed.all || parsed.day.is_some() || parsed.part.is_some() || parsed.alt.is_some() => {
+            bail!("--verify can only be combined with --input")
+        },
+        (_, _, None) if parsed.alts => bail!("--alts needs --verify"),
+        (true, Some(_), _) => bail!("--all and --day can't be used together"),
+        (false, None, None) => bail!("One of --day, --all, --verify or --gen is required"),
+        _ => {},
+    }
+
+    if parsed.stream {
+        if parsed.day.is_none() || parsed.all || parsed.alt.is_some() {
+            bail!("--stream needs --day, and can't be used with --all or --alt");
+        }
+        if parsed.input.is_none() && parsed.part.is_none() {
+            bail!("--stream reads stdin only once, so it needs --part or --input");
+        }
+    }
+
+    Ok(parsed)
+}
+
+fn select(args: &Args) -> Result<Vec<&'static Solution>> {
+    let name = args.alt.as_deref();
+
+    let solutions: Vec<_> = registry::all()
+        .filter(|solution| args.day.is_none_or(|day| solution.day == day))
+        .filter(|solution| args.part.is_none_or(|part| solution.part == part))
+        .filter(|solution| solution.name == name)
+        .collect();
+
+    if solutions.is_empty() {
+        bail!("No solution registered for the selected day, part and alternative");
+    }
+
+    Ok(solutions)
+}
+
+fn input_path(args: &Args, day: u32) -> PathBuf {
+    let dir = args.input.clone().unwrap_or_else(|| PathBuf::from("input/2023"));
+    dir.join(format!("day{}.txt", day))
+}
+
+fn read_input(args: &Args, day: u32) -> Result<String> {
+    let input = if args.all {
+        let path = input_path(args, day);
+        std::fs::read_to_string(&path).with_context(|| format!("Couldn't read {}", path.display()))?
+    } else if let Some(path) = &args.input {
+        std::fs::read_to_string(path).with_context(|| format!("Couldn't read {}", path.display()))?
+    } else {
+        let mut input = String::new();
+        std::io::stdin().read_to_string(&mut input)?;
+        input
+    };
+
+    Ok(input)
+}
+
+fn millis(duration: Duration) -> String {
+    format!("{:.3}", duration.as_secs_f64() * 1000.0)
+}
+
+/// Prints one result line and reports whether the solution succeeded.
+fn report(solution: &Solution, input: &Result<String>) -> bool {
+    let prefix = format!("day={} part={} alt={}", solution.day, solution.part, solution.name.unwrap_or("-"));
+
+    let input = match input {
+        Ok(input) => input,
+        Err(e) => {
+            println!("{} status=input_error error={:?}", prefix, format!("{:#}", e));
+            return false;
+        },
+    };
+
+    match (solution.run)(input) {
+        Ok(run) => {
+            println!("{} status=ok answer={} parse_ms={} solve_ms={}", prefix, run.answer, millis(run.parse_time), millis(run.solve_time));
+            true
+        },
+        Err(registry::RunError::Parse(e)) => {
+            println!("{} status=parse_error error={:?}", prefix, format!("{:#}", e));
+            false
+        },
+        Err(registry::RunError::Solve(e)) => {
+            println!("{} status=solve_error error={:?}", prefix, format!("{:#}", e));
+            false
+        },
+    }
+}
+
+/// Runs the streaming solvers for the selected day, printing a result line
+/// for each like `report`, and reports whether they all succeeded.
+fn stream(args: &Args) -> Result<bool> {
+    let solutions: Vec<_> = registry::streaming()
+        .filter(|solution| args.day == Some(solution.day))
+        .filter(|solution| args.part.is_none_or(|part| solution.part == part))
+        .collect();
+
+    if solutions.is_empty() {
+        bail!("No streaming solution for the selected day and part");
+    }
+
+    let mut all_ok = true;
+    for solution in solutions {
+        let prefix = format!("day={} part={} alt=stream", solution.day, solution.part);
+
+        let start = Instant::now();
+        let result = match &args.input {
+            Some(path) => File::open(path)
+                .with_context(|| format!("Couldn't read {}", path.display()))
+                .and_then(|file| (solution.run)(&mut BufReader::new(file))),
+            None => (solution.run)(&mut std::io::stdin().lock()),
+        };
+
+        match result {
+            Ok(answer) => println!("{} status=ok answer={} solve_ms={}", prefix, answer, millis(start.elapsed())),
+            Err(e) => {
+                println!("{} status=error error={:?}", prefix, format!("{:#}", e));
+                all_ok = false;
+            },
+        }
+    }
+
+    Ok(all_ok)
+}
+
+/// Prints a day by part matrix of results, then the details of each failure.
+fn verify(args: &Args, path: &Path) -> Result<bool> {
+    let answers = Answers::load(path)?;
+    let checks = answers::verify(&answers, args.alts, |day| std::fs::read_to_string(input_path(args, day)).ok());
+
+    let mut days: Vec<_> = registry::all().map(|solution| solution.day).collect();
+    days.dedup();
+
+    println!("day  part 1     part 2");
+    for day in days {
+        let cell = |part| {
+            let statuses = checks.iter()
+                .filter(|check| check.solution.day == day && check.solution.part == part)
+                .map(|check| &check.status);
+            answers::combine(statuses).map_or("", |status| status.label())
+        };
+        println!("{:>3}  {:<10} {}", day, cell(1), cell(2));
+    }
+
+    let failures: Vec<_> = checks.iter().filter(|check| check.status.is_failure()).collect();
+    if !failures.is_empty() {
+        println!();
+    }
+    for check in &failures {
+        let solution = check.solution;
+        let prefix = format!("day {} part {}{}", solution.day, solution.part, solution.name.map(|name| format!(" ({})", name)).unwrap_or_default());
+        match &check.status {
+            answers::Status::Mismatch { expected, actual } => println!("{}: expected {}, got {}", prefix, expected, actual),
+            answers::Status::Error(e) => println!("{}: {}", prefix, e),
+            answers::Status::NoInput => println!("{}: couldn't read {}", prefix, input_path(args, solution.day).display()),
+            answers::Status::Pass | answers::Status::NoAnswer => {},
+        }
+    }
+
+    Ok(failures.is_empty())
+}
+
+fn main() -> ExitCode {
+    let args = std::env::args().skip(1);
+    let args = match parse_args(args) {
+        Ok(args) => args,
+        Err(e) => {
+            eprintln!("{}\n\n{}", e, USAGE);
+            return ExitCode::from(2);
+        },
+    };
+
+    if let Some(day) = args.gen {
+        let Some(generator) = gen::find(day) else {
+            eprintln!("No generator for day {}", day);
+            return ExitCode::from(2);
+        };
+        print!("{}", generator.run(args.seed.unwrap_or(0), args.size.unwrap_or(generator.default_size)));
+        return ExitCode::SUCCESS;
+    }
+
+    if let Some(path) = &args.verify {
+        return match verify(&args, path) {
+            Ok(true) => ExitCode::SUCCESS,
+            Ok(false) => ExitCode::FAILURE,
+            Err(e) => {
+                eprintln!("{:#}", e);
+                ExitCode::from(2)
+            },
+        };
+    }
+
+    if args.stream {
+        return match stream(&args) {
+            Ok(true) => ExitCode::SUCCESS,
+            Ok(false) => ExitCode::FAILURE,
+            Err(e) => {
+                eprintln!("{}", e);
+                ExitCode::from(2)
+            },
+        };
+    }
+
+    let solutions = match select(&args) {
+        Ok(solutions) => solutions,
+        Err(e) => {
+            eprintln!("{}", e);
+            return ExitCode::from(2);
+        },
+    };
+
+    // Stdin can only be read once, so share each day's input between its parts.
+    let mut inputs: Vec<(u32, Result<String>)> = Vec::new();
+    let mut all_ok = true;
+
+    for solution in solutions {
+        if !inputs.iter().any(|(day, _)| *day == solution.day) {
+            inputs.push((solution.day, read_input(&args, solution.day)));
+        }
+        let (_, input) = inputs.iter().find(|(day, _)| *day == solution.day).unwrap();
+
+        all_ok &= report(solution, input);
+    }
+
+    if all_ok {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::FAILURE
+    }
+}
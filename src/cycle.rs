@@ -0,0 +1,194 @@
+use std::collections::HashMap;
+use std::collections::hash_map::Entry;
+use std::hash::Hash;
+
+/// The shape of an iterated sequence `x, f(x), f(f(x)), ...`: after `prefix`
+/// steps it enters a loop that repeats every `period` steps.
+#[derive(Debug, Clone, Copy)]
+#[derive(PartialEq, Eq)]
+pub struct Cycle {
+    pub prefix: usize,
+    pub period: usize,
+}
+
+impl Cycle {
+    /// The smallest step count that reaches the same state as `steps` does.
+    pub fn normalize(&self, steps: usize) -> usize {
+        if steps < self.prefix {
+            steps
+        } else {
+            self.prefix + (steps - self.prefix) % self.period
+        }
+    }
+}
+
+/// Finds the cycle using Floyd's tortoise and hare, storing only two states at a time.
+pub fn floyd<S: Clone + PartialEq>(start: &S, f: impl Fn(&S) -> S) -> Cycle {
+    let mut tortoise = f(start);
+    let mut hare = f(&tortoise);
+    while tortoise != hare {
+        tortoise = f(&tortoise);
+        hare = f(&f(&hare));
+    }
+
+    let mut prefix = 0;
+    let mut tortoise = start.clone();
+    while tortoise != hare {
+        tortoise = f(&tortoise);
+        hare = f(&hare);
+        prefix += 1;
+    }
+
+    let mut period = 1;
+    let mut hare = f(&tortoise);
+    while tortoise != hare {
+        hare = f(&hare);
+        period += 1;
+    }
+
+    Cycle { prefix, period }
+}
+
+/// Finds the cycle using Brent's algorithm, which usually needs fewer calls to `f` than `floyd`.
+pub fn brent<S: Clone + PartialEq>(start: &S, f: impl Fn(&S) -> S) -> Cycle {
+    brent_within(start, f, usize::MAX).unwrap()
+}
+
+/// Like `brent`, but gives up with `None` once the search for the period has
+/// taken `limit` steps, so that a sequence with a huge prefix or period
+/// can't run on indefinitely. A cycle whose prefix and period are both at
+/// most `limit / 8` is always found.
+pub fn brent_within<S: Clone + PartialEq>(start: &S, f: impl Fn(&S) -> S, limit: usize) -> Option<Cycle> {
+    let mut power = 1;
+    let mut period = 1;
+    let mut steps = 1;
+    let mut tortoise = start.clone();
+    let mut hare = f(start);
+    while tortoise != hare {
+        if steps >= limit {
+            return None;
+        }
+        steps += 1;
+
+        if power == period {
+            tortoise = hare.clone();
+            power *= 2;
+            period = 0;
+        }
+        hare = f(&hare);
+        period += 1;
+    }
+
+    let mut tortoise = start.clone();
+    let mut hare = start.clone();
+    for _ in 0..period {
+        hare = f(&hare);
+    }
+
+    let mut prefix = 0;
+    while tortoise != hare {
+        tortoise = f(&tortoise);
+        hare = f(&hare);
+        prefix += 1;
+    }
+
+    Some(Cycle { prefix, period })
+}
+
+/// Every distinct state of an iterated sequence, in order, up to the point where it repeats.
+#[derive(Debug, Clone)]
+pub struct History<S> {
+    pub states: Vec<S>,
+    pub cycle: Cycle,
+}
+
+impl<S> History<S> {
+    pub fn state_after(&self, steps: usize) -> &S {
+        &self.states[self.cycle.normalize(steps)]
+    }
+
+    /// The states that make up one period of the loop.
+    pub fn cycle_states(&self) -> &[S] {
+        &self.states[self.cycle.prefix..]
+    }
+}
+
+/// Finds the cycle by remembering every state seen, which takes a single pass
+/// and makes any later state available through `History::state_after`.
+pub fn record<S: Clone + Eq + Hash>(start: &S, f: impl Fn(&S) -> S) -> History<S> {
+    let mut states = vec![start.clone()];
+    let mut seen = HashMap::new();
+    seen.insert(start.clone(), 0);
+
+    loop {
+        let next = f(states.last().unwrap());
+        match seen.entry(next) {
+            Entry::Occupied(entry) => {
+                let prefix = *entry.get();
+                let period = states.len() - prefix;
+
+                return History { states, cycle: Cycle { prefix, period } };
+            },
+            Entry::Vacant(entry) => {
+                states.push(entry.key().clone());
+                entry.insert(states.len() - 1);
+            },
+        }
+    }
+}
+
+/// The state reached after `steps` applications of `f`, without iterating
+/// past the first repeat.
+pub fn state_after<S: Clone + Eq + Hash>(start: &S, f: impl Fn(&S) -> S, steps: usize) -> S {
+    record(start, f).state_after(steps).clone()
+}
+
+#[cfg(test)]
+mod test {
+    use super::Cycle;
+
+    /// 0 -> 1 -> 2 -> 3 -> 4 -> 5 -> 6 -> 3 -> ...
+    fn step(&x: &u32) -> u32 {
+        if x == 6 { 3 } else { x + 1 }
+    }
+
+    #[test]
+    fn test_detectors_agree() {
+        let expected = Cycle { prefix: 3, period: 4 };
+
+        assert_eq!(super::floyd(&0, step), expected);
+        assert_eq!(super::brent(&0, step), expected);
+        assert_eq!(super::record(&0, step).cycle, expected);
+
+        assert_eq!(super::brent(&4, step), Cycle { prefix: 0, period: 4 });
+        assert_eq!(super::floyd(&7, |&x| x), Cycle { prefix: 0, period: 1 });
+    }
+
+    #[test]
+    fn test_brent_within() {
+        assert_eq!(super::brent_within(&0, step, 14), Some(Cycle { prefix: 3, period: 4 }));
+        assert_eq!(super::brent_within(&0, step, 3), None);
+        assert_eq!(super::brent_within(&0, |&x: &u64| x + 1, 1000), None);
+    }
+
+    #[test]
+    fn test_state_after() {
+        let mut x = 0;
+        for steps in 0..50 {
+            assert_eq!(super::state_after(&0, step, steps), x);
+            x = step(&x);
+        }
+
+        let history = super::record(&0, step);
+        assert_eq!(history.cycle_states(), &[3, 4, 5, 6]);
+        assert_eq!(*history.state_after(1_000_000_000), 3 + (1_000_000_000 - 3) % 4);
+    }
+
+    #[test]
+    fn test_long_period() {
+        // A cycle much longer than any fixed look-back window.
+        let step = |&x: &u32| (x + 1) % 1000;
+        assert_eq!(super::brent(&0, step), Cycle { prefix: 0, period: 1000 });
+        assert_eq!(*super::record(&0, step).state_after(1_000_000_007), 7);
+    }
+}
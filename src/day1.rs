@@ -1,103 +1,196 @@
+use std::io::BufRead;
+use std::path::Path;
+
 use aoc_runner_derive::aoc_generator;
 use aoc_runner_derive::aoc;
 
+use anyhow::Context;
 use anyhow::Result;
+use anyhow::anyhow;
+use anyhow::bail;
+
+use thiserror::Error;
+
+use crate::algorithms::day1::DIGITS;
+use crate::algorithms::day1::ENGLISH;
+use crate::algorithms::day1::ENGLISH_TEENS;
+use crate::algorithms::day1::FRENCH;
+use crate::algorithms::day1::GERMAN;
+use crate::algorithms::day1::Matcher;
+use crate::algorithms::day1::SPANISH;
+use crate::algorithms::day1::calibration_value;
+use crate::parse::stream_lines;
+
+pub type Data = String;
+
+#[derive(Debug, Error)]
+pub enum Day1Error {
+    #[error("Line {line} has no digits: {text:?}")]
+    NoDigits { line: usize, text: String },
+    #[error("Couldn't load the vocabulary: {0:#}")]
+    Vocabulary(anyhow::Error),
+}
+
+/// Picks the vocabulary for part 2: the name of a built-in one, or the path
+/// of a file to load. English if unset.
+pub const VOCABULARY_VAR: &str = "AOC_DAY1_VOCABULARY";
+
+/// The spelled-out numbers part 2 looks for as well as digits, each with the
+/// number it stands for. Words for numbers with several digits count as
+/// those digits written out. Where words overlap, the one starting first
+/// counts as the first number and the one ending last as the last, with the
+/// longer word winning if they start or end at the same place.
+#[derive(Debug, Clone)]
+#[derive(PartialEq, Eq)]
+pub struct Vocabulary {
+    words: Vec<(String, u32)>,
+}
+
+impl Vocabulary {
+    /// The built-in vocabularies: "english", "english-teens", which adds ten
+    /// to nineteen, "german", "french" and "spanish".
+    pub fn named(name: &str) -> Option<Vocabulary> {
+        let words: Vec<_> = match name {
+            "english" => ENGLISH.to_vec(),
+            "english-teens" => [ENGLISH, ENGLISH_TEENS].concat(),
+            "german" => GERMAN.to_vec(),
+            "french" => FRENCH.to_vec(),
+            "spanish" => SPANISH.to_vec(),
+            _ => return None,
+        };
+
+        Some(Vocabulary::new(words))
+    }
+
+    pub fn english() -> Vocabulary {
+        Vocabulary::new(ENGLISH.iter().copied())
+    }
+
+    pub fn new<S: Into<String>>(words: impl IntoIterator<Item = (S, u32)>) -> Vocabulary {
+        Vocabulary {
+            words: words.into_iter().map(|(word, value)| (word.into(), value)).collect(),
+        }
+    }
+
+    /// Parses a TOML table of words and the numbers they stand for, like
+    /// `eins = 1`.
+    pub fn parse(text: &str) -> Result<Vocabulary> {
+        let table: toml::Table = text.parse()?;
+        let mut words = Vec::new();
 
-use regex::Regex;
+        for (word, value) in &table {
+            if word.is_empty() {
+                bail!("Words can't be empty");
+            }
+            let value = value.as_integer()
+                .and_then(|n| u32::try_from(n).ok())
+                .ok_or_else(|| anyhow!("Word {:?} should stand for a non-negative integer", word))?;
+
+            words.push((word.clone(), value));
+        }
+
+        Ok(Vocabulary { words })
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> Result<Vocabulary> {
+        let path = path.as_ref();
+        let text = std::fs::read_to_string(path).with_context(|| format!("Couldn't read {}", path.display()))?;
+
+        Vocabulary::parse(&text).with_context(|| format!("Couldn't parse {}", path.display()))
+    }
+
+    /// The vocabulary picked by `AOC_DAY1_VOCABULARY`.
+    pub fn from_env() -> Result<Vocabulary, Day1Error> {
+        match std::env::var_os(VOCABULARY_VAR) {
+            None => Ok(Vocabulary::english()),
+            Some(value) => match value.to_str().and_then(Vocabulary::named) {
+                Some(vocabulary) => Ok(vocabulary),
+                None => Vocabulary::load(value).map_err(Day1Error::Vocabulary),
+            },
+        }
+    }
 
-type Data = String;
+    fn matcher(&self) -> Matcher {
+        let words = self.words.iter().map(|(word, value)| (word.as_str(), *value));
+        Matcher::new(DIGITS.iter().copied().chain(words))
+    }
+}
 
 
 #[aoc_generator(day1)]
-fn input_generator(input: &str) -> Result<Vec<Data>> {
+pub fn input_generator(input: &str) -> Result<Vec<Data>> {
     Ok(input.lines().filter(|l| !l.is_empty()).map(|l| l.to_owned()).collect())
 }
 
 #[aoc(day1, part1)]
-fn solve_part1(input: &[Data]) -> u32 {
+pub fn solve_part1(input: &[Data]) -> Result<u32, Day1Error> {
     let mut total = 0;
-    for line in input {
-        let first_char = line.chars().filter(|c| c.is_digit(10)).next();
-        let last_char = line.chars().rev().filter(|c| c.is_digit(10)).next();
+    for (i, line) in input.iter().enumerate() {
+        total += digits_part1(line)
+            .ok_or_else(|| Day1Error::NoDigits { line: i + 1, text: line.clone() })?;
+    }
 
-        let first_num = first_char.unwrap().to_digit(10).unwrap();
-        let last_num = last_char.unwrap().to_digit(10).unwrap();
+    Ok(total)
+}
+
+/// The first and last digit of `line` as a two-digit number.
+fn digits_part1(line: &str) -> Option<u32> {
+    let first_num = line.chars().find_map(|c| c.to_digit(10))?;
+    let last_num = line.chars().rev().find_map(|c| c.to_digit(10))?;
+
+    Some(first_num * 10 + last_num)
+}
 
-        let val = first_num * 10 + last_num;
-        total += val;
+fn stream_part1(input: impl BufRead) -> Result<u64> {
+    let mut total = 0;
+    for line in stream_lines(input) {
+        let (line, text) = line?;
+        total += digits_part1(&text).ok_or(Day1Error::NoDigits { line, text })? as u64;
     }
 
-    total
+    Ok(total)
 }
 
 #[aoc(day1, part2)]
-fn solve_part2(input: &[Data]) -> u32 {
-    let regex_patterns = [r"([1-9])", "(one)", "(two)", "(three)", "(four)", "(five)", "(six)", "(seven)", "(eight)", "(nine)"];
-    let regexes = regex_patterns.map(|pat| Regex::new(pat).unwrap());
-    let mut total = 0;
+pub fn solve_part2(input: &[Data]) -> Result<u32, Day1Error> {
+    solve_part2_with(input, &Vocabulary::from_env()?)
+}
 
-    for line in input {
-        let mut first_pair = None;
-        let mut last_pair = None;
-        for re in &regexes {
-            let mut captures = re.captures_iter(line);
-            let first_capture = captures.next();
-            let last_capture = captures.last();
-
-            let first_match = first_capture.map(|capture| capture.get(0).unwrap());
-            let last_match = match last_capture {
-                Some(capture) => Some(capture.get(0).unwrap()),
-                None => first_match,
-            };
-
-            if let Some(first_match) = first_match {
-                let first_is_less = match first_pair {
-                    None => true,
-                    Some((start, _)) => start > first_match.start(),
-                };
-                if first_is_less {
-                    first_pair = Some((first_match.start(), str_to_num(first_match.as_str())));
-                }
-            }
-            if let Some(last_match) = last_match {
-                let last_is_more = match last_pair {
-                    None => true,
-                    Some((end, _)) => end < last_match.end(),
-                };
-                if last_is_more {
-                    last_pair = Some((last_match.end(), str_to_num(last_match.as_str())));
-                }
-            }
-        }
+pub fn solve_part2_with(input: &[Data], vocabulary: &Vocabulary) -> Result<u32, Day1Error> {
+    let matcher = vocabulary.matcher();
+    let mut total = 0;
 
-        let val = first_pair.unwrap().1 * 10 + last_pair.unwrap().1;
-        total += val;
+    for (i, line) in input.iter().enumerate() {
+        total += digits_part2(&matcher, line)
+            .ok_or_else(|| Day1Error::NoDigits { line: i + 1, text: line.clone() })?;
     }
-    total
+    Ok(total)
+}
+
+/// The first and last number in `line`, digits or spelled out, as a two-digit number.
+fn digits_part2(matcher: &Matcher, line: &str) -> Option<u32> {
+    Some(calibration_value(matcher.first(line)?, matcher.last(line)?))
 }
 
-fn str_to_num(num_str: &str) -> u32 {
-    match num_str {
-        "1" => 1,
-        "2" => 2,
-        "3" => 3,
-        "4" => 4,
-        "5" => 5,
-        "6" => 6,
-        "7" => 7,
-        "8" => 8,
-        "9" => 9,
-        "one" => 1,
-        "two" => 2,
-        "three" => 3,
-        "four" => 4,
-        "five" => 5,
-        "six" => 6,
-        "seven" => 7,
-        "eight" => 8,
-        "nine" => 9,
-        _ => panic!("unexpected input: {}", num_str),
+fn stream_part2(input: impl BufRead) -> Result<u64> {
+    let matcher = Vocabulary::from_env()?.matcher();
+    let mut total = 0;
+    for line in stream_lines(input) {
+        let (line, text) = line?;
+        total += digits_part2(&matcher, &text).ok_or(Day1Error::NoDigits { line, text })? as u64;
     }
+
+    Ok(total)
+}
+
+crate::registry::solutions! {
+    (1, 1, input_generator, solve_part1),
+    (1, 2, input_generator, solve_part2),
+}
+
+crate::registry::streaming_solutions! {
+    (1, 1, stream_part1),
+    (1, 2, stream_part2),
 }
 
 #[cfg(test)]
@@ -125,7 +218,7 @@ treb7uchet
     #[test]
     fn test_part1_example() {
         let input = super::input_generator(TEST_INPUT1).unwrap();
-        let result = super::solve_part1(&input);
+        let result = super::solve_part1(&input).unwrap();
 
         assert_eq!(result, 142);
     }
@@ -133,8 +226,57 @@ treb7uchet
     #[test]
     fn test_part2_example() {
         let input = super::input_generator(TEST_INPUT2).unwrap();
-        let result = super::solve_part2(&input);
+        let result = super::solve_part2(&input).unwrap();
 
         assert_eq!(result, 281 + 77 + 82);
     }
+
+    #[test]
+    fn test_vocabularies() {
+        let input = super::input_generator("zweiundvierzig\nxdeuxtroisx\nseisdos\n").unwrap();
+
+        let german = super::Vocabulary::named("german").unwrap();
+        let french = super::Vocabulary::named("french").unwrap();
+        let spanish = super::Vocabulary::named("spanish").unwrap();
+        assert_eq!(super::solve_part2_with(&input[..1], &german).unwrap(), 24);
+        assert_eq!(super::solve_part2_with(&input[1..2], &french).unwrap(), 23);
+        assert_eq!(super::solve_part2_with(&input[2..], &spanish).unwrap(), 62);
+        assert!(super::solve_part2_with(&input, &german).is_err());
+
+        let input = super::input_generator(TEST_INPUT2).unwrap();
+        assert_eq!(super::solve_part2_with(&input, &super::Vocabulary::english()).unwrap(), 281 + 77 + 82);
+    }
+
+    #[test]
+    fn test_multi_digit_words() {
+        let teens = super::Vocabulary::named("english-teens").unwrap();
+
+        let input = super::input_generator("twelve\nxseventeen\neighteenine\n4eleven\n").unwrap();
+        assert_eq!(super::solve_part2_with(&input, &teens).unwrap(), 12 + 17 + 19 + 41);
+
+        let input = super::input_generator("xseventeen\n").unwrap();
+        assert_eq!(super::solve_part2_with(&input, &super::Vocabulary::english()).unwrap(), 77);
+    }
+
+    #[test]
+    fn test_vocabulary_file() {
+        let vocabulary = super::Vocabulary::parse("eins = 1\n\"fünf\" = 5\nzehn = 10\n").unwrap();
+        let input = super::input_generator("fünfzehn\n").unwrap();
+        assert_eq!(super::solve_part2_with(&input, &vocabulary).unwrap(), 50);
+
+        assert!(super::Vocabulary::parse("eins = -1\n").is_err());
+        assert!(super::Vocabulary::parse("eins = \"1\"\n").is_err());
+        assert!(super::Vocabulary::parse("\"\" = 1\n").is_err());
+        assert!(super::Vocabulary::named("klingon").is_none());
+    }
+
+    #[test]
+    fn test_line_without_digits() {
+        let input = super::input_generator("1abc2\nnothing\n").unwrap();
+
+        let error = super::solve_part1(&input).unwrap_err();
+        assert_eq!(error.to_string(), "Line 2 has no digits: \"nothing\"");
+
+        assert!(super::solve_part2(&input).is_err());
+    }
 }
@@ -2,20 +2,31 @@ use aoc_runner_derive::aoc_generator;
 use aoc_runner_derive::aoc;
 
 use anyhow::Result;
-use ndarray::Array2;
-use ndarray::ShapeBuilder;
+use anyhow::anyhow;
 
 use std::collections::HashMap;
 use std::collections::HashSet;
 
-type Data = (Pos, ndarray::Array2<Adjacencies>);
+use thiserror::Error;
+
+use crate::grid::Direction;
+use crate::grid::Grid;
+use crate::grid::Pos;
+
+pub type Data = (Pos, Grid<Adjacencies>);
+
+#[derive(Debug, Error)]
+pub enum Day10Error {
+    #[error("The start at row {}, column {} isn't part of a loop", .0.row + 1, .0.col + 1)]
+    NoLoop(Pos),
+}
 
 #[derive(Debug, Clone, Copy)]
-struct Adjacencies {
-    up: bool,
-    right: bool,
-    down: bool,
-    left: bool,
+pub struct Adjacencies {
+    pub up: bool,
+    pub right: bool,
+    pub down: bool,
+    pub left: bool,
 }
 
 impl From<[bool; 4]> for Adjacencies {
@@ -46,72 +57,8 @@ impl Adjacencies {
     }
 }
 
-#[derive(Debug, Clone, Copy)]
-#[derive(PartialEq, Eq)]
-#[derive(Hash)]
-struct Pos {
-    i: usize,
-    j: usize,
-}
-
-impl Pos {
-    fn try_move(&self, direction: Direction, bounds: (usize, usize)) -> Option<Self> {
-        use Direction::*;
-        match direction {
-            Up => {
-                if self.j > 0 {
-                    Some(Pos {
-                        i: self.i,
-                        j: self.j - 1,
-                    })
-                } else {
-                    None
-                }
-            },
-            Right => {
-                if self.i < bounds.0 - 1 {
-                    Some(Pos {
-                        i: self.i + 1,
-                        j: self.j,
-                    })
-                } else {
-                    None
-                }
-            },
-            Down => {
-                if self.j < bounds.1 - 1 {
-                    Some(Pos {
-                        i: self.i,
-                        j: self.j + 1,
-                    })
-                } else {
-                    None
-                }
-            },
-            Left => {
-                if self.i > 0 {
-                    Some(Pos {
-                        i: self.i - 1,
-                        j: self.j,
-                    })
-                } else {
-                    None
-                }
-            },
-        }
-    }
-}
-
-impl std::ops::Index<Pos> for Array2<Adjacencies> {
-    type Output = <Self as std::ops::Index<ndarray::Ix2>>::Output;
-
-    fn index(&self, index: Pos) -> &Self::Output {
-        &self[(index.i, index.j)]
-    }
-}
-
 #[aoc_generator(day10)]
-fn input_generator(input: &str) -> Result<Data> {
+pub fn input_generator(input: &str) -> Result<Data> {
     let character_map = {
         let mut tmp: HashMap<char, Adjacencies> = HashMap::new();
         tmp.insert('|', [true, false, true, false].into());
@@ -126,52 +73,39 @@ fn input_generator(input: &str) -> Result<Data> {
         tmp
     };
 
-    let mut rows = Vec::new();
-    let mut rowlength = None;
-
     let mut start = None;
 
-    for (j, line) in input.lines().filter(|line| !line.is_empty()).enumerate()  {
-        let mut row = Vec::new();
-        for (i, c) in line.char_indices() {
-            if c == 'S' {
-                start = Some(Pos {
-                    i,
-                    j,
-                });
-            }
-            row.push(character_map[&c]);
+    let grid = Grid::parse_indexed(input, |pos, c| {
+        if c == 'S' {
+            start = Some(pos);
         }
+        character_map.get(&c).cloned()
+            .ok_or_else(|| anyhow!("Invalid character {:?}", c))
+    })?;
 
-        if let None = rowlength { rowlength = Some(row.len()); }
-        rows.extend(row);
-    }
-
-    let rowlength = rowlength.unwrap();
-    let shape = (rowlength, rows.len() / rowlength).strides((1, rowlength));
+    let start = start.ok_or_else(|| anyhow!("No start position"))?;
 
-    Ok((start.unwrap(), Array2::from_shape_vec(shape, rows).unwrap()))
+    Ok((start, grid))
 }
 
 #[aoc(day10, part1)]
-fn solve_part1(input: &Data) -> usize {
+pub fn solve_part1(input: &Data) -> Result<usize, Day10Error> {
     let start = input.0;
 
-    let array = &input.1;
-    let bounds = (array.shape()[0], array.shape()[1]);
+    let grid = &input.1;
 
-    let neighbors = get_neighbors(start, array);
+    let neighbors = grid.neighbors(start);
 
     for (mut incoming_direction, neighbor) in neighbors {
         let mut current_position = neighbor;
         let mut length = 1;
 
         while current_position != start {
-            let cell = array[current_position];
+            let cell = grid[current_position];
             let next_direction = cell.other_direction(incoming_direction);
             
             if let Some(next_direction) = next_direction {
-                let next_position = current_position.try_move(next_direction, bounds);
+                let next_position = grid.try_move(current_position, next_direction);
                 if let Some(next_position) = next_position {
                     current_position = next_position;
                     incoming_direction = next_direction;
@@ -186,58 +120,20 @@ fn solve_part1(input: &Data) -> usize {
         }
 
         if current_position == start {
-            return length / 2;
-        }
-    }
-
-    0
-}
-
-#[derive(Debug, Clone, Copy)]
-#[derive(PartialEq, Eq)]
-enum Direction {
-    Up,
-    Right,
-    Down,
-    Left,
-}
-
-impl Direction {
-    fn invert(self) -> Self {
-        use Direction::*;
-
-        match self {
-            Up => Down,
-            Down => Up,
-            Right => Left,
-            Left => Right,
+            return Ok(length / 2);
         }
     }
-}
 
-const DIRECTIONS: [Direction; 4] = [
-    Direction::Up,
-    Direction::Right,
-    Direction::Down,
-    Direction::Left,
-];
-
-fn get_neighbors(location: Pos, array: &Array2<Adjacencies>) -> impl Iterator<Item = (Direction, Pos)> {
-    let bounds = (array.shape()[0], array.shape()[1]);
-    DIRECTIONS.into_iter()
-        .filter_map(move |direction| {
-            location.try_move(direction, bounds).map(|neighbor| (direction, neighbor))
-        })
+    Err(Day10Error::NoLoop(start))
 }
 
 #[aoc(day10, part2)]
-fn solve_part2(input: &Data) -> usize {
-    let mut path = solve_maze(input);
+pub fn solve_part2(input: &Data) -> Result<usize, Day10Error> {
+    let mut path = solve_maze(input)?;
 
     let boundary: HashSet<_> = path.iter().map(|(_, pos, _)| pos).cloned().collect();
 
     let winding_number = calculate_winding_number(&path);
-    dbg!{winding_number};
     if winding_number != 4 {
         path.reverse();
         path.iter_mut().for_each(|point| {
@@ -248,7 +144,6 @@ fn solve_part2(input: &Data) -> usize {
     let mut interior = HashSet::new();
 
     use Direction::*;
-    let bounds = (input.1.shape()[0], input.1.shape()[1]);
     for point in &path {
         let directions = match (point.0, point.2) {
             (Up, Up) => [Right].iter(),
@@ -262,7 +157,7 @@ fn solve_part2(input: &Data) -> usize {
             _ => [].iter(),
         };
         for &d in directions {
-            let potential_interior_pos = point.1.try_move(d, bounds);
+            let potential_interior_pos = input.1.try_move(point.1, d);
 
             if let Some(pos) = potential_interior_pos {
                 if !boundary.contains(&pos) {
@@ -277,7 +172,7 @@ fn solve_part2(input: &Data) -> usize {
     while !frontier.is_empty() {
         let pos = frontier.pop().unwrap();
 
-        for (_, neighbor) in get_neighbors(pos, &input.1) {
+        for (_, neighbor) in input.1.neighbors(pos) {
             if !interior.contains(&neighbor) && !boundary.contains(&neighbor) {
                 frontier.push(neighbor);
                 interior.insert(neighbor);
@@ -285,7 +180,7 @@ fn solve_part2(input: &Data) -> usize {
         }
     }
 
-    interior.len()
+    Ok(interior.len())
 }
 
 fn calculate_winding_number(path: &[(Direction, Pos, Direction)]) -> i32 {
@@ -309,13 +204,12 @@ fn calculate_winding_number(path: &[(Direction, Pos, Direction)]) -> i32 {
     }).sum()
 }
 
-fn solve_maze(input: &Data) -> Vec<(Direction, Pos, Direction)> {
+fn solve_maze(input: &Data) -> Result<Vec<(Direction, Pos, Direction)>, Day10Error> {
     let start = input.0;
 
-    let array = &input.1;
-    let bounds = (array.shape()[0], array.shape()[1]);
+    let grid = &input.1;
 
-    let neighbors = get_neighbors(start, array);
+    let neighbors = grid.neighbors(start);
 
     for (incoming_direction, neighbor) in neighbors {
         let mut current_position = neighbor;
@@ -324,11 +218,11 @@ fn solve_maze(input: &Data) -> Vec<(Direction, Pos, Direction)> {
         let mut history = Vec::new();
 
         while current_position != start {
-            let cell = array[current_position];
+            let cell = grid[current_position];
             let next_direction = cell.other_direction(current_direction);
             
             if let Some(next_direction) = next_direction {
-                let next_position = current_position.try_move(next_direction, bounds);
+                let next_position = grid.try_move(current_position, next_direction);
                 if let Some(next_position) = next_position {
                     history.push((current_direction, current_position, next_direction));
                     current_position = next_position;
@@ -344,11 +238,16 @@ fn solve_maze(input: &Data) -> Vec<(Direction, Pos, Direction)> {
         history.push((current_direction, current_position, incoming_direction));
 
         if current_position == start {
-            return history;
+            return Ok(history);
         }
     }
 
-    unreachable!()
+    Err(Day10Error::NoLoop(start))
+}
+
+crate::registry::solutions! {
+    (10, 1, input_generator, solve_part1),
+    (10, 2, input_generator, solve_part2),
 }
 
 #[cfg(test)]
@@ -400,12 +299,12 @@ L--J.L7...LJS7F-7L7.
     #[test]
     fn test_part1_example() {
         let input = super::input_generator(TEST_INPUT_CIRCLE).unwrap();
-        let result = super::solve_part1(&input);
+        let result = super::solve_part1(&input).unwrap();
 
         assert_eq!(result, 4);
 
         let input = super::input_generator(TEST_INPUT_COMPLICATED).unwrap();
-        let result = super::solve_part1(&input);
+        let result = super::solve_part1(&input).unwrap();
 
         assert_eq!(result, 8);
     }
@@ -413,13 +312,22 @@ L--J.L7...LJS7F-7L7.
     #[test]
     fn test_part2_example() {
         let input = super::input_generator(TEST_INPUT_1_TILE).unwrap();
-        let result = super::solve_part2(&input);
+        let result = super::solve_part2(&input).unwrap();
 
         assert_eq!(result, 4);
 
         let input = super::input_generator(TEST_INPUT_CORNERS).unwrap();
-        let result = super::solve_part2(&input);
+        let result = super::solve_part2(&input).unwrap();
 
         assert_eq!(result, 8);
     }
+
+    #[test]
+    fn test_no_loop() {
+        let input = super::input_generator(".....\n.S-7.\n.|...\n.....\n").unwrap();
+
+        let error = super::solve_part1(&input).unwrap_err();
+        assert_eq!(error.to_string(), "The start at row 2, column 2 isn't part of a loop");
+        assert!(super::solve_part2(&input).is_err());
+    }
 }
@@ -7,11 +7,21 @@ use anyhow::anyhow;
 use ndarray::Array2;
 use ndarray::ShapeBuilder;
 
-type Data = (Vec<(usize, usize)>, Array2<bool>);
+use thiserror::Error;
+
+pub type Data = (Vec<(usize, usize)>, Array2<bool>);
+
+#[derive(Debug, Error)]
+pub enum Day11Error {
+    #[error("The image is empty")]
+    EmptyImage,
+    #[error("Row {row} is {length} pixels long, but the first row is {expected}")]
+    RaggedRow { row: usize, length: usize, expected: usize },
+}
 
 
 #[aoc_generator(day11)]
-fn input_generator(input: &str) -> Result<Data> {
+pub fn input_generator(input: &str) -> Result<Data> {
     let mut rows = Vec::new();
     let mut rowlength = None;
 
@@ -33,19 +43,22 @@ fn input_generator(input: &str) -> Result<Data> {
             row.push(cell);
         }
 
-        if let None = rowlength { rowlength = Some(row.len()); }
+        let expected = *rowlength.get_or_insert(row.len());
+        if row.len() != expected {
+            return Err(Day11Error::RaggedRow { row: j + 1, length: row.len(), expected }.into());
+        }
         rows.extend(row);
     }
 
-    let rowlength = rowlength.unwrap();
+    let rowlength = rowlength.filter(|&length| length > 0).ok_or(Day11Error::EmptyImage)?;
     let shape = (rowlength, rows.len() / rowlength).strides((1, rowlength));
 
-    Ok((galaxies, Array2::from_shape_vec(shape, rows).unwrap()))
+    Ok((galaxies, Array2::from_shape_vec(shape, rows)?))
 }
 
 #[aoc(day11, part1)]
-fn solve_part1(input: &Data) -> usize {
-    solve_multiplied_expansion(input, 2)
+pub fn solve_part1(input: &Data) -> Result<usize, Day11Error> {
+    Ok(solve_multiplied_expansion(input, 2))
 }
 
 fn manhattan_distance(a: &(usize, usize), b: &(usize, usize)) -> usize {
@@ -53,8 +66,8 @@ fn manhattan_distance(a: &(usize, usize), b: &(usize, usize)) -> usize {
 }
 
 #[aoc(day11, part2)]
-fn solve_part2(input: &Data) -> usize {
-    solve_multiplied_expansion(input, 1_000_000)
+pub fn solve_part2(input: &Data) -> Result<usize, Day11Error> {
+    Ok(solve_multiplied_expansion(input, 1_000_000))
 }
 
 fn solve_multiplied_expansion((galaxies, array): &Data, multiply: usize) -> usize {
@@ -100,6 +113,11 @@ fn solve_multiplied_expansion((galaxies, array): &Data, multiply: usize) -> usiz
     distance
 }
 
+crate::registry::solutions! {
+    (11, 1, input_generator, solve_part1),
+    (11, 2, input_generator, solve_part2),
+}
+
 #[cfg(test)]
 mod test {
     const TEST_INPUT: &'static str =
@@ -118,7 +136,7 @@ r#"
     #[test]
     fn test_part1_example() {
         let input = super::input_generator(TEST_INPUT).unwrap();
-        let result = super::solve_part1(&input);
+        let result = super::solve_part1(&input).unwrap();
 
         assert_eq!(result, 374);
     }
@@ -134,4 +152,13 @@ r#"
 
         assert_eq!(result, 8410);
     }
+
+    #[test]
+    fn test_invalid_image() {
+        let error = super::input_generator("#..\n.#\n").unwrap_err();
+        assert_eq!(error.to_string(), "Row 2 is 2 pixels long, but the first row is 3");
+
+        let error = super::input_generator("\n").unwrap_err();
+        assert_eq!(error.to_string(), "The image is empty");
+    }
 }
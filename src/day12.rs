@@ -3,169 +3,157 @@ use aoc_runner_derive::aoc;
 
 use anyhow::anyhow;
 
-use std::collections::HashMap;
+use std::io::BufRead;
 
-type Data = Record;
+use thiserror::Error;
 
-#[derive(Debug, Clone)]
-struct Record {
-    row: Vec<SpringCondition>,
-    groups: Vec<usize>,
-}
+use crate::algorithms::day12::SpringCondition;
+use crate::algorithms::day12::possible_arrangements;
+use crate::algorithms::day12::possible_arrangements_memoized;
+use crate::par;
+use crate::parse::stream_lines;
 
+pub type Data = Record;
+
+#[derive(Debug, Error)]
+pub enum Day12Error {
+    #[error("Record {record} has a group of zero damaged springs")]
+    EmptyGroup { record: usize },
+    #[error("The number of arrangements overflows")]
+    Overflow,
+}
 
-#[derive(Debug, Clone, Copy)]
-#[derive(PartialEq, Eq)]
-#[derive(Hash)]
-enum SpringCondition {
-    Operational,
-    Damaged,
-    Unknown,
+#[derive(Debug, Clone)]
+pub struct Record {
+    pub row: Vec<SpringCondition>,
+    pub groups: Vec<usize>,
 }
 
 
 #[aoc_generator(day12)]
-fn input_generator(input: &str) -> anyhow::Result<Vec<Data>> {
+pub fn input_generator(input: &str) -> anyhow::Result<Vec<Data>> {
     input.lines()
         .filter(|line| !line.is_empty())
-        .map(|line| {
-            let (row_str, group_str) = line.split_once(' ').ok_or(anyhow!("Expected two parts"))?;
-            let row = row_str.chars()
-                .map(|c| {
-                    use SpringCondition::*;
-                    match c {
-                        '.' => Ok(Operational),
-                        '?' => Ok(Unknown),
-                        '#' => Ok(Damaged),
-                        _ => Err(anyhow!("Invalid character")),
-                    }
-                })
-                .collect::<Result<_, _>>()?;
-
-            let groups = group_str.split(',')
-                .map(|num_str| num_str.parse::<usize>())
-                .collect::<Result<_, _>>()?;
-
-            Ok(Record {
-                row,
-                groups,
-            })
-        })
+        .map(parse_record)
         .collect()
 
 }
 
-#[aoc(day12, part1)]
-fn solve_part1(input: &[Data]) -> usize {
-    input.iter()
-        .map(|record| possible_arrangements(&record.row, &record.groups))
-        .sum()
-}
-
-fn possible_arrangements(row: &[SpringCondition], groups: &[usize]) -> usize {
-    if groups.is_empty() {
-        if row.iter().all(|&condition| condition != SpringCondition::Damaged) {
-            1
-        } else {
-            0
-        }
-    } else {
-        if minimum_group_length(groups) > row.len() {
-            0
-        } else {
-            let mut total = 0;
-            if group_fits(groups[0], row) {
-                if row.len() == groups[0] {
-                    total += possible_arrangements(&[], &groups[1..]);
-                } else {
-                    total += possible_arrangements(&row[groups[0] + 1..], &groups[1..]);
-                }
+fn parse_record(line: &str) -> anyhow::Result<Record> {
+    let (row_str, group_str) = line.split_once(' ').ok_or(anyhow!("Expected two parts"))?;
+    let row = row_str.chars()
+        .map(|c| {
+            use SpringCondition::*;
+            match c {
+                '.' => Ok(Operational),
+                '?' => Ok(Unknown),
+                '#' => Ok(Damaged),
+                _ => Err(anyhow!("Invalid character")),
             }
+        })
+        .collect::<Result<_, _>>()?;
 
-            if row[0] != SpringCondition::Damaged {
-                total += possible_arrangements(&row[1..], groups);
-            }
+    let groups = group_str.split(',')
+        .map(|num_str| num_str.parse::<usize>())
+        .collect::<Result<_, _>>()?;
+
+    Ok(Record {
+        row,
+        groups,
+    })
+}
 
-            total
+/// Sums `count` over each record of `input`, one record at a time.
+fn stream_sum(input: impl BufRead, count: fn(Record) -> usize) -> anyhow::Result<usize> {
+    let mut total = 0usize;
+    for line in stream_lines(input) {
+        let (line, text) = line?;
+        let record = parse_record(&text)?;
+        if record.groups.contains(&0) {
+            return Err(Day12Error::EmptyGroup { record: line }.into());
         }
+
+        total = total.checked_add(count(record)).ok_or(Day12Error::Overflow)?;
     }
+
+    Ok(total)
+}
+
+fn stream_part1(input: impl BufRead) -> anyhow::Result<usize> {
+    stream_sum(input, |record| possible_arrangements(&record.row, &record.groups))
 }
 
-fn minimum_group_length(groups: &[usize]) -> usize {
-    groups.iter().cloned().sum::<usize>() + groups.len() - 1
+fn stream_part2(input: impl BufRead) -> anyhow::Result<usize> {
+    stream_sum(input, |record| {
+        let record = unfold(&record);
+        possible_arrangements_memoized(&record.row, &record.groups)
+    })
 }
 
-fn group_fits(n: usize, row: &[SpringCondition]) -> bool {
-    if row.len() < n {
-        false
-    } else if row.len() == n {
-        row.iter().take(n).all(|&condition| condition != SpringCondition::Operational)
-    } else {
-        row.iter().take(n).all(|&condition| condition != SpringCondition::Operational) &&
-            row[n] != SpringCondition::Damaged
+/// Zero-length groups would let the recursion place a group without consuming any springs.
+fn check_records(input: &[Data]) -> Result<(), Day12Error> {
+    match input.iter().position(|record| record.groups.contains(&0)) {
+        Some(i) => Err(Day12Error::EmptyGroup { record: i + 1 }),
+        None => Ok(()),
     }
 }
 
-#[aoc(day12, part2)]
-fn solve_part2(input: &[Data]) -> usize {
+#[aoc(day12, part1)]
+pub fn solve_part1(input: &[Data]) -> Result<usize, Day12Error> {
+    check_records(input)?;
+
     input.iter()
-        .map(|record| {
-            let mut row = Vec::with_capacity(record.row.len() * 5);
-            for i in 0..5 {
-                if i != 0 { row.push(SpringCondition::Unknown); }
-                row.extend(&record.row);
-            }
+        .map(|record| possible_arrangements(&record.row, &record.groups))
+        .try_fold(0usize, |total, count| total.checked_add(count).ok_or(Day12Error::Overflow))
+}
 
-            let mut groups = Vec::with_capacity(record.groups.len() * 5);
-            for _ in 0..5 {
-                groups.extend(&record.groups);
-            }
-            Record {
-                row,
-                groups,
-            }
-        })
-        .map(|record| possible_arrangements_memoized(&record.row, &record.groups, &mut HashMap::new()))
-        .sum()
+#[aoc(day12, part2)]
+pub fn solve_part2(input: &[Data]) -> Result<usize, Day12Error> {
+    check_records(input)?;
+
+    let records = input.iter().map(unfold).collect();
+
+    par::map(records, |record| possible_arrangements_memoized(&record.row, &record.groups))
+        .into_iter()
+        .try_fold(0usize, |total, count| total.checked_add(count).ok_or(Day12Error::Overflow))
 }
 
-fn possible_arrangements_memoized<'a>(row: &'a[SpringCondition], groups: &'a[usize], subproblems: & mut HashMap<(&'a [SpringCondition], &'a [usize]), usize>) -> usize {
-    if subproblems.contains_key(&(row, groups)) {
-        return subproblems[&(row, groups)];
+/// The record repeated five times, with an unknown spring between each copy.
+fn unfold(record: &Record) -> Record {
+    let mut row = Vec::with_capacity(record.row.len() * 5);
+    for i in 0..5 {
+        if i != 0 { row.push(SpringCondition::Unknown); }
+        row.extend(&record.row);
     }
-    let result = if groups.is_empty() {
-        if row.iter().all(|&condition| condition != SpringCondition::Damaged) {
-            1
-        } else {
-            0
-        }
-    } else {
-        if minimum_group_length(groups) > row.len() {
-            0
-        } else {
-            let mut total = 0;
-            if group_fits(groups[0], row) {
-                if row.len() == groups[0] {
-                    total += possible_arrangements_memoized(&[], &groups[1..], subproblems);
-                } else {
-                    total += possible_arrangements_memoized(&row[groups[0] + 1..], &groups[1..], subproblems);
-                }
-            }
 
-            if row[0] != SpringCondition::Damaged {
-                total += possible_arrangements_memoized(&row[1..], groups, subproblems);
-            }
+    let mut groups = Vec::with_capacity(record.groups.len() * 5);
+    for _ in 0..5 {
+        groups.extend(&record.groups);
+    }
+    Record {
+        row,
+        groups,
+    }
+}
 
-            total
-        }
-    };
-    subproblems.insert((row, groups), result);
-    result
+crate::registry::solutions! {
+    (12, 1, input_generator, solve_part1),
+    (12, 2, input_generator, solve_part2),
 }
 
+crate::registry::streaming_solutions! {
+    (12, 1, stream_part1),
+    (12, 2, stream_part2),
+}
 
 #[cfg(test)]
 mod test {
+    #[cfg(not(target_arch = "wasm32"))]
+    use proptest::prelude::*;
+
+    #[cfg(not(target_arch = "wasm32"))]
+    use super::SpringCondition;
+
     const TEST_INPUT: &'static str =
 r#"
 ???.### 1,1,3
@@ -178,7 +166,7 @@ r#"
     #[test]
     fn test_part1_example() {
         let input = super::input_generator(TEST_INPUT).unwrap();
-        let result = super::solve_part1(&input);
+        let result = super::solve_part1(&input).unwrap();
 
         assert_eq!(result, 21);
     }
@@ -186,8 +174,91 @@ r#"
     #[test]
     fn test_part2_example() {
         let input = super::input_generator(TEST_INPUT).unwrap();
-        let result = super::solve_part2(&input);
+        let result = super::solve_part2(&input).unwrap();
 
         assert_eq!(result, 525152);
     }
+
+    #[test]
+    fn test_empty_group() {
+        let input = super::input_generator("???.### 1,1,3\n.??. 1,0\n").unwrap();
+
+        let error = super::solve_part1(&input).unwrap_err();
+        assert_eq!(error.to_string(), "Record 2 has a group of zero damaged springs");
+
+        assert!(super::solve_part2(&input).is_err());
+    }
+
+    /// Records built from a real row of springs with some of them hidden, so
+    /// every record has at least one arrangement.
+    #[cfg(not(target_arch = "wasm32"))]
+    fn record() -> impl Strategy<Value = String> {
+        proptest::collection::vec((any::<bool>(), proptest::bool::weighted(0.6)), 1..=14)
+            .prop_filter("needs a damaged spring", |springs| springs.iter().any(|&(damaged, _)| damaged))
+            .prop_map(|springs| {
+                let row: String = springs.iter()
+                    .map(|&(damaged, hidden)| match (damaged, hidden) {
+                        (_, true) => '?',
+                        (true, false) => '#',
+                        (false, false) => '.',
+                    })
+                    .collect();
+                let groups: Vec<_> = springs.split(|&(damaged, _)| !damaged)
+                    .filter(|group| !group.is_empty())
+                    .map(|group| group.len().to_string())
+                    .collect();
+
+                format!("{} {}", row, groups.join(","))
+            })
+    }
+
+    /// Counts arrangements by trying every assignment of the unknown springs.
+    #[cfg(not(target_arch = "wasm32"))]
+    fn brute_force_arrangements(row: &[SpringCondition], groups: &[usize]) -> usize {
+        let unknowns: Vec<_> = (0..row.len()).filter(|&i| row[i] == SpringCondition::Unknown).collect();
+
+        (0..1usize << unknowns.len())
+            .filter(|assignment| {
+                let damaged: Vec<_> = (0..row.len())
+                    .map(|i| match unknowns.iter().position(|&u| u == i) {
+                        Some(bit) => assignment & (1 << bit) != 0,
+                        None => row[i] == SpringCondition::Damaged,
+                    })
+                    .collect();
+                let found: Vec<_> = damaged.split(|&d| !d)
+                    .filter(|group| !group.is_empty())
+                    .map(|group| group.len())
+                    .collect();
+                found == groups
+            })
+            .count()
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    proptest! {
+        #[test]
+        fn test_arrangements_agree(record in record()) {
+            let input = super::input_generator(&record).unwrap();
+            let record = &input[0];
+
+            let expected = brute_force_arrangements(&record.row, &record.groups);
+            prop_assert!(expected >= 1);
+            prop_assert_eq!(super::possible_arrangements(&record.row, &record.groups), expected);
+            prop_assert_eq!(super::possible_arrangements_memoized(&record.row, &record.groups), expected);
+        }
+
+        #[test]
+        fn test_unfolded_arrangements_agree(record in record()) {
+            let input = super::input_generator(&record).unwrap();
+            let record = &input[0];
+
+            let mut row = record.row.clone();
+            row.push(SpringCondition::Unknown);
+            row.extend(&record.row);
+            let groups = [&record.groups[..], &record.groups[..]].concat();
+
+            let expected = super::possible_arrangements(&row, &groups);
+            prop_assert_eq!(super::possible_arrangements_memoized(&row, &groups), expected);
+        }
+    }
 }
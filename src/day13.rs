@@ -4,60 +4,49 @@ use aoc_runner_derive::aoc_generator;
 use aoc_runner_derive::aoc;
 
 use anyhow::Result;
-use anyhow::anyhow;
 
 use ndarray::Array2;
-use ndarray::ShapeBuilder;
 
-type Input = Vec<Data>;
-type InputRef = [Data];
-type Data = Pattern;
-type Pattern = Array2<Space>;
+use thiserror::Error;
+
+pub type Input = Vec<Data>;
+pub type InputRef = [Data];
+pub type Data = Pattern;
+pub type Pattern = Array2<Space>;
 
 #[derive(Debug, Clone, Copy)]
 #[derive(PartialEq, Eq)]
-enum Space {
+pub enum Space {
     Ash,
     Rock,
 }
 
+#[derive(Debug, Error)]
+pub enum Day13Error {
+    #[error("Pattern {0} has no line of reflection")]
+    NoReflection(usize),
+}
+
 
 #[aoc_generator(day13)]
-fn input_generator(input: &str) -> Result<Input> {
-    let (input, result) = parse_input(input).map_err(|err| err.to_owned())?;
-    if !input.is_empty() {
-        return Err(anyhow!("Had unparsed input after parsing: {}", input));
-    }
-    Ok(result)
+pub fn input_generator(input: &str) -> Result<Input> {
+    run_parser(input, parse_input)
 }
 
-use nom::IResult;
-use nom::combinator::opt;
-use nom::multi::many1;
-use nom::multi::separated_list1;
 use nom::bytes::complete::tag;
 use nom::branch::alt;
 
-fn parse_input(input: &str) -> IResult<&str, Input> {
-    let (input, _) = opt(tag("\n"))(input)?;
-    let (input, result) = separated_list1(tag("\n\n"), parse_pattern)(input)?;
-    let (input, _) = opt(tag("\n"))(input)?;
+use crate::parse::IResult;
+use crate::parse::blocks;
+use crate::parse::grid;
+use crate::parse::run_parser;
 
-    Ok((input, result))
+fn parse_input(input: &str) -> IResult<&str, Input> {
+    blocks(parse_pattern)(input)
 }
 
 fn parse_pattern(input: &str) -> IResult<&str, Pattern> {
-    let (input, row_data) = separated_list1(tag("\n"),
-        many1(parse_space)
-    )(input)?;
-
-    let cols = row_data[0].len();
-    let rows = row_data.len();
-
-    let data: Vec<_> = row_data.into_iter().flatten().collect();
-    let shape = (rows, cols).strides((cols, 1));
-
-    Ok((input, Array2::from_shape_vec(shape, data).unwrap()))
+    grid(parse_space)(input)
 }
 
 fn parse_space(input: &str) -> IResult<&str, Space> {
@@ -74,11 +63,11 @@ fn parse_space(input: &str) -> IResult<&str, Space> {
 }
 
 #[aoc(day13, part1)]
-fn solve_part1(input: &InputRef) -> usize {
+pub fn solve_part1(input: &InputRef) -> Result<usize, Day13Error> {
     let mut total = 0;
 
     'outer:
-    for arr in input {
+    for (pattern, arr) in input.iter().enumerate() {
         // Vertical reflection
         let shape = arr.shape();
         let cols = shape[1];
@@ -97,10 +86,10 @@ fn solve_part1(input: &InputRef) -> usize {
             }
         }
 
-        unreachable!();
+        return Err(Day13Error::NoReflection(pattern + 1));
     }
 
-    total
+    Ok(total)
 }
 
 fn flips_vertically(arr: &Array2<Space>, i: usize) -> bool {
@@ -137,11 +126,11 @@ fn flips_horizontally(arr: &Array2<Space>, j: usize) -> bool {
 }
 
 #[aoc(day13, part2)]
-fn solve_part2(input: &InputRef) -> usize {
+pub fn solve_part2(input: &InputRef) -> Result<usize, Day13Error> {
     let mut total = 0;
 
     'outer:
-    for arr in input {
+    for (pattern, arr) in input.iter().enumerate() {
         // Vertical reflection
         let shape = arr.shape();
         let cols = shape[1];
@@ -160,10 +149,10 @@ fn solve_part2(input: &InputRef) -> usize {
             }
         }
 
-        unreachable!();
+        return Err(Day13Error::NoReflection(pattern + 1));
     }
 
-    total
+    Ok(total)
 }
 
 fn flips_vertically_with_smudge(arr: &Array2<Space>, i: usize) -> bool {
@@ -207,6 +196,11 @@ fn flips_horizontally_with_smudge(arr: &Array2<Space>, j: usize) -> bool {
     smudges == 1
 }
 
+crate::registry::solutions! {
+    (13, 1, input_generator, solve_part1),
+    (13, 2, input_generator, solve_part2),
+}
+
 #[cfg(test)]
 mod test {
     const TEST_INPUT: &'static str =
@@ -231,7 +225,7 @@ r#"
     fn test_part1_example() {
         let input = super::input_generator(TEST_INPUT).unwrap();
         dbg!{&input};
-        let result = super::solve_part1(&input);
+        let result = super::solve_part1(&input).unwrap();
 
         assert_eq!(result, 405);
     }
@@ -239,8 +233,16 @@ r#"
     #[test]
     fn test_part2_example() {
         let input = super::input_generator(TEST_INPUT).unwrap();
-        let result = super::solve_part2(&input);
+        let result = super::solve_part2(&input).unwrap();
 
         assert_eq!(result, 400);
     }
+
+    #[test]
+    fn test_no_reflection() {
+        let input = super::input_generator("#.\n..\n").unwrap();
+
+        let error = super::solve_part1(&input).unwrap_err();
+        assert_eq!(error.to_string(), "Pattern 1 has no line of reflection");
+    }
 }
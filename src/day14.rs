@@ -2,65 +2,57 @@ use aoc_runner_derive::aoc_generator;
 use aoc_runner_derive::aoc;
 
 use anyhow::Result;
-use anyhow::anyhow;
 
 use ndarray::s;
 use ndarray::Array2;
 use ndarray::ArrayViewMut1;
-use ndarray::ShapeBuilder;
 
-use std::collections::VecDeque;
+use thiserror::Error;
 
-type Input = Data;
-type InputRef = Data;
-type Data = Dish;
-type Dish = Array2<Space>;
+use crate::cycle;
+use crate::render;
+use crate::render::Frame;
+use crate::render::Rgb;
+
+pub type Input = Data;
+pub type InputRef = Data;
+pub type Data = Dish;
+pub type Dish = Array2<Space>;
 
 #[derive(Debug, Clone, Copy)]
 #[derive(PartialEq, Eq)]
-enum Space {
+#[derive(Hash)]
+pub enum Space {
     Rounded,
     Cube,
     Empty
 }
 
+#[derive(Debug, Error)]
+pub enum Day14Error {
+    #[error("Couldn't write a frame: {0}")]
+    Render(#[from] std::io::Error),
+}
+
 
 #[aoc_generator(day14)]
-fn input_generator(input: &str) -> Result<Input> {
-    let (input, result) = parse_input(input).map_err(|err| err.to_owned())?;
-    if !input.is_empty() {
-        return Err(anyhow!("Had unparsed input after parsing: {}", input));
-    }
-    Ok(result)
+pub fn input_generator(input: &str) -> Result<Input> {
+    run_parser(input, parse_input)
 }
 
-use nom::IResult;
-use nom::combinator::opt;
-use nom::multi::many1;
-use nom::multi::separated_list1;
 use nom::bytes::complete::tag;
 use nom::branch::alt;
 
-fn parse_input(input: &str) -> IResult<&str, Input> {
-    let (input, _) = opt(tag("\n"))(input)?;
-    let (input, result) = parse_dish(input)?;
-    let (input, _) = opt(tag("\n"))(input)?;
+use crate::parse::IResult;
+use crate::parse::grid;
+use crate::parse::run_parser;
 
-    Ok((input, result))
+fn parse_input(input: &str) -> IResult<&str, Input> {
+    parse_dish(input)
 }
 
 fn parse_dish(input: &str) -> IResult<&str, Dish> {
-    let (input, row_data) = separated_list1(tag("\n"),
-        many1(parse_space)
-    )(input)?;
-
-    let cols = row_data[0].len();
-    let rows = row_data.len();
-
-    let data: Vec<_> = row_data.into_iter().flatten().collect();
-    let shape = (rows, cols).strides((cols, 1));
-
-    Ok((input, Array2::from_shape_vec(shape, data).unwrap()))
+    grid(parse_space)(input)
 }
 
 fn parse_space(input: &str) -> IResult<&str, Space> {
@@ -78,12 +70,13 @@ fn parse_space(input: &str) -> IResult<&str, Space> {
 }
 
 #[aoc(day14, part1)]
-fn solve_part1(input: &InputRef) -> usize {
+pub fn solve_part1(input: &InputRef) -> Result<usize, Day14Error> {
     let mut dish = input.clone();
 
     tilt_north(&mut dish);
+    render::dump("day14-part1", || frame(&dish))?;
 
-    north_load(&dish)
+    Ok(north_load(&dish))
 }
 
 fn tilt_north(dish: &mut Dish) {
@@ -151,64 +144,34 @@ fn spin_cycle(dish: &mut Dish) {
 }
 
 #[aoc(day14, part2)]
-fn solve_part2(input: &InputRef) -> usize {
-    let mut dish = input.clone();
-
-    let mut previous_dish_queue: VecDeque<Dish> = VecDeque::new();
-    for cycles in 0..1_000_000_000 {
-        let cycles = cycles + 1;
-        if previous_dish_queue.len() == 100 {
-            previous_dish_queue.rotate_right(1);
-            previous_dish_queue[0].assign(&dish);
-        } else {
-            previous_dish_queue.push_front(dish.clone());
-        }
-
+pub fn solve_part2(input: &InputRef) -> Result<usize, Day14Error> {
+    let history = cycle::record(input, |dish| {
+        let mut dish = dish.clone();
         spin_cycle(&mut dish);
+        dish
+    });
 
-        for (i, previous_dish) in previous_dish_queue.iter().enumerate() {
-            if previous_dish == dish {
-                let cycle_length = i + 1;
-
-                let cycle_offset = (1_000_000_000 - cycles) % cycle_length;
-
-                #[cfg(test)]
-                {
-                    dbg!(cycles, cycle_length, cycle_offset);
-
-                    for dish in previous_dish_queue.iter().take(cycle_length).rev() {
-                        pretty_print(dish);
-                        println!("{}", north_load(&dish));
-                    }
-
-                    pretty_print(&dish);
-                    println!("{}", north_load(&dish));
-
-                    dbg!{north_load(&dish)};
-                }
-
-                return north_load(&previous_dish_queue[(i + cycle_length - cycle_offset) % cycle_length]);
-            }
-        }
+    for (i, dish) in history.cycle_states().iter().enumerate() {
+        render::dump(&format!("day14-part2-{:03}", i), || frame(dish))?;
     }
 
-    north_load(&dish)
+    Ok(north_load(history.state_after(1_000_000_000)))
 }
 
-#[cfg(test)]
-fn pretty_print(dish: &Dish) {
-    for row in dish.rows() {
-        for c in row.iter() {
-            use Space::*;
-            let c = match c {
-                Rounded => 'O',
-                Cube => '#',
-                Empty => '.',
-            };
-            print!("{}", c);
+fn frame(dish: &Dish) -> Frame {
+    Frame::new(&dish.clone().into(), |space| {
+        use Space::*;
+        match space {
+            Rounded => Rgb::ORANGE,
+            Cube => Rgb::GREY,
+            Empty => Rgb::BLACK,
         }
-        println!();
-    }
+    })
+}
+
+crate::registry::solutions! {
+    (14, 1, input_generator, solve_part1),
+    (14, 2, input_generator, solve_part2),
 }
 
 #[cfg(test)]
@@ -230,7 +193,7 @@ O.#..O.#.#
     fn test_part1_example() {
         let input = super::input_generator(TEST_INPUT).unwrap();
         dbg!{&input};
-        let result = super::solve_part1(&input);
+        let result = super::solve_part1(&input).unwrap();
 
         assert_eq!(result, 136);
     }
@@ -238,8 +201,13 @@ O.#..O.#.#
     #[test]
     fn test_part2_example() {
         let input = super::input_generator(TEST_INPUT).unwrap();
-        let result = super::solve_part2(&input);
+        let result = super::solve_part2(&input).unwrap();
 
         assert_eq!(result, 64);
     }
+
+    #[test]
+    fn test_ragged_dish() {
+        assert!(super::input_generator("O..\n.#\n").is_err());
+    }
 }
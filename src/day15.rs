@@ -1,16 +1,28 @@
 use std::collections::HashMap;
+use std::io::BufRead;
 
 use aoc_runner_derive::aoc_generator;
 use aoc_runner_derive::aoc;
 
 use anyhow::Result;
-use anyhow::anyhow;
 
-type Data = String;
+use thiserror::Error;
+
+use crate::algorithms::day15::hash_algorithm;
+
+pub type Data = String;
+
+#[derive(Debug, Error)]
+pub enum Day15Error {
+    #[error("Step {step} isn't ASCII: {text:?}")]
+    NonAscii { step: usize, text: String },
+    #[error("Lens {label} has focal length {focal_length}, which isn't between 1 and 9")]
+    InvalidFocalLength { label: String, focal_length: usize },
+}
 
 
 #[aoc_generator(day15, part1)]
-fn input_generator(input: &str) -> Result<Vec<Data>> {
+pub fn input_generator(input: &str) -> Result<Vec<Data>> {
     let mut result = Vec::new();
 
     for line in input.lines() {
@@ -23,30 +35,30 @@ fn input_generator(input: &str) -> Result<Vec<Data>> {
 }
 
 #[aoc(day15, part1)]
-fn solve_part1(input: &[Data]) -> usize {
-    input.iter().map(|s| s.as_str()).map(hash_algorithm).sum()
-}
+pub fn solve_part1(input: &[Data]) -> Result<usize, Day15Error> {
+    check_ascii(input.iter().map(|s| s.as_str()))?;
 
-fn hash_algorithm(s: &str) -> usize {
-    let mut current: usize = 0;
+    Ok(input.iter().map(|s| s.as_str()).map(hash_algorithm).sum())
+}
 
-    for c in s.chars() {
-        current += c as usize;
-        current *= 17;
-        current %= 256;
+fn check_ascii<'a>(steps: impl Iterator<Item = &'a str>) -> Result<(), Day15Error> {
+    for (i, text) in steps.enumerate() {
+        if !text.is_ascii() {
+            return Err(Day15Error::NonAscii { step: i + 1, text: text.to_owned() });
+        }
     }
 
-    current
+    Ok(())
 }
 
 #[derive(Debug, Clone)]
-struct Step {
-    label: String,
-    instruction: Instruction,
+pub struct Step {
+    pub label: String,
+    pub instruction: Instruction,
 }
 
 #[derive(Debug, Clone, Copy)]
-enum Instruction {
+pub enum Instruction {
     Dash,
     Equals {
         focal_length: usize
@@ -54,29 +66,21 @@ enum Instruction {
 }
 
 #[aoc_generator(day15, part2)]
-fn input_generator_parsed(input: &str) -> Result<Vec<Step>> {
-    let (input, result) = parse_input(input).map_err(|err| err.map(|err| anyhow!(nom::error::convert_error(input, err))))?;
-    if !input.is_empty() {
-        return Err(anyhow!("Had unparsed input after parsing: {}", input));
-    }
-    Ok(result)
+pub fn input_generator_parsed(input: &str) -> Result<Vec<Step>> {
+    run_parser(input, parse_input)
 }
 
-use nom::bytes::complete::take_while1;
 use nom::bytes::complete::is_not;
 use nom::bytes::complete::take;
-use nom::combinator::opt;
-use nom::multi::separated_list1;
-use nom::bytes::complete::tag;
 
-type IResult<I, O> = nom::IResult<I, O, nom::error::VerboseError<I>>;
+use crate::parse::IResult;
+use crate::parse::comma_separated;
+use crate::parse::run_parser;
+use crate::parse::stream_records;
+use crate::parse::unsigned;
 
 fn parse_input(input: &str) -> IResult<&str, Vec<Step>> {
-    let (input, _) = opt(tag("\n"))(input)?;
-    let (input, result) = separated_list1(tag(","), parse_step)(input)?;
-    let (input, _) = opt(tag("\n"))(input)?;
-
-    Ok((input, result))
+    comma_separated(parse_step)(input)
 }
 
 fn parse_step(input: &str) -> IResult<&str, Step> {
@@ -89,7 +93,7 @@ fn parse_step(input: &str) -> IResult<&str, Step> {
     let instruction = match operation_str {
         "-" => Dash,
         "=" => {
-            let (next_input, focal_length) = parse_usize(input)?;
+            let (next_input, focal_length) = unsigned(input)?;
             input = next_input;
             Equals {
                 focal_length,
@@ -104,57 +108,57 @@ fn parse_step(input: &str) -> IResult<&str, Step> {
     }))
 }
 
-fn parse_usize(input: &str) -> IResult<&str, usize> {
-    let (input, number_string) = take_while1(is_number)(input)?;
-    let number = number_string.parse().unwrap();
-
-    Ok((input, number))
-}
-fn is_number(c: char) -> bool {
-    ('0'..='9').contains(&c)
-}
-
-struct LabeledLens<'a> {
-    label: &'a str,
+struct LabeledLens<L> {
+    label: L,
     focal_length: usize,
 }
 
+/// The lenses in each box, by box number.
+type Boxes<L> = HashMap<usize, Vec<LabeledLens<L>>>;
+
 #[aoc(day15, part2)]
-fn solve_part2<'a>(input: &'a[Step]) -> usize {
-    let mut boxes: HashMap<usize, Vec<LabeledLens<'a>>> = HashMap::new();
+pub fn solve_part2(input: &[Step]) -> Result<usize, Day15Error> {
+    check_ascii(input.iter().map(|step| step.label.as_str()))?;
+
+    let mut boxes = HashMap::new();
 
     for step in input {
-        let box_number = hash_algorithm(&step.label);
-        let step_box = boxes.entry(box_number).or_insert_with(|| Vec::new());
-        use Instruction::*;
-        match step.instruction {
-            Dash => {
-                for i in 0..step_box.len() {
-                    if step_box[i].label == step.label {
-                        step_box.remove(i);
-                        break;
-                    }
-                }
-            },
-            Equals { focal_length } => {
-                let mut found = false;
-                for i in 0..step_box.len() {
-                    if step_box[i].label == step.label {
-                        step_box[i].focal_length = focal_length;
-                        found = true;
-                        break;
-                    }
-                }
-                if !found {
-                    step_box.push(LabeledLens {
-                        label: &step.label,
-                        focal_length,
-                    });
-                }
-            },
-        }
+        apply_step(&mut boxes, step.label.as_str(), step.instruction)?;
+    }
+
+    Ok(focusing_power(&boxes))
+}
+
+fn apply_step<L: AsRef<str>>(boxes: &mut Boxes<L>, label: L, instruction: Instruction) -> Result<(), Day15Error> {
+    let box_number = hash_algorithm(label.as_ref());
+    let step_box = boxes.entry(box_number).or_default();
+    use Instruction::*;
+    match instruction {
+        Dash => {
+            if let Some(i) = step_box.iter().position(|lens| lens.label.as_ref() == label.as_ref()) {
+                step_box.remove(i);
+            }
+        },
+        Equals { focal_length } => {
+            if !(1..=9).contains(&focal_length) {
+                return Err(Day15Error::InvalidFocalLength { label: label.as_ref().to_owned(), focal_length });
+            }
+
+            if let Some(lens) = step_box.iter_mut().find(|lens| lens.label.as_ref() == label.as_ref()) {
+                lens.focal_length = focal_length;
+            } else {
+                step_box.push(LabeledLens {
+                    label,
+                    focal_length,
+                });
+            }
+        },
     }
 
+    Ok(())
+}
+
+fn focusing_power<L>(boxes: &Boxes<L>) -> usize {
     let mut total = 0;
     for box_number in 0..256 {
         let step_box = boxes.get(&box_number).map(|v| v.as_slice()).unwrap_or(&[]);
@@ -171,6 +175,57 @@ fn solve_part2<'a>(input: &'a[Step]) -> usize {
     total
 }
 
+/// Calls `f` with each step of `input` and its 1-based number, reading one
+/// comma-separated step at a time. Like `input_generator`, this also splits
+/// steps at line breaks.
+fn stream_steps(input: impl BufRead, mut f: impl FnMut(usize, &str) -> Result<()>) -> Result<()> {
+    let mut step = 0;
+    for record in stream_records(input, b',') {
+        let (_, text) = record?;
+        for text in text.split('\n').filter(|text| !text.is_empty()) {
+            step += 1;
+            if !text.is_ascii() {
+                return Err(Day15Error::NonAscii { step, text: text.to_owned() }.into());
+            }
+            f(step, text)?;
+        }
+    }
+
+    Ok(())
+}
+
+fn stream_part1(input: impl BufRead) -> Result<usize> {
+    let mut total = 0;
+    stream_steps(input, |_, text| {
+        total += hash_algorithm(text);
+        Ok(())
+    })?;
+
+    Ok(total)
+}
+
+/// Only the lenses currently in a box are kept, so memory use is bounded by
+/// the number of distinct labels rather than the number of steps.
+fn stream_part2(input: impl BufRead) -> Result<usize> {
+    let mut boxes = HashMap::new();
+    stream_steps(input, |_, text| {
+        let step = run_parser(text, parse_step)?;
+        Ok(apply_step(&mut boxes, step.label, step.instruction)?)
+    })?;
+
+    Ok(focusing_power(&boxes))
+}
+
+crate::registry::solutions! {
+    (15, 1, input_generator, solve_part1),
+    (15, 2, input_generator_parsed, solve_part2),
+}
+
+crate::registry::streaming_solutions! {
+    (15, 1, stream_part1),
+    (15, 2, stream_part2),
+}
+
 #[cfg(test)]
 mod test {
     const TEST_INPUT: &'static str =
@@ -180,7 +235,7 @@ rn=1,cm-,qp=3,cm=2,qp-,pc=4,ot=9,ab=5,pc-,pc=6,ot=7
     #[test]
     fn test_part1_example() {
         let input = super::input_generator(TEST_INPUT).unwrap();
-        let result = super::solve_part1(&input);
+        let result = super::solve_part1(&input).unwrap();
 
         assert_eq!(result, 1320);
     }
@@ -188,8 +243,19 @@ rn=1,cm-,qp=3,cm=2,qp-,pc=4,ot=9,ab=5,pc-,pc=6,ot=7
     #[test]
     fn test_part2_example() {
         let input = super::input_generator_parsed(TEST_INPUT).unwrap();
-        let result = super::solve_part2(&input);
+        let result = super::solve_part2(&input).unwrap();
 
         assert_eq!(result, 145);
     }
+
+    #[test]
+    fn test_invalid_steps() {
+        let input = super::input_generator("rn=1,cmé-\n").unwrap();
+        let error = super::solve_part1(&input).unwrap_err();
+        assert_eq!(error.to_string(), "Step 2 isn't ASCII: \"cmé-\"");
+
+        let input = super::input_generator_parsed("rn=1,cm=12\n").unwrap();
+        let error = super::solve_part2(&input).unwrap_err();
+        assert_eq!(error.to_string(), "Lens cm has focal length 12, which isn't between 1 and 9");
+    }
 }
@@ -4,17 +4,31 @@ use aoc_runner_derive::aoc_generator;
 use aoc_runner_derive::aoc;
 
 use anyhow::Result;
-use anyhow::anyhow;
 
-use ndarray::Array2;
-use ndarray::ShapeBuilder;
-
-type Input = Data;
-type InputRef = Data;
-type Data = Array2<Cell>;
+use thiserror::Error;
+
+use crate::grid::Direction;
+use crate::grid::Grid;
+use crate::grid::Pos;
+use crate::par;
+use crate::render;
+use crate::render::Frame;
+use crate::render::Rgb;
+
+pub type Input = Data;
+pub type InputRef = Data;
+pub type Data = Grid<Cell>;
+
+#[derive(Debug, Error)]
+pub enum Day16Error {
+    #[error("The contraption has no tiles for the beam to enter")]
+    EmptyGrid,
+    #[error("Couldn't write a frame: {0}")]
+    Render(#[from] std::io::Error),
+}
 
 #[derive(Debug, Clone, Copy)]
-enum Cell {
+pub enum Cell {
     Empty,
     Mirror(MirrorType),
     Splitter(SplitterType),
@@ -69,64 +83,37 @@ impl Cell {
     }
 }
 
-impl std::ops::Index<Pos> for Array2<Cell> {
-    type Output = <Self as std::ops::Index<ndarray::Ix2>>::Output;
-
-    fn index(&self, index: Pos) -> &Self::Output {
-        &self[(index.j as usize, index.i as usize)]
-    }
-}
-
 #[derive(Debug, Clone, Copy)]
-enum MirrorType {
+pub enum MirrorType {
     UpRight,
     DownRight,
 }
 
 #[derive(Debug, Clone, Copy)]
-enum SplitterType {
+pub enum SplitterType {
     Vertical,
     Horizontal,
 }
 
 
 #[aoc_generator(day16)]
-fn input_generator(input: &str) -> Result<Input> {
-    let (input, result) = parse_input(input)
-        .map_err(|err| err.map(|err| anyhow!(nom::error::convert_error(input, err))))?;
-    if !input.is_empty() {
-        return Err(anyhow!("Had unparsed input after parsing: {}", input));
-    }
-    Ok(result)
+pub fn input_generator(input: &str) -> Result<Input> {
+    run_parser(input, parse_input)
 }
 
-type IResult<I, T> = nom::IResult<I, T, nom::error::VerboseError<I>>;
 use nom::character::complete::one_of;
-use nom::combinator::opt;
-use nom::multi::separated_list1;
-use nom::multi::many1;
-use nom::bytes::complete::tag;
+use nom::combinator::map;
 
-fn parse_input(input: &str) -> IResult<&str, Data> {
-    let (input, _) = opt(tag("\n"))(input)?;
-    let (input, result) = parse_grid(input)?;
-    let (input, _) = opt(tag("\n"))(input)?;
+use crate::parse::IResult;
+use crate::parse::grid;
+use crate::parse::run_parser;
 
-    Ok((input, result))
+fn parse_input(input: &str) -> IResult<&str, Data> {
+    parse_grid(input)
 }
 
 fn parse_grid(input: &str) -> IResult<&str, Data> {
-    let (input, row_data) = separated_list1(tag("\n"),
-        many1(parse_space)
-    )(input)?;
-
-    let cols = row_data[0].len();
-    let rows = row_data.len();
-
-    let data: Vec<_> = row_data.into_iter().flatten().collect();
-    let shape = (rows, cols).strides((cols, 1));
-
-    Ok((input, Array2::from_shape_vec(shape, data).unwrap()))
+    map(grid(parse_space), Grid::from)(input)
 }
 
 fn parse_space(input: &str) -> IResult<&str, Cell> {
@@ -153,26 +140,11 @@ struct Energized {
     left: bool,
 }
 
-impl std::ops::Index<Pos> for Array2<Energized> {
-    type Output = <Self as std::ops::Index<ndarray::Ix2>>::Output;
-
-    fn index(&self, index: Pos) -> &Self::Output {
-        &self[(index.i as usize, index.j as usize)]
-    }
-}
-
-impl std::ops::IndexMut<Pos> for Array2<Energized> {
-    fn index_mut(&mut self, index: Pos) -> &mut Self::Output {
-        &mut self[(index.j as usize, index.i as usize)]
-    }
-}
-
 impl Energized {
     fn is_energized(&self) -> bool {
         self.up || self.down ||
             self.right || self.left
     }
-    #[cfg(test)]
     fn count_beams(&self) -> u32 {
         [self.up, self.down, self.right, self.left].iter().cloned().filter(|b| *b).count() as u32
     }
@@ -194,23 +166,17 @@ impl Energized {
     }
 }
 
-#[cfg(test)]
-fn pretty_print(state: &Array2<Energized>) {
-    for row in state.rows() {
-        for s in row.iter() {
-            let c = match s {
-                Energized { up: false, down: false, right: false, left: false } => '.',
-                Energized { up: true, down: false, right: false, left: false } => '^',
-                Energized { up: false, down: false, right: true, left: false } => '>',
-                Energized { up: false, down: true, right: false, left: false } => 'v',
-                Energized { up: false, down: false, right: false, left: true } => '<',
-                _ => char::from_digit(s.count_beams(), 10).unwrap(),
-            };
-
-            print!("{}", c);
+/// Mirrors and splitters in white, over tiles that get brighter the more beams cross them.
+fn frame(input: &InputRef, state: &Grid<Energized>) -> Frame {
+    let cells = Grid::from_fn(input.bounds(), |pos| (input[pos], state[pos]));
+
+    Frame::new(&cells, |&(cell, energized)| {
+        match (cell, energized.count_beams()) {
+            (Cell::Mirror(_) | Cell::Splitter(_), _) => Rgb::WHITE,
+            (Cell::Empty, 0) => Rgb::BLACK,
+            (Cell::Empty, beams) => Rgb::ORANGE.lerp(Rgb::YELLOW, (beams - 1) as f64 / 3.0),
         }
-        println!();
-    }
+    })
 }
 
 impl Default for Energized {
@@ -224,78 +190,44 @@ impl Default for Energized {
     }
 }
 
-#[derive(Debug, Clone, Copy)]
-enum Direction {
-    Up,
-    Right,
-    Down,
-    Left,
-}
-
-#[derive(Debug, Clone, Copy)]
-struct Pos {
-    i: i32,
-    j: i32,
-}
-
-impl Pos {
-    fn try_move(&self, direction: Direction, shape: &[usize]) -> Option<Pos> {
-        let mut next_pos = *self;
-        use Direction::*;
-        match direction {
-            Up => next_pos.j -= 1,
-            Right => next_pos.i += 1,
-            Down => next_pos.j += 1,
-            Left => next_pos.i -= 1,
-        }
-
-        if next_pos.i >= 0 && next_pos.j >= 0 &&
-                next_pos.i < shape[0] as i32 && next_pos.j < shape[1] as i32 {
-            Some(next_pos)
-        } else {
-            None
-        }
-    }
-}
-
 #[aoc(day16, part1)]
-fn solve_part1(input: &InputRef) -> usize {
-    let mut state = Array2::from_elem(input.raw_dim(), Energized::default());
+pub fn solve_part1(input: &InputRef) -> Result<usize, Day16Error> {
+    check_grid(input)?;
 
-    let mut frontier = VecDeque::new();
+    let state = energize(input, Pos::new(0, 0), Direction::Right);
 
-    let start_pos = Pos { i: -1, j: 0 };
-    let start_direction = Direction::Right;
+    render::dump("day16-part1", || frame(input, &state))?;
 
-    frontier.push_back((start_pos, start_direction));
+    Ok(state.iter().filter(|s| s.is_energized()).count())
+}
 
-    while let Some((pos, direction)) = frontier.pop_front() {
-        let next_pos = pos.try_move(direction, input.shape());
-        if let Some(next_pos) = next_pos {
-            let next_cell = input[next_pos];
-            for new_direction in next_cell.next_moves(direction) {
-                if state[next_pos].add_direction(new_direction) {
-                    frontier.push_back((next_pos, new_direction));
-                }
-            }
-        }
+fn check_grid(input: &InputRef) -> Result<(), Day16Error> {
+    if input.rows() == 0 || input.cols() == 0 {
+        return Err(Day16Error::EmptyGrid);
     }
 
-    #[cfg(test)]
-    pretty_print(&state);
-
-    state.iter().filter(|s| s.is_energized()).count()
+    Ok(())
 }
 
 fn count_energized(input: &InputRef, start_pos: Pos, start_direction: Direction) -> usize {
-    let mut state = Array2::from_elem(input.raw_dim(), Energized::default());
+    energize(input, start_pos, start_direction)
+        .iter().filter(|s| s.is_energized()).count()
+}
+
+/// Traces a beam entering the grid at `start_pos` while travelling in `start_direction`.
+fn energize(input: &InputRef, start_pos: Pos, start_direction: Direction) -> Grid<Energized> {
+    let mut state = Grid::from_elem(input.bounds(), Energized::default());
 
     let mut frontier = VecDeque::new();
 
-    frontier.push_back((start_pos, start_direction));
+    for new_direction in input[start_pos].next_moves(start_direction) {
+        if state[start_pos].add_direction(new_direction) {
+            frontier.push_back((start_pos, new_direction));
+        }
+    }
 
     while let Some((pos, direction)) = frontier.pop_front() {
-        let next_pos = pos.try_move(direction, input.shape());
+        let next_pos = input.try_move(pos, direction);
         if let Some(next_pos) = next_pos {
             let next_cell = input[next_pos];
             for new_direction in next_cell.next_moves(direction) {
@@ -306,23 +238,29 @@ fn count_energized(input: &InputRef, start_pos: Pos, start_direction: Direction)
         }
     }
 
-    state.iter().filter(|s| s.is_energized()).count()
+    state
 }
 
 #[aoc(day16, part2)]
-fn solve_part2(input: &InputRef) -> usize {
-    let shape = input.shape();
-    let rows = shape[0] as i32;
-    let cols = shape[1] as i32;
+pub fn solve_part2(input: &InputRef) -> Result<usize, Day16Error> {
+    check_grid(input)?;
+
+    let rows = input.rows();
+    let cols = input.cols();
 
     use Direction::*;
-    let start_iter = (0..rows).map(|j| (Pos { i: -1, j, }, Right))
-        .chain((0..cols).map(|i| (Pos { i, j: -1, }, Down)))
-        .chain((0..rows).map(|j| (Pos { i: cols, j, }, Left)))
-        .chain((0..cols).map(|i| (Pos { i, j: rows, }, Up)));
+    let start_iter = (0..rows).map(|row| (Pos::new(row, 0), Right))
+        .chain((0..cols).map(|col| (Pos::new(0, col), Down)))
+        .chain((0..rows).map(|row| (Pos::new(row, cols - 1), Left)))
+        .chain((0..cols).map(|col| (Pos::new(rows - 1, col), Up)));
 
-    start_iter.map(|(p, d)| count_energized(input, p, d))
-        .max().unwrap()
+    par::map(start_iter.collect(), |(p, d)| count_energized(input, p, d))
+        .into_iter().max().ok_or(Day16Error::EmptyGrid)
+}
+
+crate::registry::solutions! {
+    (16, 1, input_generator, solve_part1),
+    (16, 2, input_generator, solve_part2),
 }
 
 #[cfg(test)]
@@ -343,7 +281,7 @@ r#"
     #[test]
     fn test_part1_example() {
         let input = super::input_generator(TEST_INPUT).unwrap();
-        let result = super::solve_part1(&input);
+        let result = super::solve_part1(&input).unwrap();
 
         assert_eq!(result, 46);
     }
@@ -351,8 +289,18 @@ r#"
     #[test]
     fn test_part2_example() {
         let input = super::input_generator(TEST_INPUT).unwrap();
-        let result = super::solve_part2(&input);
+        let result = super::solve_part2(&input).unwrap();
 
         assert_eq!(result, 51);
     }
+
+    #[test]
+    fn test_empty_grid() {
+        let input = super::Grid::from_elem((0, 0), super::Cell::Empty);
+
+        let error = super::solve_part1(&input).unwrap_err();
+        assert_eq!(error.to_string(), "The contraption has no tiles for the beam to enter");
+
+        assert!(super::solve_part2(&input).is_err());
+    }
 }
@@ -4,22 +4,36 @@ use aoc_runner_derive::aoc_generator;
 use aoc_runner_derive::aoc;
 
 use anyhow::Result;
-use anyhow::anyhow;
 
-type Input = Vec<Data>;
-type InputRef = [Data];
-type Data = Dig;
+use thiserror::Error;
+
+pub type Input = Vec<Data>;
+pub type InputRef = [Data];
+pub type Data = Dig;
+
+#[derive(Debug, Error)]
+pub enum Day18Error {
+    #[error("The dig plan is empty")]
+    EmptyPlan,
+    #[error("Dig {dig} doubles back on the one before it")]
+    Reversal { dig: usize },
+    #[error("Dig {dig} is too long")]
+    TooLong { dig: usize },
+    #[error("The trench doesn't end where it started")]
+    NotClosed,
+    #[error("The trench has to be dug clockwise")]
+    CounterClockwise,
+}
 
-struct Dig {
-    direction: Direction,
-    length: usize,
-    #[allow(dead_code)]
-    color: Color,
+pub struct Dig {
+    pub direction: Direction,
+    pub length: usize,
+    pub color: Color,
 }
 
 #[derive(Debug, Clone, Copy)]
 #[derive(PartialEq, Eq)]
-enum Direction {
+pub enum Direction {
     Up,
     Right,
     Down,
@@ -49,45 +63,37 @@ impl Direction {
     }
 }
 
-#[allow(dead_code)]
-struct Color {
-    r: u8,
-    g: u8,
-    b: u8,
+pub struct Color {
+    pub r: u8,
+    pub g: u8,
+    pub b: u8,
 }
 
 
 #[aoc_generator(day18, part1)]
-fn input_generator(input: &str) -> Result<Input> {
-    let (input, result) = parse_input(input)
-        .map_err(|err| err.map(|err| anyhow!(nom::error::convert_error(input, err))))?;
-    if !input.is_empty() {
-        return Err(anyhow!("Had unparsed input after parsing: {}", input));
-    }
-    Ok(result)
+pub fn input_generator(input: &str) -> Result<Input> {
+    run_parser(input, parse_input)
 }
 
-type IResult<I, T> = nom::IResult<I, T, nom::error::VerboseError<I>>;
-use nom::bytes::complete::take_while1;
 use nom::bytes::complete::take_while_m_n;
 use nom::character::complete::one_of;
-use nom::combinator::opt;
-use nom::multi::separated_list1;
 use nom::bytes::complete::tag;
+use nom::combinator::map_res;
 use nom::sequence::tuple;
 
-fn parse_input(input: &str) -> IResult<&str, Input> {
-    let (input, _) = opt(tag("\n"))(input)?;
-    let (input, result) = separated_list1(tag("\n"), parse_dig)(input)?;
-    let (input, _) = opt(tag("\n"))(input)?;
+use crate::parse::IResult;
+use crate::parse::lines;
+use crate::parse::run_parser;
+use crate::parse::unsigned;
 
-    Ok((input, result))
+fn parse_input(input: &str) -> IResult<&str, Input> {
+    lines(parse_dig)(input)
 }
 
 fn parse_dig(input: &str) -> IResult<&str, Dig> {
     let (input, direction) = parse_direction(input)?;
     let (input, _) = tag(" ")(input)?;
-    let (input, length) = parse_usize(input)?;
+    let (input, length) = unsigned(input)?;
     let (input, _) = tag(" ")(input)?;
     let (input, color) = parse_color(input)?;
 
@@ -113,16 +119,6 @@ fn parse_direction(input: &str) -> IResult<&str, Direction> {
     Ok((input, direction))
 }
 
-fn parse_usize(input: &str) -> IResult<&str, usize> {
-    let (input, number_string) = take_while1(is_number)(input)?;
-    let number = number_string.parse().unwrap();
-
-    Ok((input, number))
-}
-fn is_number(c: char) -> bool {
-    ('0'..='9').contains(&c)
-}
-
 fn parse_color(input: &str) -> IResult<&str, Color> {
     let (input, _) = tag("(#")(input)?;
     let (input, color_vals) = tuple((parse_hex_number, parse_hex_number, parse_hex_number))(input)?;
@@ -138,9 +134,7 @@ fn parse_color(input: &str) -> IResult<&str, Color> {
 }
 
 fn parse_hex_number(input: &str) -> IResult<&str, u8> {
-    let (input, num_str) = take_while_m_n(2, 2, |c: char| c.is_digit(16))(input)?;
-
-    Ok((input, u8::from_str_radix(num_str, 16).unwrap()))
+    map_res(take_while_m_n(2, 2, |c: char| c.is_ascii_hexdigit()), |num_str| u8::from_str_radix(num_str, 16))(input)
 }
 
 #[derive(Debug, Clone, Copy)]
@@ -171,8 +165,12 @@ impl Pos {
 }
 
 #[aoc(day18, part1)]
-fn solve_part1(input: &InputRef) -> usize {
-    let winding_number = winding_number(input);
+pub fn solve_part1(input: &InputRef) -> Result<usize, Day18Error> {
+    if input.is_empty() {
+        return Err(Day18Error::EmptyPlan);
+    }
+
+    let winding_number = winding_number(input)?;
 
     let right_hand = winding_number > 0;
 
@@ -215,6 +213,10 @@ fn solve_part1(input: &InputRef) -> usize {
         last_direction = Some(dig.direction);
     }
 
+    if current_pos != (Pos { i: 0, j: 0 }) {
+        return Err(Day18Error::NotClosed);
+    }
+
     let mut interior: HashSet<_> = interior.difference(&boundary).cloned().collect();
 
     let mut frontier: Vec<_> = interior.iter().cloned().collect();
@@ -228,13 +230,13 @@ fn solve_part1(input: &InputRef) -> usize {
         }
     }
 
-    interior.len() + boundary.len()
+    Ok(interior.len() + boundary.len())
 }
 
-fn winding_number(input: &InputRef) -> i32 {
+fn winding_number(input: &InputRef) -> Result<i32, Day18Error> {
     use Direction::*;
-    input.windows(2).map(|point| {
-        match (point[0].direction, point[1].direction) {
+    input.windows(2).enumerate().map(|(i, point)| {
+        let turn = match (point[0].direction, point[1].direction) {
             (Up, Right) => 1,
             (Up, Up) => 0,
             (Up, Left) => -1,
@@ -247,11 +249,17 @@ fn winding_number(input: &InputRef) -> i32 {
             (Left, Up) => 1,
             (Left, Left) => 0,
             (Left, Down) => -1,
-            _ => unreachable!(),
-        }
+            _ => return Err(Day18Error::Reversal { dig: i + 2 }),
+        };
+
+        Ok(turn)
     }).sum()
 }
 
+crate::registry::solutions! {
+    (18, 1, input_generator, solve_part1),
+}
+
 #[cfg(test)]
 mod test {
     const TEST_INPUT: &'static str =
@@ -274,8 +282,19 @@ U 2 (#7a21e3)
     #[test]
     fn test_part1_example() {
         let input = super::input_generator(TEST_INPUT).unwrap();
-        let result = super::solve_part1(&input);
+        let result = super::solve_part1(&input).unwrap();
 
         assert_eq!(result, 62);
     }
+
+    #[test]
+    fn test_invalid_plan() {
+        let input = super::input_generator("R 2 (#70c710)\nL 2 (#0dc571)\n").unwrap();
+        let error = super::solve_part1(&input).unwrap_err();
+        assert_eq!(error.to_string(), "Dig 2 doubles back on the one before it");
+
+        let input = super::input_generator("R 2 (#70c710)\nD 2 (#0dc571)\nL 1 (#5713f0)\n").unwrap();
+        let error = super::solve_part1(&input).unwrap_err();
+        assert_eq!(error.to_string(), "The trench doesn't end where it started");
+    }
 }
@@ -6,20 +6,21 @@ use aoc_runner_derive::aoc_generator;
 use aoc_runner_derive::aoc;
 
 use anyhow::Result;
-use anyhow::anyhow;
 
-type Input = Vec<Data>;
-type InputRef = [Data];
-type Data = Dig;
+use crate::day18::Day18Error;
 
-struct Dig {
-    direction: Direction,
-    length: u64,
+pub type Input = Vec<Data>;
+pub type InputRef = [Data];
+pub type Data = Dig;
+
+pub struct Dig {
+    pub direction: Direction,
+    pub length: u64,
 }
 
 #[derive(Debug, Clone, Copy)]
 #[derive(PartialEq, Eq)]
-enum Direction {
+pub enum Direction {
     Up,
     Right,
     Down,
@@ -34,36 +35,29 @@ const DIRECTIONS: [Direction; 4] = [
 ];
 
 #[aoc_generator(day18, part2)]
-fn input_generator(input: &str) -> Result<Input> {
-    let (input, result) = parse_input(input)
-        .map_err(|err| err.map(|err| anyhow!(nom::error::convert_error(input, err))))?;
-    if !input.is_empty() {
-        return Err(anyhow!("Had unparsed input after parsing: {}", input));
-    }
-    Ok(result)
+pub fn input_generator(input: &str) -> Result<Input> {
+    run_parser(input, parse_input)
 }
 
-type IResult<I, T> = nom::IResult<I, T, nom::error::VerboseError<I>>;
-use nom::bytes::complete::take_while1;
 use nom::bytes::complete::take_while_m_n;
 use nom::character::complete::one_of;
-use nom::combinator::opt;
-use nom::multi::separated_list1;
 use nom::bytes::complete::tag;
+use nom::combinator::map_res;
 use nom::sequence::tuple;
 
-fn parse_input(input: &str) -> IResult<&str, Input> {
-    let (input, _) = opt(tag("\n"))(input)?;
-    let (input, result) = separated_list1(tag("\n"), parse_dig)(input)?;
-    let (input, _) = opt(tag("\n"))(input)?;
+use crate::parse::IResult;
+use crate::parse::lines;
+use crate::parse::run_parser;
+use crate::parse::unsigned;
 
-    Ok((input, result))
+fn parse_input(input: &str) -> IResult<&str, Input> {
+    lines(parse_dig)(input)
 }
 
 fn parse_dig(input: &str) -> IResult<&str, Dig> {
     let (input, _) = parse_direction(input)?;
     let (input, _) = tag(" ")(input)?;
-    let (input, _) = parse_usize(input)?;
+    let (input, _) = unsigned::<usize>(input)?;
     let (input, _) = tag(" ")(input)?;
     let (input, (length, direction)) = parse_color(input)?;
 
@@ -88,28 +82,17 @@ fn parse_direction(input: &str) -> IResult<&str, Direction> {
     Ok((input, direction))
 }
 
-fn parse_usize(input: &str) -> IResult<&str, usize> {
-    let (input, number_string) = take_while1(is_number)(input)?;
-    let number = number_string.parse().unwrap();
-
-    Ok((input, number))
-}
-fn is_number(c: char) -> bool {
-    ('0'..='9').contains(&c)
-}
-
 fn parse_color(input: &str) -> IResult<&str, (u64, Direction)> {
     let (input, _) = tag("(#")(input)?;
-    let (input, vals) = tuple((parse_hex_number(5), parse_hex_number(1)))(input)?;
+    let (input, (length, direction_c)) = tuple((parse_hex_number(5), one_of("0123")))(input)?;
     let (input, _) = tag(")")(input)?;
 
-    let length = vals.0;
     use Direction::*;
-    let direction = match vals.1 {
-        0 => Right,
-        1 => Down,
-        2 => Left,
-        3 => Up,
+    let direction = match direction_c {
+        '0' => Right,
+        '1' => Down,
+        '2' => Left,
+        '3' => Up,
         _ => unreachable!(),
     };
 
@@ -118,9 +101,7 @@ fn parse_color(input: &str) -> IResult<&str, (u64, Direction)> {
 
 fn parse_hex_number(width: usize) -> impl Fn(&str) -> IResult<&str, u64> {
     move |input: &str| {
-        let (input, num_str) = take_while_m_n(width, width, |c: char| c.is_digit(16))(input)?;
-
-        Ok((input, u64::from_str_radix(num_str, 16).unwrap()))
+        map_res(take_while_m_n(width, width, |c: char| c.is_ascii_hexdigit()), |num_str| u64::from_str_radix(num_str, 16))(input)
     }
 }
 
@@ -155,8 +136,12 @@ impl Pos {
 }
 
 #[aoc(day18, part2)]
-fn solve_part2(input: &InputRef) -> i64 {
-    let winding_number = winding_number(input);
+pub fn solve_part2(input: &InputRef) -> Result<i64, Day18Error> {
+    if input.is_empty() {
+        return Err(Day18Error::EmptyPlan);
+    }
+
+    let winding_number = winding_number(input)?;
 
     let right_hand = winding_number > 0;
 
@@ -164,12 +149,18 @@ fn solve_part2(input: &InputRef) -> i64 {
 
     let mut path = Vec::new();
 
-    for dig in input {
-        current_pos = current_pos.shift_multiple(dig.direction, dig.length.try_into().unwrap());
+    for (i, dig) in input.iter().enumerate() {
+        let length = dig.length.try_into().map_err(|_| Day18Error::TooLong { dig: i + 1 })?;
+        current_pos = current_pos.shift_multiple(dig.direction, length);
         path.push((current_pos, dig.direction));
     }
 
-    assert!(right_hand);
+    if current_pos != (Pos { i: 0, j: 0 }) {
+        return Err(Day18Error::NotClosed);
+    }
+    if !right_hand {
+        return Err(Day18Error::CounterClockwise);
+    }
 
     let mut boundary_is = HashSet::new();
     let mut boundary_js = HashSet::new();
@@ -278,13 +269,13 @@ fn solve_part2(input: &InputRef) -> i64 {
         total += (end_i - start_i) as i64 * (end_j - start_j) as i64;
     }
 
-    total
+    Ok(total)
 }
 
-fn winding_number(input: &InputRef) -> i32 {
+fn winding_number(input: &InputRef) -> Result<i32, Day18Error> {
     use Direction::*;
-    input.windows(2).map(|point| {
-        match (point[0].direction, point[1].direction) {
+    input.windows(2).enumerate().map(|(i, point)| {
+        let turn = match (point[0].direction, point[1].direction) {
             (Up, Right) => 1,
             (Up, Up) => 0,
             (Up, Left) => -1,
@@ -297,11 +288,17 @@ fn winding_number(input: &InputRef) -> i32 {
             (Left, Up) => 1,
             (Left, Left) => 0,
             (Left, Down) => -1,
-            _ => unreachable!(),
-        }
+            _ => return Err(Day18Error::Reversal { dig: i + 2 }),
+        };
+
+        Ok(turn)
     }).sum()
 }
 
+crate::registry::solutions! {
+    (18, 2, input_generator, solve_part2),
+}
+
 #[cfg(test)]
 mod test {
     const TEST_INPUT: &'static str =
@@ -325,8 +322,16 @@ U 2 (#7a21e3)
     #[test]
     fn test_part2_example() {
         let input = super::input_generator(TEST_INPUT).unwrap();
-        let result = super::solve_part2(&input);
+        let result = super::solve_part2(&input).unwrap();
 
         assert_eq!(result, 952408144115);
     }
+
+    #[test]
+    fn test_counter_clockwise() {
+        let input = super::input_generator("U 1 (#000023)\nU 1 (#000022)\nU 1 (#000021)\nU 1 (#000020)\n").unwrap();
+
+        let error = super::solve_part2(&input).unwrap_err();
+        assert_eq!(error.to_string(), "The trench has to be dug clockwise");
+    }
 }
@@ -1,5 +1,4 @@
 use std::collections::HashMap;
-use std::ops::RangeInclusive;
 
 use std::cmp::{max, min};
 
@@ -7,45 +6,61 @@ use aoc_runner_derive::aoc_generator;
 use aoc_runner_derive::aoc;
 
 use anyhow::Result;
-use anyhow::anyhow;
 
-type Input = (HashMap<WorkflowName, Workflow>, Vec<Part>);
-type InputRef = Input;
+use thiserror::Error;
+
+use crate::ranges::AxisBox;
+use crate::ranges::BoxSet;
+
+pub type Input = (HashMap<WorkflowName, Workflow>, Vec<Part>);
+pub type InputRef = Input;
+
+#[derive(Debug, Error)]
+pub enum Day19Error {
+    #[error("Workflow {0} doesn't exist")]
+    UnknownWorkflow(String),
+    #[error("Workflow {0} doesn't end with a rule that always applies")]
+    NoFallback(String),
+    #[error("Workflow {0} is part of a cycle")]
+    Cycle(String),
+    #[error("Workflow {0} is reached from more than one rule")]
+    SharedWorkflow(String),
+}
 
 #[derive(Debug, Clone)]
 #[derive(PartialEq, Eq)]
 #[derive(Hash)]
-struct WorkflowName(String);
+pub struct WorkflowName(pub String);
 #[derive(Debug, Clone)]
-struct Workflow {
-    name: WorkflowName,
-    rules: Vec<Rule>,
+pub struct Workflow {
+    pub name: WorkflowName,
+    pub rules: Vec<Rule>,
 }
 
 #[derive(Debug, Clone)]
-enum Rule {
+pub enum Rule {
     Comparison(ComparisonRule),
     Jump(JumpTarget),
 }
 
 #[derive(Debug, Clone)]
-struct ComparisonRule {
-    category: Category,
-    comp: ComparisonDirection,
-    value: u32,
-    jump_target: JumpTarget,
+pub struct ComparisonRule {
+    pub category: Category,
+    pub comp: ComparisonDirection,
+    pub value: u32,
+    pub jump_target: JumpTarget,
 }
 
 #[derive(Debug, Clone)]
 #[derive(PartialEq, Eq)]
-enum JumpTarget {
+pub enum JumpTarget {
     Reject,
     Accept,
     Jump(WorkflowName),
 }
 
 #[derive(Debug, Clone, Copy)]
-enum Category {
+pub enum Category {
     X,
     M,
     A,
@@ -53,17 +68,17 @@ enum Category {
 }
 
 #[derive(Debug, Clone, Copy)]
-enum ComparisonDirection {
+pub enum ComparisonDirection {
     Less,
     Greater,
 }
 
 #[derive(Debug, Clone, Copy)]
-struct Part {
-    x: u32,
-    m: u32,
-    a: u32,
-    s: u32,
+pub struct Part {
+    pub x: u32,
+    pub m: u32,
+    pub a: u32,
+    pub s: u32,
 }
 
 impl Part {
@@ -74,31 +89,27 @@ impl Part {
 
 
 #[aoc_generator(day19)]
-fn input_generator(input: &str) -> Result<Input> {
-    let (input, result) = parse_input(input)
-        .map_err(|err| err.map(|err| anyhow!(nom::error::convert_error(input, err))))?;
-    if !input.is_empty() {
-        return Err(anyhow!("Had unparsed input after parsing: {}", input));
-    }
-    Ok(result)
+pub fn input_generator(input: &str) -> Result<Input> {
+    run_parser(input, parse_input)
 }
 
-type IResult<I, T> = nom::IResult<I, T, nom::error::VerboseError<I>>;
 use nom::bytes::complete::take_while1;
 use nom::character::complete::one_of;
-use nom::combinator::opt;
 use nom::combinator::map;
 use nom::multi::separated_list1;
 use nom::bytes::complete::tag;
 use nom::branch::alt;
 use nom::sequence::tuple;
 
+use crate::parse::IResult;
+use crate::parse::lines;
+use crate::parse::run_parser;
+use crate::parse::unsigned;
+
 fn parse_input(input: &str) -> IResult<&str, Input> {
-    let (input, _) = opt(tag("\n"))(input)?;
-    let (input, workflow_vec) = separated_list1(tag("\n"), parse_workflow)(input)?;
+    let (input, workflow_vec) = lines(parse_workflow)(input)?;
     let (input, _) = tag("\n\n")(input)?;
-    let (input, parts) = separated_list1(tag("\n"), parse_part)(input)?;
-    let (input, _) = opt(tag("\n"))(input)?;
+    let (input, parts) = lines(parse_part)(input)?;
 
     let workflows = workflow_vec.into_iter().map(|w| (w.name.clone(), w)).collect();
 
@@ -124,7 +135,7 @@ fn parse_rule(input: &str) -> IResult<&str, Rule> {
 fn parse_comparison_rule(input: &str) -> IResult<&str, ComparisonRule> {
     let (input, category_c) = one_of("xmas")(input)?;
     let (input, comp_c) = one_of("<>")(input)?;
-    let (input, value) = parse_u32(input)?;
+    let (input, value) = unsigned(input)?;
     let (input, _) = tag(":")(input)?;
     let (input, jump_target) = parse_jump_target(input)?;
 
@@ -189,30 +200,57 @@ fn parse_val(category: &str) -> impl Fn(&str) -> IResult<&str, u32> + '_ {
     move |input: &str| {
         let (input, _) = tag(category)(input)?;
         let (input, _) = tag("=")(input)?;
-        let (input, value) = parse_u32(input)?;
+        let (input, value) = unsigned(input)?;
 
         Ok((input, value))
     }
 }
 
-fn parse_u32(input: &str) -> IResult<&str, u32> {
-    let (input, number_string) = take_while1(is_number)(input)?;
-    let number = number_string.parse().unwrap();
+/// Checks that every part ends up accepted or rejected after following the workflows.
+fn check_workflows(input: &InputRef) -> Result<(), Day19Error> {
+    if !input.0.contains_key(&WorkflowName("in".to_owned())) {
+        return Err(Day19Error::UnknownWorkflow("in".to_owned()));
+    }
+
+    for workflow in input.0.values() {
+        if !matches!(workflow.rules.last(), Some(Rule::Jump(_))) {
+            return Err(Day19Error::NoFallback(workflow.name.0.clone()));
+        }
 
-    Ok((input, number))
-}
-fn is_number(c: char) -> bool {
-    ('0'..='9').contains(&c)
+        for rule in &workflow.rules {
+            let target = match rule {
+                Rule::Jump(target) => target,
+                Rule::Comparison(comp) => &comp.jump_target,
+            };
+
+            if let JumpTarget::Jump(name) = target {
+                if !input.0.contains_key(name) {
+                    return Err(Day19Error::UnknownWorkflow(name.0.clone()));
+                }
+            }
+        }
+    }
+
+    Ok(())
 }
 
 #[aoc(day19, part1)]
-fn solve_part1(input: &InputRef) -> u32 {
+pub fn solve_part1(input: &InputRef) -> Result<u32, Day19Error> {
+    check_workflows(input)?;
+
     let start = WorkflowName("in".to_owned());
     let mut total = 0;
     for part in &input.1 {
         let mut current_workflow_name = &start;
+        let mut visited = 0;
 
         let accepted = 'outer: loop {
+            // A part can't visit more workflows than there are without going round in circles
+            visited += 1;
+            if visited > input.0.len() {
+                return Err(Day19Error::Cycle(current_workflow_name.0.clone()));
+            }
+
             let current_workflow = &input.0[current_workflow_name];
 
             for rule in &current_workflow.rules {
@@ -255,29 +293,15 @@ fn solve_part1(input: &InputRef) -> u32 {
             total += part.total_rating();
         }
     }
-    total
+    Ok(total)
 }
 
-#[derive(Debug, Clone)]
-struct Quad {
-    x: RangeInclusive<u32>,
-    m: RangeInclusive<u32>,
-    a: RangeInclusive<u32>,
-    s: RangeInclusive<u32>,
-}
-
-impl Quad {
-    fn is_empty(&self) -> bool {
-        self.x.is_empty() || self.m.is_empty() || self.a.is_empty() || self.s.is_empty()
-    }
-}
+/// The ratings accepted along one path through the workflows, one axis per category.
+type Quad = AxisBox<u64, 4>;
 
 fn find_quad(input: &InputRef, workflow: &Workflow, index: usize) -> Option<Quad> {
     let quad = Quad {
-        x: 1..=4000,
-        m: 1..=4000,
-        a: 1..=4000,
-        s: 1..=4000,
+        axes: [1..4001, 1..4001, 1..4001, 1..4001],
     };
 
     let end = WorkflowName("in".to_owned());
@@ -290,40 +314,36 @@ fn find_quad_recursive(input: &InputRef, stop_name: &WorkflowName, current_workf
         &Rule::Jump(JumpTarget::Accept) => { },
         &Rule::Jump(_) => {
             if !jumped {
-                return dbg!{None};
+                return None;
             }
         },
         &Rule::Comparison(ref rule) => {
-            let value = match rule.category {
-                Category::X => &mut current_quad.x,
-                Category::M => &mut current_quad.m,
-                Category::A => &mut current_quad.a,
-                Category::S => &mut current_quad.s,
-            };
+            let value = &mut current_quad.axes[rule.category as usize];
+            let rule_value = rule.value as u64;
 
             if jumped {
                 match rule.comp {
                     ComparisonDirection::Less => {
-                        *value = *value.start()..=min(*value.end(), rule.value - 1);
+                        value.end = min(value.end, rule_value);
                     },
                     ComparisonDirection::Greater => {
-                        *value = max(*value.start(), rule.value + 1)..=*value.end();
+                        value.start = max(value.start, rule_value + 1);
                     },
                 }
             } else {
                 match rule.comp {
                     ComparisonDirection::Greater => {
-                        *value = *value.start()..=min(*value.end(), rule.value);
+                        value.end = min(value.end, rule_value + 1);
                     },
                     ComparisonDirection::Less => {
-                        *value = max(*value.start(), rule.value)..=*value.end();
+                        value.start = max(value.start, rule_value);
                     },
                 }
             }
         },
     }
 
-    if current_quad.is_empty() { return dbg!{None}; }
+    if current_quad.is_empty() { return None; }
 
     if current_index == 0 {
         //dbg!{&current_workflow.name, &current_quad};
@@ -332,7 +352,7 @@ fn find_quad_recursive(input: &InputRef, stop_name: &WorkflowName, current_workf
         } else {
             let jump_target = JumpTarget::Jump(current_workflow.name.clone());
             let next_rule = find_matching_rules(input, &jump_target).next();
-            if next_rule.is_none() { return dbg!{None}; }
+            if next_rule.is_none() { return None; }
             let (next_workflow, next_index) = next_rule.unwrap();
             //dbg!{&next_workflow.name};
             find_quad_recursive(input, stop_name, next_workflow, next_index, true, current_quad)
@@ -363,103 +383,42 @@ fn find_matching_rules<'w>(input: &'w InputRef, jump_target: &'w JumpTarget) ->
 }
 
 #[aoc(day19, part2)]
-fn solve_part2(input: &InputRef) -> u64 {
-    // Going to assume that this is a acyclic tree
+pub fn solve_part2(input: &InputRef) -> Result<u64, Day19Error> {
+    Ok(accepted_ratings(input)?.volume())
+}
 
-    let mut quads = Vec::new();
+/// Every combination of ratings that ends up accepted, as disjoint boxes with
+/// one axis per category in the order x, m, a, s.
+pub fn accepted_ratings(input: &InputRef) -> Result<BoxSet<u64, 4>, Day19Error> {
+    check_workflows(input)?;
+
+    // Walking back up from each accepting rule only works if the workflows form a tree
+    for name in input.0.keys() {
+        let jump_target = JumpTarget::Jump(name.clone());
+        if find_matching_rules(input, &jump_target).nth(1).is_some() {
+            return Err(Day19Error::SharedWorkflow(name.0.clone()));
+        }
+    }
+
+    let mut quads = BoxSet::new();
 
     for (workflow, i) in find_matching_rules(input, &JumpTarget::Accept) {
         //dbg!{&workflow.name, i};
         let quad = find_quad(input, workflow, i);
         if let Some(quad) = quad {
             //dbg!{&workflow.name, i, &quad};
-            quads.push(quad);
+            quads.insert(quad);
         }
     }
 
     //dbg!{&quads};
 
-    println!("finding volume of {} quads", quads.len());
-
-    quad_union_volume(&quads)
-}
-fn get_boundaries<F: Fn(&Quad) -> &RangeInclusive<u32>>(quads: &[Quad], key: F) -> Vec<u32> {
-    let mut boundaries: Vec<_> = quads.iter().flat_map(|q| [*key(q).start(), *key(q).end() + 1]).collect();
-    boundaries.sort();
-    boundaries.dedup();
-    boundaries
+    Ok(quads)
 }
 
-fn quad_union_volume(quads: &[Quad]) -> u64 {
-    let x_boundaries = get_boundaries(quads, |q| &q.x);
-    let m_boundaries = get_boundaries(quads, |q| &q.m);
-    let a_boundaries = get_boundaries(quads, |q| &q.a);
-
-    //dbg!{&x_boundaries, &m_boundaries, &a_boundaries};
-
-    let x_map: HashMap<_, _> = x_boundaries.iter().enumerate().map(|(i, n)| (*n, i)).collect();
-    let m_map: HashMap<_, _> = m_boundaries.iter().enumerate().map(|(i, n)| (*n, i)).collect();
-    let a_map: HashMap<_, _> = a_boundaries.iter().enumerate().map(|(i, n)| (*n, i)).collect();
-
-    let mut lines = HashMap::new();
-
-    for quad in quads {
-        let x_min = x_map[quad.x.start()];
-        let x_max = x_map[&(quad.x.end() + 1)];
-        for x_point in x_min..x_max {
-            let m_min = m_map[quad.m.start()];
-            let m_max = m_map[&(quad.m.end() + 1)];
-            for m_point in m_min..m_max {
-                let a_min = a_map[quad.a.start()];
-                let a_max = a_map[&(quad.a.end() + 1)];
-                for a_point in a_min..a_max {
-                    let line = lines.entry((x_point, m_point, a_point)).or_insert(Vec::new());
-                    line.push(&quad.s);
-                }
-            }
-        }
-    }
-
-    lines.into_iter()
-        .map(|((x_point, m_point, a_point), s_intervals)| {
-            (x_boundaries[x_point + 1] - x_boundaries[x_point]) as u64 *
-            (m_boundaries[m_point + 1] - m_boundaries[m_point]) as u64 *
-            (a_boundaries[a_point + 1] - a_boundaries[a_point]) as u64 *
-            length_of_union(s_intervals) as u64
-        })
-        .sum()
-}
-
-fn length_of_union(mut intervals: Vec<&RangeInclusive<u32>>) -> u32 {
-    intervals.sort_by(|a, b| a.start().cmp(b.start()));
-    let mut current_interval = None;
-
-    let mut length = 0;
-
-    for interval in intervals {
-        if current_interval.is_none() {
-            current_interval = Some(interval.clone());
-        } else {
-            if current_interval.as_ref().unwrap().start() <= interval.end() && interval.start() <= current_interval.as_ref().unwrap().end() {
-                // overlaps, merge them
-                current_interval = Some(
-                    min(*current_interval.as_ref().unwrap().start(), *interval.start())..=
-                    max(*current_interval.as_ref().unwrap().end(), *interval.end())
-                )
-            } else {
-                let i = current_interval.unwrap();
-                length += i.end() - i.start() + 1;
-                current_interval = Some(interval.clone());
-            }
-        }
-    }
-
-    if let Some(current_interval) = current_interval {
-        let i = current_interval;
-        length += i.end() - i.start() + 1;
-    }
-
-    length
+crate::registry::solutions! {
+    (19, 1, input_generator, solve_part1),
+    (19, 2, input_generator, solve_part2),
 }
 
 #[cfg(test)]
@@ -487,7 +446,7 @@ hdj{m>838:A,pv}
     #[test]
     fn test_part1_example() {
         let input = super::input_generator(TEST_INPUT).unwrap();
-        let result = super::solve_part1(&input);
+        let result = super::solve_part1(&input).unwrap();
 
         assert_eq!(result, 19114);
     }
@@ -495,8 +454,23 @@ hdj{m>838:A,pv}
     #[test]
     fn test_part2_example() {
         let input = super::input_generator(TEST_INPUT).unwrap();
-        let result = super::solve_part2(&input);
+        let result = super::solve_part2(&input).unwrap();
 
         assert_eq!(result, 167409079868000);
     }
+
+    #[test]
+    fn test_invalid_workflows() {
+        let input = super::input_generator("in{x<10:ab,R}\n\n{x=1,m=2,a=3,s=4}\n").unwrap();
+        let error = super::solve_part1(&input).unwrap_err();
+        assert_eq!(error.to_string(), "Workflow ab doesn't exist");
+
+        let input = super::input_generator("in{x<10:ab,R}\nab{in}\n\n{x=1,m=2,a=3,s=4}\n").unwrap();
+        let error = super::solve_part1(&input).unwrap_err();
+        assert!(matches!(error, super::Day19Error::Cycle(_)));
+
+        let input = super::input_generator("in{x<10:ab,m<5:ab,R}\nab{A}\n\n{x=1,m=2,a=3,s=4}\n").unwrap();
+        let error = super::solve_part2(&input).unwrap_err();
+        assert_eq!(error.to_string(), "Workflow ab is reached from more than one rule");
+    }
 }
@@ -2,54 +2,100 @@ use aoc_runner_derive::aoc_generator;
 use aoc_runner_derive::aoc;
 
 use anyhow::Result;
-use anyhow::anyhow;
 
 use std::cmp::max;
-
-type Data = Game;
+use std::collections::BTreeMap;
+use std::collections::BTreeSet;
+use std::collections::HashSet;
+use std::io::BufRead;
+
+use num::BigInt;
+use num::BigRational;
+use num::One;
+use num::Zero;
+
+use thiserror::Error;
+
+pub type Data = Game;
+
+#[derive(Debug, Error)]
+pub enum Day2Error {
+    #[error("Game {id} appears more than once")]
+    DuplicateGame { id: u32 },
+    #[error("Game {id} comes after game {previous}, but streamed games need increasing IDs")]
+    UnorderedGame { id: u32, previous: u32 },
+    #[error("The power of the minimum bag for game {id} overflows")]
+    PowerOverflow { id: u32 },
+}
 
 #[derive(Debug, Clone)]
-struct Game {
-    id: u32,
-    moves: Vec<Move>,
+pub struct Game {
+    pub id: u32,
+    pub moves: Vec<Move>,
 }
 
-#[derive(Debug, Clone, Copy)]
-struct Move {
-    r: u32,
-    g: u32,
-    b: u32,
+/// A number of cubes of each colour, whether shown in one draw or held in a
+/// bag. Colours that aren't listed count as none.
+#[derive(Debug, Clone, Default)]
+#[derive(PartialEq, Eq)]
+pub struct Move {
+    pub counts: BTreeMap<String, u32>,
 }
 
+/// The cubes in a bag, which a game is possible with if it holds at least as
+/// many of each colour as any draw shows.
+pub type Bag = Move;
 
-#[aoc_generator(day2)]
-fn input_generator(input: &str) -> Result<Vec<Data>> {
-    let (input, result) = parse_input(input).map_err(|err| err.to_owned())?;
-    if !input.is_empty() {
-        return Err(anyhow!("Had unparsed input after parsing: {}", input));
+impl Move {
+    pub fn new<S: Into<String>>(counts: impl IntoIterator<Item = (S, u32)>) -> Move {
+        Move {
+            counts: counts.into_iter().map(|(color, count)| (color.into(), count)).collect(),
+        }
     }
-    Ok(result)
+
+    pub fn get(&self, color: &str) -> u32 {
+        self.counts.get(color).copied().unwrap_or(0)
+    }
+
+    /// Whether there are at least as many cubes of every colour as in `other`.
+    pub fn contains(&self, other: &Move) -> bool {
+        other.counts.iter().all(|(color, &count)| self.get(color) >= count)
+    }
+
+    /// The counts of `colors` multiplied together, or `None` if that overflows.
+    pub fn power(&self, colors: &[&str]) -> Option<u32> {
+        colors.iter().try_fold(1u32, |power, color| power.checked_mul(self.get(color)))
+    }
+
+    pub fn total(&self) -> u64 {
+        self.counts.values().map(|&count| count as u64).sum()
+    }
+}
+
+
+#[aoc_generator(day2)]
+pub fn input_generator(input: &str) -> Result<Vec<Data>> {
+    run_parser(input, parse_input)
 }
 
-use nom::IResult;
-use nom::bytes::complete::take_while1;
-use nom::combinator::opt;
 use nom::multi::separated_list1;
 use nom::bytes::complete::tag;
-use nom::branch::alt;
+use nom::character::complete::alpha1;
 use nom::sequence::separated_pair;
 
-fn parse_input(input: &str) -> IResult<&str, Vec<Data>> {
-    let (input, _) = opt(tag("\n"))(input)?;
-    let (input, result) = separated_list1(tag("\n"), parse_game)(input)?;
-    let (input, _) = opt(tag("\n"))(input)?;
+use crate::parse::IResult;
+use crate::parse::lines;
+use crate::parse::run_parser;
+use crate::parse::stream_lines;
+use crate::parse::unsigned;
 
-    Ok((input, result))
+fn parse_input(input: &str) -> IResult<&str, Vec<Data>> {
+    lines(parse_game)(input)
 }
 
 fn parse_game(input: &str) -> IResult<&str, Game> {
     let (input, _) = tag("Game ")(input)?;
-    let (input, id) = parse_u32(input)?;
+    let (input, id) = unsigned(input)?;
     let (input, _) = tag(": ")(input)?;
 
     let (input, moves) = separated_list1(tag("; "), parse_move)(input)?;
@@ -60,116 +106,310 @@ fn parse_game(input: &str) -> IResult<&str, Game> {
     }))
 }
 
+/// A draw like `3 blue, 4 red`, adding up the counts if a colour appears twice.
 fn parse_move(input: &str) -> IResult<&str, Move> {
-    let (input, numbers) = separated_list1(tag(", "), separated_pair(parse_u32, tag(" "), parse_color))(input)?;
-
-    let mut single_move = Move {
-        r: 0, g: 0, b: 0
-    };
+    let (input, numbers) = separated_list1(tag(", "), separated_pair(unsigned, tag(" "), alpha1))(input)?;
 
-    use Color::*;
+    let mut single_move = Move::default();
     for (num, color) in numbers {
-        match color {
-            Red => single_move.r = num,
-            Green => single_move.g = num,
-            Blue => single_move.b = num,
-        }
+        let count = single_move.counts.entry(color.to_owned()).or_insert(0);
+        *count = count.saturating_add(num);
     }
 
     Ok((input, single_move))
 }
 
-enum Color {
-    Red,
-    Green,
-    Blue,
+/// The colours whose counts make up the power of a bag.
+const POWER_COLORS: [&str; 3] = ["red", "green", "blue"];
+
+fn allowed_cubes() -> Bag {
+    Bag::new([("red", 12), ("green", 13), ("blue", 14)])
 }
 
-fn parse_color(input: &str) -> IResult<&str, Color> {
-    let (input, color) = alt((tag("red"), tag("blue"), tag("green")))(input)?;
+#[aoc(day2, part1)]
+pub fn solve_part1(input: &[Data]) -> Result<u32, Day2Error> {
+    let mut total = 0;
+    let mut seen = HashSet::new();
+    let bag = allowed_cubes();
 
-    use Color::*;
+    for game in input {
+        if !seen.insert(game.id) {
+            return Err(Day2Error::DuplicateGame { id: game.id });
+        }
 
-    let color = match color {
-        "red" => Red,
-        "green" => Green,
-        "blue" => Blue,
-        _ => panic!("Impossible input"),
-    };
+        if is_game_possible(&bag, game) {
+            total += game.id;
+        }
+    }
 
-    Ok((input, color))
+    Ok(total)
 }
 
-fn parse_u32(input: &str) -> IResult<&str, u32> {
-    let (input, number_string) = take_while1(is_number)(input)?;
-    let number = number_string.parse().unwrap();
+/// Like `solve_part1`, but to catch duplicates while keeping only the last ID
+/// rather than every one seen, the IDs have to increase.
+fn stream_part1(input: impl BufRead) -> Result<u64> {
+    let mut total = 0;
+    let mut previous = None;
+    let bag = allowed_cubes();
+
+    for game in stream_lines(input).parse(parse_game) {
+        let (_, game) = game?;
+        match previous {
+            Some(previous) if game.id == previous => return Err(Day2Error::DuplicateGame { id: game.id }.into()),
+            Some(previous) if game.id < previous => return Err(Day2Error::UnorderedGame { id: game.id, previous }.into()),
+            _ => previous = Some(game.id),
+        }
 
-    Ok((input, number))
+        if is_game_possible(&bag, &game) {
+            total += game.id as u64;
+        }
+    }
+
+    Ok(total)
 }
-fn is_number(c: char) -> bool {
-    ('0'..='9').contains(&c)
+
+pub fn is_game_possible(bag: &Bag, game: &Game) -> bool {
+    game.moves.iter().all(|single_move| bag.contains(single_move))
 }
 
-#[aoc(day2, part1)]
-fn solve_part1(input: &[Data]) -> u32 {
+/// The IDs of the games that are possible with `bag`.
+pub fn possible_games(games: &[Game], bag: &Bag) -> BTreeSet<u32> {
+    games.iter()
+        .filter(|game| is_game_possible(bag, game))
+        .map(|game| game.id)
+        .collect()
+}
+
+#[aoc(day2, part2)]
+pub fn solve_part2(input: &[Data]) -> Result<u32, Day2Error> {
     let mut total = 0;
+    for game in input {
+        total += minimum_bag(game).power(&POWER_COLORS)
+            .ok_or(Day2Error::PowerOverflow { id: game.id })?;
+    }
 
-    let allowed_cubes = Move {
-        r: 12,
-        g: 13,
-        b: 14,
-    };
+    Ok(total)
+}
 
-    for game in input {
-        if is_game_possible(&allowed_cubes, game) {
-            total += game.id;
-        }
+fn stream_part2(input: impl BufRead) -> Result<u64> {
+    let mut total = 0;
+    for game in stream_lines(input).parse(parse_game) {
+        let (_, game) = game?;
+
+        total += minimum_bag(&game).power(&POWER_COLORS)
+            .ok_or(Day2Error::PowerOverflow { id: game.id })? as u64;
     }
 
-    total
+    Ok(total)
 }
 
-fn is_game_possible(bag: &Move, game: &Game) -> bool {
+/// The smallest bag that `game` is possible with.
+pub fn minimum_bag(game: &Game) -> Bag {
+    let mut bag = Bag::default();
+
     for single_move in &game.moves {
-        let impossible =
-            bag.r < single_move.r ||
-            bag.g < single_move.g ||
-            bag.b < single_move.b;
-        
-        if impossible {
-            return false;
+        for (color, &count) in &single_move.counts {
+            let most = bag.counts.entry(color.clone()).or_insert(0);
+            *most = max(*most, count);
         }
     }
 
-    true
+    bag
 }
 
-#[aoc(day2, part2)]
-fn solve_part2(input: &[Data]) -> u32 {
-    let mut total = 0;
-    for game in input {
-        let minimum_bag = minimum_bag(game);
+/// The bag with the fewest cubes in total that makes at least `k` of `games`
+/// possible, or `None` if there aren't that many games. Of bags with the same
+/// total, the one with the fewest cubes of the alphabetically first colours
+/// wins.
+///
+/// Each colour's count in the best bag is the minimum for one of the games,
+/// so this tries every combination of those for all but the last colour,
+/// which takes time exponential in the number of colours.
+pub fn smallest_bag(games: &[Game], k: usize) -> Option<Bag> {
+    if k > games.len() {
+        return None;
+    }
+    if k == 0 {
+        return Some(Bag::default());
+    }
+
+    let minimums: Vec<_> = games.iter().map(minimum_bag).collect();
+    let colors: Vec<&str> = minimums.iter()
+        .flat_map(|bag| bag.counts.keys().map(String::as_str))
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .collect();
 
-        total += minimum_bag.r * minimum_bag.g * minimum_bag.b;
+    let Some((&last_color, colors)) = colors.split_last() else {
+        return Some(Bag::default());
+    };
+
+    let choices: Vec<Vec<u32>> = colors.iter()
+        .map(|color| {
+            let counts: BTreeSet<_> = minimums.iter().map(|bag| bag.get(color)).collect();
+            counts.into_iter().collect()
+        })
+        .collect();
+
+    let mut best: Option<(u64, Vec<u32>)> = None;
+    let mut indices = vec![0; colors.len()];
+
+    loop {
+        let counts: Vec<u32> = indices.iter().zip(&choices).map(|(&i, choice)| choice[i]).collect();
+
+        // The fewest cubes of the last colour that leave k games possible.
+        let mut last_counts: Vec<u32> = minimums.iter()
+            .filter(|bag| colors.iter().zip(&counts).all(|(color, &count)| bag.get(color) <= count))
+            .map(|bag| bag.get(last_color))
+            .collect();
+        if last_counts.len() >= k {
+            last_counts.sort_unstable();
+            let last_count = last_counts[k - 1];
+
+            let mut candidate = counts;
+            candidate.push(last_count);
+            let total = candidate.iter().map(|&count| count as u64).sum();
+            if best.as_ref().is_none_or(|(best_total, best_counts)| (total, &candidate) < (*best_total, best_counts)) {
+                best = Some((total, candidate));
+            }
+        }
+
+        // Step to the next combination, like an odometer.
+        let Some(position) = indices.iter().zip(&choices).rposition(|(&i, choice)| i + 1 < choice.len()) else {
+            break;
+        };
+        indices[position] += 1;
+        indices[position + 1..].fill(0);
     }
 
-    total
+    let (_, counts) = best?;
+    Some(Bag::new(colors.iter().copied().chain([last_color]).zip(counts)))
 }
 
-fn minimum_bag(game: &Game) -> Move {
-    let mut bag = Move {
-        r: 0,
-        g: 0,
-        b: 0,
-    };
+/// The number of ways to pick `k` things from `n`.
+fn binomial(n: u64, k: u64) -> BigInt {
+    if k > n {
+        return BigInt::zero();
+    }
 
-    for single_move in &game.moves {
-        bag.r = max(bag.r, single_move.r);
-        bag.g = max(bag.g, single_move.g);
-        bag.b = max(bag.b, single_move.b);
+    // Each partial product is itself a binomial coefficient, so the division is exact.
+    (0..k).fold(BigInt::one(), |ways, i| ways * (n - i) / (i + 1))
+}
+
+/// The ways to draw `single_move` from `bag`, out of `binomial(bag.total(), cubes)`.
+fn ways_to_draw(bag: &Bag, single_move: &Move) -> BigInt {
+    single_move.counts.iter()
+        .map(|(color, &count)| binomial(bag.get(color) as u64, count as u64))
+        .product()
+}
+
+/// The ways to draw every move in `game` from a bag of `size` cubes, whatever
+/// their colours.
+fn ways_to_draw_any(size: u64, game: &Game) -> BigInt {
+    game.moves.iter()
+        .map(|single_move| binomial(size, single_move.total()))
+        .product()
+}
+
+/// The chance of seeing every draw in `game` if they're taken from `bag`
+/// without replacement, with the cubes put back between draws.
+pub fn likelihood(bag: &Bag, game: &Game) -> BigRational {
+    let ways = ways_to_draw_all(bag, game);
+    if ways.is_zero() {
+        return BigRational::zero();
     }
 
-    bag
+    BigRational::new(ways, ways_to_draw_any(bag.total(), game))
+}
+
+/// Calls `f` with every bag of `size` cubes of `colors`, holding at least
+/// `minimum` of each, in increasing order of the counts for each colour in
+/// turn.
+fn for_each_bag(colors: &[String], minimum: &Bag, size: u32, mut f: impl FnMut(&Bag)) {
+    fn fill(colors: &[String], minimum: &Bag, remaining: u32, bag: &mut Bag, f: &mut impl FnMut(&Bag)) {
+        let Some((color, rest)) = colors.split_first() else {
+            if remaining == 0 {
+                f(bag);
+            }
+            return;
+        };
+
+        for count in minimum.get(color)..=remaining {
+            bag.counts.insert(color.clone(), count);
+            fill(rest, minimum, remaining - count, bag, f);
+        }
+    }
+
+    fill(colors, minimum, size, &mut Bag::default(), &mut f);
+}
+
+/// The ways to draw every move in `game` from `bag`.
+fn ways_to_draw_all(bag: &Bag, game: &Game) -> BigInt {
+    game.moves.iter().map(|single_move| ways_to_draw(bag, single_move)).product()
+}
+
+/// The bag of `size` cubes, in the colours seen in `game`, most likely to
+/// give its draws, along with that likelihood. Of equally likely bags, the
+/// one with the fewest cubes of the alphabetically first colours wins.
+/// `None` if no bag of that size could give the draws.
+pub fn most_likely_bag(game: &Game, size: u32) -> Option<(Bag, BigRational)> {
+    let minimum = minimum_bag(game);
+    let colors: Vec<_> = minimum.counts.keys().cloned().collect();
+
+    let mut best: Option<(Bag, BigInt)> = None;
+    for_each_bag(&colors, &minimum, size, |bag| {
+        let ways = ways_to_draw_all(bag, game);
+        if best.as_ref().is_none_or(|(_, best_ways)| ways > *best_ways) {
+            best = Some((bag.clone(), ways));
+        }
+    });
+
+    let (bag, ways) = best?;
+    Some((bag, BigRational::new(ways, ways_to_draw_any(size as u64, game))))
+}
+
+/// How likely each of `sizes` is to be the number of cubes in the bag, given
+/// the draws in `game`. Every size starts out equally likely, and so does
+/// every way of colouring that many cubes with the colours seen in `game`.
+/// `None` if no bag of any of the sizes could give the draws.
+pub fn size_posterior(game: &Game, sizes: impl IntoIterator<Item = u32>) -> Option<Vec<(u32, BigRational)>> {
+    let minimum = minimum_bag(game);
+    let colors: Vec<_> = minimum.counts.keys().cloned().collect();
+
+    let evidence: Vec<_> = sizes.into_iter()
+        .map(|size| {
+            let mut ways = BigInt::zero();
+            for_each_bag(&colors, &minimum, size, |bag| ways += ways_to_draw_all(bag, game));
+            if ways.is_zero() {
+                return (size, BigRational::zero());
+            }
+
+            // Spread evenly over every bag of this size, including the ones
+            // that can't give the draws.
+            let bags = match colors.len() as u64 {
+                0 => BigInt::one(),
+                colors => binomial(size as u64 + colors - 1, size as u64),
+            };
+            (size, BigRational::new(ways, bags * ways_to_draw_any(size as u64, game)))
+        })
+        .collect();
+
+    let total: BigRational = evidence.iter().map(|(_, chance)| chance).sum();
+    if total.is_zero() {
+        return None;
+    }
+
+    Some(evidence.into_iter().map(|(size, chance)| (size, chance / &total)).collect())
+}
+
+crate::registry::solutions! {
+    (2, 1, input_generator, solve_part1),
+    (2, 2, input_generator, solve_part2),
+}
+
+crate::registry::streaming_solutions! {
+    (2, 1, stream_part1),
+    (2, 2, stream_part2),
 }
 
 #[cfg(test)]
@@ -185,7 +425,7 @@ Game 5: 6 red, 1 blue, 3 green; 2 blue, 1 red, 2 green
     #[test]
     fn test_part1_example() {
         let input = super::input_generator(TEST_INPUT).unwrap();
-        let result = super::solve_part1(&input);
+        let result = super::solve_part1(&input).unwrap();
 
         assert_eq!(result, 8);
     }
@@ -193,8 +433,113 @@ Game 5: 6 red, 1 blue, 3 green; 2 blue, 1 red, 2 green
     #[test]
     fn test_part2_example() {
         let input = super::input_generator(TEST_INPUT).unwrap();
-        let result = super::solve_part2(&input);
+        let result = super::solve_part2(&input).unwrap();
 
         assert_eq!(result, 2286);
     }
+
+    #[test]
+    fn test_extra_colors() {
+        let input = super::input_generator("Game 1: 2 red, 1 yellow; 3 blue\nGame 2: 1 red, 1 red, 4 green\n").unwrap();
+        assert_eq!(input[0].moves[0].get("yellow"), 1);
+        assert_eq!(input[1].moves[0].get("red"), 2);
+
+        assert_eq!(super::solve_part1(&input).unwrap(), 2);
+        // Neither game shows all of red, green and blue.
+        assert_eq!(super::solve_part2(&input).unwrap(), 0);
+
+        let bag = super::Bag::new([("red", 2), ("blue", 3), ("yellow", 1)]);
+        assert!(super::is_game_possible(&bag, &input[0]));
+        assert!(!super::is_game_possible(&bag, &input[1]));
+    }
+
+    #[test]
+    fn test_queries() {
+        let input = super::input_generator(TEST_INPUT).unwrap();
+
+        assert_eq!(super::minimum_bag(&input[0]), super::Bag::new([("red", 4), ("green", 2), ("blue", 6)]));
+
+        let bag = super::Bag::new([("red", 12), ("green", 13), ("blue", 14)]);
+        assert_eq!(super::possible_games(&input, &bag), [1, 2, 5].into());
+        assert!(super::possible_games(&input, &super::Bag::default()).is_empty());
+    }
+
+    #[test]
+    fn test_smallest_bag() {
+        let input = super::input_generator(TEST_INPUT).unwrap();
+
+        assert_eq!(super::smallest_bag(&input, 0), Some(super::Bag::default()));
+        // Game 2 needs the fewest cubes, 4 blue, 3 green and 1 red.
+        assert_eq!(super::smallest_bag(&input, 1), Some(super::minimum_bag(&input[1])));
+        // Games 2 and 5 need as many cubes together as games 1 and 2, but fewer blue ones.
+        assert_eq!(super::smallest_bag(&input, 2), Some(super::Bag::new([("blue", 4), ("green", 3), ("red", 6)])));
+        assert_eq!(super::smallest_bag(&input, 5), Some(super::Bag::new([("blue", 15), ("green", 13), ("red", 20)])));
+        assert_eq!(super::smallest_bag(&input, 6), None);
+
+        for k in 0..=5 {
+            let bag = super::smallest_bag(&input, k).unwrap();
+            assert!(super::possible_games(&input, &bag).len() >= k);
+        }
+    }
+
+    fn ratio(numer: i64, denom: i64) -> num::BigRational {
+        num::BigRational::new(numer.into(), denom.into())
+    }
+
+    #[test]
+    fn test_likelihood() {
+        let input = super::input_generator("Game 1: 1 red, 1 blue\n").unwrap();
+        let game = &input[0];
+
+        assert_eq!(super::likelihood(&super::Bag::new([("red", 1), ("blue", 1)]), game), ratio(1, 1));
+        assert_eq!(super::likelihood(&super::Bag::new([("red", 2), ("blue", 2)]), game), ratio(2, 3));
+        assert_eq!(super::likelihood(&super::Bag::new([("red", 1), ("blue", 1), ("green", 1)]), game), ratio(1, 3));
+        assert_eq!(super::likelihood(&super::Bag::new([("red", 2)]), game), ratio(0, 1));
+    }
+
+    #[test]
+    fn test_most_likely_bag() {
+        let input = super::input_generator("Game 1: 1 red, 1 blue; 2 red\n").unwrap();
+        let game = &input[0];
+
+        // 1 blue and 3 red gives 3 ways to draw the first move and 3 the second, out of 6 each.
+        let (bag, chance) = super::most_likely_bag(game, 4).unwrap();
+        assert_eq!(bag, super::Bag::new([("blue", 1), ("red", 3)]));
+        assert_eq!(chance, ratio(1, 4));
+        assert_eq!(chance, super::likelihood(&bag, game));
+
+        assert!(super::most_likely_bag(game, 2).is_none());
+    }
+
+    #[test]
+    fn test_size_posterior() {
+        let input = super::input_generator("Game 1: 1 red, 1 blue; 2 red\n").unwrap();
+        let game = &input[0];
+
+        // Averaged over the bags of each size, the draws have a chance of 0,
+        // 8/36 / 4 and 13/36 / 5 with 2, 3 and 4 cubes.
+        let posterior = super::size_posterior(game, 2..=4).unwrap();
+        assert_eq!(posterior, [(2, ratio(0, 1)), (3, ratio(10, 23)), (4, ratio(13, 23))]);
+
+        assert!(super::size_posterior(game, 0..3).is_none());
+    }
+
+    #[test]
+    fn test_duplicate_game() {
+        let input = super::input_generator("Game 1: 1 red\nGame 1: 2 blue\n").unwrap();
+        let error = super::solve_part1(&input).unwrap_err();
+
+        assert_eq!(error.to_string(), "Game 1 appears more than once");
+
+        let error = super::stream_part1("Game 1: 1 red\nGame 1: 2 blue\n".as_bytes()).unwrap_err();
+        assert_eq!(error.to_string(), "Game 1 appears more than once");
+    }
+
+    #[test]
+    fn test_streaming_unordered_games() {
+        let error = super::stream_part1("Game 2: 1 red\nGame 1: 2 blue\n".as_bytes()).unwrap_err();
+        assert_eq!(error.to_string(), "Game 1 comes after game 2, but streamed games need increasing IDs");
+
+        assert_eq!(super::stream_part1("Game 1: 1 red\nGame 3: 2 blue\n".as_bytes()).unwrap(), 4);
+    }
 }
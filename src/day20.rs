@@ -1,4 +1,4 @@
-use std::collections::{HashMap, HashSet};
+use std::collections::HashMap;
 use std::collections::BTreeMap;
 use std::collections::VecDeque;
 
@@ -6,30 +6,50 @@ use aoc_runner_derive::aoc_generator;
 use aoc_runner_derive::aoc;
 
 use anyhow::Result;
-use anyhow::anyhow;
 
-type Input = (Vec<ModuleName>, Vec<Module>);
-type InputRef = Input;
+use num::Integer;
+
+use thiserror::Error;
+
+use crate::cycle;
+
+
+pub type Input = (Vec<ModuleName>, Vec<Module>);
+pub type InputRef = Input;
+
+#[derive(Debug, Error)]
+pub enum Day20Error {
+    #[error("There is no module named {0}")]
+    MissingModule(&'static str),
+    #[error("rx should be fed by a single conjunction")]
+    NoFeeder,
+    #[error("The counter starting at {counter} doesn't return to its starting state within {limit} presses")]
+    NoCycle { counter: String, limit: usize },
+    #[error("The counter starting at {counter} doesn't signal {feeder} only on the last press of its loop")]
+    IrregularCounter { counter: String, feeder: String },
+    #[error("The number of presses overflows")]
+    Overflow,
+}
 
 #[derive(Debug, Clone)]
-struct ModuleName(String);
+pub struct ModuleName(pub String);
 
 #[derive(Debug, Clone)]
-struct Module {
-    name: ModuleIndex,
-    module_type: ModuleType,
-    connections: Vec<ModuleIndex>,
+pub struct Module {
+    pub name: ModuleIndex,
+    pub module_type: ModuleType,
+    pub connections: Vec<ModuleIndex>,
 }
 
 #[derive(Debug, Clone, Copy)]
 #[derive(PartialEq, Eq)]
 #[derive(PartialOrd, Ord)]
 #[derive(Hash)]
-struct ModuleIndex(usize);
+pub struct ModuleIndex(pub usize);
 
 #[derive(Debug, Clone, Copy)]
 #[derive(PartialEq, Eq)]
-enum ModuleType {
+pub enum ModuleType {
     FlipFlop,
     Conjunction,
     Broadcast,
@@ -37,26 +57,22 @@ enum ModuleType {
 
 
 #[aoc_generator(day20)]
-fn input_generator(input: &str) -> Result<Input> {
-    let (input, result) = parse_input(input)
-        .map_err(|err| err.map(|err| anyhow!(nom::error::convert_error(input, err))))?;
-    if !input.is_empty() {
-        return Err(anyhow!("Had unparsed input after parsing: {}", input));
-    }
-    Ok(result)
+pub fn input_generator(input: &str) -> Result<Input> {
+    run_parser(input, parse_input)
 }
 
-type IResult<I, T> = nom::IResult<I, T, nom::error::VerboseError<I>>;
 use nom::bytes::complete::take_while1;
 use nom::character::complete::one_of;
 use nom::combinator::opt;
-use nom::multi::separated_list1;
 use nom::bytes::complete::tag;
 
+use crate::parse::IResult;
+use crate::parse::comma_separated;
+use crate::parse::lines;
+use crate::parse::run_parser;
+
 fn parse_input(input: &str) -> IResult<&str, Input> {
-    let (input, _) = opt(tag("\n"))(input)?;
-    let (input, str_modules) = separated_list1(tag("\n"), parse_module)(input)?;
-    let (input, _) = opt(tag("\n"))(input)?;
+    let (input, str_modules) = lines(parse_module)(input)?;
 
     let mut module_names: Vec<_> = str_modules.iter()
         .map(|module| ModuleName(module.name.to_owned()))
@@ -98,7 +114,7 @@ fn parse_module(input: &str) -> IResult<&str, StrModule<'_>> {
     let (input, module_type) = parse_module_type(input)?;
     let (input, name) = parse_name(input)?;
     let (input, _) = tag(" -> ")(input)?;
-    let (input, connections) = separated_list1(tag(", "), parse_name)(input)?;
+    let (input, connections) = comma_separated(parse_name)(input)?;
 
     Ok((input, StrModule {
         name,
@@ -138,21 +154,20 @@ enum ModuleState {
 #[derive(Debug, Clone, Copy)]
 #[derive(PartialEq, Eq)]
 #[derive(Hash)]
-enum Pulse {
+pub enum Pulse {
     Low,
     High,
 }
 
-#[aoc(day20, part1)]
-fn solve_part1(input: &InputRef) -> usize {
-    let mut module_states: Vec<_> = input.1.iter()
+fn initial_states(modules: &[Module]) -> Vec<ModuleState> {
+    modules.iter()
         .map(|module| {
             use ModuleType::*;
             match module.module_type {
                 Broadcast => ModuleState::Broadcast,
                 FlipFlop => ModuleState::FlipFlop { on: false },
                 Conjunction => {
-                    let connection_states: BTreeMap<_, _> = input.1.iter().enumerate()
+                    let connection_states: BTreeMap<_, _> = modules.iter().enumerate()
                         .filter(|(_, other_module)| other_module.connections.contains(&module.name))
                         .map(|(i, _)| (ModuleIndex(i), Pulse::Low))
                         .collect();
@@ -161,203 +176,199 @@ fn solve_part1(input: &InputRef) -> usize {
                 },
             }
         })
-        .collect();
-
-    let button_index = ModuleIndex(input.0.len());
-    let broadcast_index = input.0.iter().enumerate()
-        .filter(|(_, name)| name.0 == "broadcaster")
-        .map(|(i, _)| ModuleIndex(i))
-        .next()
-        .unwrap();
-
+        .collect()
+}
 
-    let mut pulse_counts = HashMap::new();
-    pulse_counts.insert(Pulse::Low, 0);
-    pulse_counts.insert(Pulse::High, 0);
+/// Sends a single low pulse from the button to the broadcaster and delivers
+/// pulses until the network settles, reporting each one to `on_pulse`.
+fn press_button(modules: &[Module], module_states: &mut [ModuleState], button_index: ModuleIndex, broadcast_index: ModuleIndex, mut on_pulse: impl FnMut(ModuleIndex, Pulse, ModuleIndex)) {
+    let mut pulses = VecDeque::new();
 
-    for _ in 0..1000 {
-        let mut pulses = VecDeque::new();
+    pulses.push_back((button_index, Pulse::Low, broadcast_index));
 
-        pulses.push_back((button_index, Pulse::Low, broadcast_index));
+    while let Some((source, pulse, dest)) = pulses.pop_front() {
+        on_pulse(source, pulse, dest);
+        if modules.len() <= dest.0 { continue; }
+        let dest_module = &modules[dest.0];
 
-        while let Some((source, pulse, dest)) = pulses.pop_front() {
-            /*
-            let source_name = if source.0 == input.0.len() {
-                "button"
-            } else {
-                &input.0[source.0].0
-            };
-            dbg!{source_name, pulse, &input.0[dest.0].0};
-            */
-            *pulse_counts.get_mut(&pulse).unwrap() += 1;
-            if input.1.len() <= dest.0 { continue; }
-            let dest_module = &input.1[dest.0];
+        use ModuleType::*;
+        let pulse_to_send = match dest_module.module_type {
+            Broadcast => Some(pulse),
+            FlipFlop => {
+                if pulse == Pulse::Low {
+                    if let ModuleState::FlipFlop { on } = &mut module_states[dest.0] {
+                        *on = !*on;
 
-            use ModuleType::*;
-            let pulse_to_send = match dest_module.module_type {
-                Broadcast => Some(pulse),
-                FlipFlop => {
-                    if pulse == Pulse::Low {
-                        if let ModuleState::FlipFlop { on } = &mut module_states[dest.0] {
-                            *on = !*on;
-
-                            if *on {
-                                Some(Pulse::High)
-                            } else {
-                                Some(Pulse::Low)
-                            }
+                        if *on {
+                            Some(Pulse::High)
                         } else {
-                            None
+                            Some(Pulse::Low)
                         }
                     } else {
                         None
                     }
-                },
-                Conjunction => {
-                    if let ModuleState::Conjunction { connection_states } = &mut module_states[dest.0] {
-                        *connection_states.get_mut(&source).unwrap() = pulse;
-                        let all_high = connection_states.iter().all(|(_, &pulse_state)| pulse_state == Pulse::High);
-                        if all_high {
-                            Some(Pulse::Low)
-                        } else {
-                            Some(Pulse::High)
-                        }
+                } else {
+                    None
+                }
+            },
+            Conjunction => {
+                if let ModuleState::Conjunction { connection_states } = &mut module_states[dest.0] {
+                    *connection_states.get_mut(&source).unwrap() = pulse;
+                    let all_high = connection_states.iter().all(|(_, &pulse_state)| pulse_state == Pulse::High);
+                    if all_high {
+                        Some(Pulse::Low)
                     } else {
-                        None
+                        Some(Pulse::High)
                     }
-                },
-            };
-
-            if let Some(pulse_to_send) = pulse_to_send {
-                for &connection in &dest_module.connections {
-                    pulses.push_back((dest_module.name, pulse_to_send, connection));
+                } else {
+                    None
                 }
+            },
+        };
+
+        if let Some(pulse_to_send) = pulse_to_send {
+            for &connection in &dest_module.connections {
+                pulses.push_back((dest_module.name, pulse_to_send, connection));
             }
         }
     }
+}
 
-    //dbg!{&pulse_counts};
-    pulse_counts[&Pulse::Low] * pulse_counts[&Pulse::High]
+fn find_module(input: &InputRef, name: &'static str) -> Result<ModuleIndex, Day20Error> {
+    input.0.iter().enumerate()
+        .filter(|(_, module_name)| module_name.0 == name)
+        .map(|(i, _)| ModuleIndex(i))
+        .next()
+        .ok_or(Day20Error::MissingModule(name))
 }
 
-#[aoc(day20, part2)]
-fn solve_part2(input: &InputRef) -> usize {
-    //render_graph(input).unwrap();
-    let mut module_states: Vec<_> = input.1.iter()
-        .map(|module| {
-            use ModuleType::*;
-            match module.module_type {
-                Broadcast => ModuleState::Broadcast,
-                FlipFlop => ModuleState::FlipFlop { on: false },
-                Conjunction => {
-                    let connection_states: BTreeMap<_, _> = input.1.iter().enumerate()
-                        .filter(|(_, other_module)| other_module.connections.contains(&module.name))
-                        .map(|(i, _)| (ModuleIndex(i), Pulse::Low))
-                        .collect();
+/// The state of the network between button presses, for stepping through
+/// it one press at a time.
+#[derive(Debug, Clone)]
+pub struct Simulation {
+    input: Input,
+    module_states: Vec<ModuleState>,
+    button_index: ModuleIndex,
+    broadcast_index: ModuleIndex,
+}
 
-                    ModuleState::Conjunction { connection_states, }
-                },
-            }
+impl Simulation {
+    pub fn new(input: Input) -> Result<Simulation, Day20Error> {
+        let broadcast_index = find_module(&input, "broadcaster")?;
+
+        Ok(Simulation {
+            module_states: initial_states(&input.1),
+            button_index: ModuleIndex(input.0.len()),
+            broadcast_index,
+            input,
         })
-        .collect();
+    }
 
-    let button_index = ModuleIndex(input.0.len());
-    let broadcast_index = input.0.iter().enumerate()
-        .filter(|(_, name)| name.0 == "broadcaster")
-        .map(|(i, _)| ModuleIndex(i))
-        .next()
-        .unwrap();
-    let rx_index = input.0.iter().enumerate()
-        .filter(|(_, name)| name.0 == "rx")
-        .map(|(i, _)| ModuleIndex(i))
-        .next()
-        .unwrap();
+    pub fn input(&self) -> &InputRef {
+        &self.input
+    }
 
-    let rx_predecessor = get_predecessors(input, rx_index)[0];
-    let rx_pred_preds = get_predecessors(input, rx_predecessor);
-    let keys: HashSet<_> = rx_pred_preds.into_iter()
-        .map(|predecessor| get_predecessors(input, predecessor)[0])
-        .collect();
+    /// The name of a module, or of the button, which isn't one.
+    pub fn name(&self, index: ModuleIndex) -> &str {
+        if index == self.button_index {
+            "button"
+        } else {
+            &self.input.0[index.0].0
+        }
+    }
 
-    let mut key_cycle_lengths = HashMap::new();
+    /// Presses the button once, returning every pulse it sets off as
+    /// `(source, pulse, destination)` in the order they're delivered.
+    pub fn press(&mut self) -> Vec<(ModuleIndex, Pulse, ModuleIndex)> {
+        let mut pulses = Vec::new();
+        press_button(&self.input.1, &mut self.module_states, self.button_index, self.broadcast_index, |source, pulse, dest| {
+            pulses.push((source, pulse, dest));
+        });
+        pulses
+    }
+}
 
-    dbg!{&keys};
+#[aoc(day20, part1)]
+pub fn solve_part1(input: &InputRef) -> Result<usize, Day20Error> {
+    let mut module_states = initial_states(&input.1);
 
-    for i in 1.. {
-        let mut pulses = VecDeque::new();
+    let button_index = ModuleIndex(input.0.len());
+    let broadcast_index = find_module(input, "broadcaster")?;
 
-        pulses.push_back((button_index, Pulse::Low, broadcast_index));
+    let mut pulse_counts = HashMap::new();
+    pulse_counts.insert(Pulse::Low, 0);
+    pulse_counts.insert(Pulse::High, 0);
 
-        while let Some((source, pulse, dest)) = pulses.pop_front() {
-            if pulse == Pulse::Low && dest == rx_index {
-                return i;
-            }
+    for _ in 0..1000 {
+        press_button(&input.1, &mut module_states, button_index, broadcast_index, |_, pulse, _| {
+            *pulse_counts.get_mut(&pulse).unwrap() += 1;
+        });
+    }
 
-            if dest.0 >= input.1.len() { continue; }
-            let dest_module = &input.1[dest.0];
+    //dbg!{&pulse_counts};
+    Ok(pulse_counts[&Pulse::Low] * pulse_counts[&Pulse::High])
+}
 
-            use ModuleType::*;
-            let pulse_to_send = match dest_module.module_type {
-                Broadcast => Some(pulse),
-                FlipFlop => {
-                    if pulse == Pulse::Low {
-                        if let ModuleState::FlipFlop { on } = &mut module_states[dest.0] {
-                            *on = !*on;
-
-                            if *on {
-                                Some(Pulse::High)
-                            } else {
-                                Some(Pulse::Low)
-                            }
-                        } else {
-                            None
-                        }
-                    } else {
-                        None
-                    }
-                },
-                Conjunction => {
-                    if let ModuleState::Conjunction { connection_states } = &mut module_states[dest.0] {
-                        *connection_states.get_mut(&source).unwrap() = pulse;
-                        let all_high = connection_states.iter().all(|(_, &pulse_state)| pulse_state == Pulse::High);
-                        if all_high {
-                            if keys.contains(&dest) {
-                                println!("{}: {:?}", i, dest);
-                                key_cycle_lengths.insert(dest, i);
-                                if key_cycle_lengths.len() == keys.len() {
-                                    use num::Integer;
-                                    
-                                    return key_cycle_lengths.iter()
-                                        .map(|(_, length)| *length)
-                                        .reduce(|a, b| a.lcm(&b)).unwrap();
-                                }
-                            }
-                            Some(Pulse::Low)
-                        } else {
-                            Some(Pulse::High)
-                        }
-                    } else {
-                        None
-                    }
-                },
-            };
+/// How many presses the search for a counter's loop gets before giving up.
+const MAX_COUNTER_PRESSES: usize = 1 << 24;
 
-            if let Some(pulse_to_send) = pulse_to_send {
-                for &connection in &dest_module.connections {
-                    pulses.push_back((dest_module.name, pulse_to_send, connection));
+/// The broadcaster drives several independent counters, each of which only
+/// signals `rx` on the press that completes its loop. Running each counter on
+/// its own and finding the period of its state gives the presses until they
+/// all line up, so long as each one signals the conjunction in front of `rx`
+/// on every multiple of its period and never in between.
+#[aoc(day20, part2)]
+pub fn solve_part2(input: &InputRef) -> Result<usize, Day20Error> {
+    //render_graph(input).unwrap();
+    let button_index = ModuleIndex(input.0.len());
+    let broadcast_index = find_module(input, "broadcaster")?;
+    let rx_index = find_module(input, "rx")?;
+
+    let feeders: Vec<_> = input.1.iter()
+        .filter(|module| module.connections.contains(&rx_index))
+        .collect();
+    let feeder_index = match feeders[..] {
+        [feeder] if feeder.module_type == ModuleType::Conjunction => feeder.name,
+        _ => return Err(Day20Error::NoFeeder),
+    };
+
+    let counter_starts = &input.1[broadcast_index.0].connections;
+
+    counter_starts.iter()
+        .map(|&counter_start| {
+            let mut modules = input.1.clone();
+            modules[broadcast_index.0].connections = vec![counter_start];
+
+            let counter_cycle = cycle::brent_within(&initial_states(&modules), |module_states| {
+                let mut module_states = module_states.clone();
+                press_button(&modules, &mut module_states, button_index, broadcast_index, |_, _, _| { });
+                module_states
+            }, MAX_COUNTER_PRESSES)
+            .ok_or_else(|| Day20Error::NoCycle { counter: input.0[counter_start.0].0.clone(), limit: MAX_COUNTER_PRESSES })?;
+
+            // Past the prefix every press repeats one from the first loop, so
+            // checking up to the end of it covers them all.
+            let mut module_states = initial_states(&modules);
+            for presses in 1..=counter_cycle.prefix + counter_cycle.period {
+                let mut signalled = false;
+                press_button(&modules, &mut module_states, button_index, broadcast_index, |_, pulse, dest| {
+                    signalled |= pulse == Pulse::High && dest == feeder_index;
+                });
+
+                if signalled != presses.is_multiple_of(counter_cycle.period) {
+                    return Err(Day20Error::IrregularCounter {
+                        counter: input.0[counter_start.0].0.clone(),
+                        feeder: input.0[feeder_index.0].0.clone(),
+                    });
                 }
             }
-        }
-    }
 
-    unreachable!()
-}
-
-fn get_predecessors(input: &InputRef, index: ModuleIndex) -> Vec<ModuleIndex> {
-    input.1.iter().enumerate()
-        .filter(|(_, other_module)| other_module.connections.contains(&index))
-        .map(|(_, other_module)| other_module.name)
-        .collect()
+            Ok(counter_cycle.period)
+        })
+        .try_fold(1usize, |a, period| {
+            let period = period?;
+            (a / a.gcd(&period)).checked_mul(period).ok_or(Day20Error::Overflow)
+        })
 }
 
 #[allow(unused)]
@@ -388,6 +399,11 @@ fn render_graph(input: &InputRef) -> Result<()> {
     Ok(())
 }
 
+crate::registry::solutions! {
+    (20, 1, input_generator, solve_part1),
+    (20, 2, input_generator, solve_part2),
+}
+
 #[cfg(test)]
 mod test {
     const TEST_INPUT_REPEAT: &'static str =
@@ -406,17 +422,91 @@ broadcaster -> a
 &inv -> b
 %b -> con
 &con -> output
+"#;
+    const TEST_INPUT_COUNTERS: &'static str =
+r#"
+broadcaster -> a, c
+%a -> b, ka
+%b -> ka
+&ka -> a, ia
+&ia -> out
+%c -> d, kb
+%d -> e
+%e -> kb
+&kb -> c, d, ib
+&ib -> out
+&out -> rx
 "#;
     #[test]
     fn test_part1_example() {
         let input = super::input_generator(TEST_INPUT_REPEAT).unwrap();
-        let result = super::solve_part1(&input);
+        let result = super::solve_part1(&input).unwrap();
 
         assert_eq!(result, 32000000);
 
         let input = super::input_generator(TEST_INPUT_INTERESTING).unwrap();
-        let result = super::solve_part1(&input);
+        let result = super::solve_part1(&input).unwrap();
 
         assert_eq!(result, 11687500);
     }
+
+    #[test]
+    fn test_simulation() {
+        use super::Pulse::*;
+
+        let input = super::input_generator(TEST_INPUT_INTERESTING).unwrap();
+        let mut simulation = super::Simulation::new(input).unwrap();
+
+        let pulses: Vec<_> = simulation.press().into_iter()
+            .map(|(source, pulse, dest)| (simulation.name(source), pulse, simulation.name(dest)))
+            .collect();
+        assert_eq!(pulses, [
+            ("button", Low, "broadcaster"),
+            ("broadcaster", Low, "a"),
+            ("a", High, "inv"),
+            ("a", High, "con"),
+            ("inv", Low, "b"),
+            ("con", High, "output"),
+            ("b", High, "con"),
+            ("con", Low, "output"),
+        ]);
+
+        assert_eq!(simulation.press().len(), 6);
+    }
+
+    #[test]
+    fn test_part2_counters() {
+        let input = super::input_generator(TEST_INPUT_COUNTERS).unwrap();
+        let result = super::solve_part2(&input).unwrap();
+
+        assert_eq!(result, 15);
+    }
+
+    #[test]
+    fn test_missing_modules() {
+        let input = super::input_generator("%a -> b\n%b -> a\n").unwrap();
+        let error = super::solve_part1(&input).unwrap_err();
+        assert_eq!(error.to_string(), "There is no module named broadcaster");
+
+        let input = super::input_generator("broadcaster -> a\n%a -> output\n").unwrap();
+        let error = super::solve_part2(&input).unwrap_err();
+        assert_eq!(error.to_string(), "There is no module named rx");
+    }
+
+    #[test]
+    fn test_part2_unexpected_networks() {
+        let input = super::input_generator("broadcaster -> a\n%a -> rx\n").unwrap();
+        let error = super::solve_part2(&input).unwrap_err();
+        assert_eq!(error.to_string(), "rx should be fed by a single conjunction");
+
+        // The counter signals out on its first press, but takes two to loop
+        let input = super::input_generator("broadcaster -> a\n%a -> out\n&out -> rx\n").unwrap();
+        let error = super::solve_part2(&input).unwrap_err();
+        assert_eq!(error.to_string(), "The counter starting at a doesn't signal out only on the last press of its loop");
+
+        // Every other press, the counter signals out twice as often as it loops
+        let input = super::input_generator("broadcaster -> a\n%a -> b\n%b -> ka\n&ka -> ia\n&ia -> out\n&out -> rx\n").unwrap();
+        let error = super::solve_part2(&input).unwrap_err();
+        assert_eq!(error.to_string(), "The counter starting at a doesn't signal out only on the last press of its loop");
+    }
 }
@@ -6,148 +6,62 @@ use aoc_runner_derive::aoc;
 use anyhow::Result;
 use anyhow::anyhow;
 
-use ndarray::Array2;
-use ndarray::ShapeBuilder;
-
-type Input = (Pos, Array2<Data>);
-type InputRef = (Pos, Array2<Data>);
-type Data = bool;
-
-#[derive(Debug, Clone, Copy)]
-#[derive(PartialEq, Eq)]
-#[derive(Hash)]
-struct Pos {
-    i: usize,
-    j: usize,
-}
-
-impl Pos {
-    fn try_move(&self, direction: Direction, bounds: (usize, usize)) -> Option<Self> {
-        use Direction::*;
-        match direction {
-            Up => {
-                if self.j > 0 {
-                    Some(Pos {
-                        i: self.i,
-                        j: self.j - 1,
-                    })
-                } else {
-                    None
-                }
-            },
-            Right => {
-                if self.i < bounds.0 - 1 {
-                    Some(Pos {
-                        i: self.i + 1,
-                        j: self.j,
-                    })
-                } else {
-                    None
-                }
-            },
-            Down => {
-                if self.j < bounds.1 - 1 {
-                    Some(Pos {
-                        i: self.i,
-                        j: self.j + 1,
-                    })
-                } else {
-                    None
-                }
-            },
-            Left => {
-                if self.i > 0 {
-                    Some(Pos {
-                        i: self.i - 1,
-                        j: self.j,
-                    })
-                } else {
-                    None
-                }
-            },
-        }
-    }
-
-    fn neighbors<'a>(&'a self, shape: &'a [usize]) -> impl Iterator<Item = Pos> + 'a  {
-        DIRECTIONS.into_iter()
-            .filter_map(move |direction| {
-                self.try_move(direction, (shape[1], shape[0])).map(|neighbor| neighbor)
-            })
-    }
-}
-
-impl std::ops::Index<Pos> for Array2<bool> {
-    type Output = <Self as std::ops::Index<ndarray::Ix2>>::Output;
-
-    fn index(&self, index: Pos) -> &Self::Output {
-        &self[(index.i, index.j)]
-    }
+use thiserror::Error;
+
+use crate::grid::Grid;
+use crate::grid::Pos;
+use crate::par;
+
+pub type Input = (Pos, Grid<Data>);
+pub type InputRef = (Pos, Grid<Data>);
+pub type Data = bool;
+
+#[derive(Debug, Error)]
+pub enum Day21Error {
+    #[error("The garden is {rows} by {cols} but it has to be square")]
+    NotSquare { rows: usize, cols: usize },
+    #[error("The start at row {}, column {} isn't in the middle of the garden", .0.row + 1, .0.col + 1)]
+    OffCenter(Pos),
+    #[error("The rock at row {}, column {} is on the edge or a middle line of the garden, which have to be clear", .0.row + 1, .0.col + 1)]
+    Obstructed(Pos),
 }
 
 #[aoc_generator(day21)]
-fn input_generator(input: &str) -> Result<Input> {
-    let mut row_data = Vec::new();
-
+pub fn input_generator(input: &str) -> Result<Input> {
     let mut start = None;
 
-    for (j, line) in input.lines().filter(|line| !line.is_empty()).enumerate()  {
-        let mut row = Vec::new();
-        for (i, c) in line.char_indices() {
-            let cell = match c {
-                '.' => Ok(false),
-                'S' => Ok(false),
-                '#' => Ok(true),
-                _ => Err(anyhow!("Invalid character")),
-            }?;
-
-            if c == 'S' {
-                start = Some(Pos { i, j });
-            }
-
-            row.push(cell);
+    let grid = Grid::parse_indexed(input, |pos, c| {
+        if c == 'S' {
+            start = Some(pos);
         }
 
-        row_data.push(row);
-    }
-
-    let cols = row_data[0].len();
-    let rows = row_data.len();
-
-    let data: Vec<_> = row_data.into_iter().flatten().collect();
-    let shape = (rows, cols).strides((cols, 1));
+        match c {
+            '.' => Ok(false),
+            'S' => Ok(false),
+            '#' => Ok(true),
+            _ => Err(anyhow!("Invalid character")),
+        }
+    })?;
 
-    Ok((start.unwrap(), Array2::from_shape_vec(shape, data).unwrap()))
-}
+    let start = start.ok_or_else(|| anyhow!("No start position"))?;
 
-enum Direction {
-    Up,
-    Right,
-    Down,
-    Left,
+    Ok((start, grid))
 }
 
-const DIRECTIONS: [Direction; 4] = [
-    Direction::Up,
-    Direction::Right,
-    Direction::Down,
-    Direction::Left,
-];
-
 #[aoc(day21, part1)]
-fn solve_part1(input: &InputRef) -> usize {
-    solve_part1_inner(input.0, &input.1, 64)
+pub fn solve_part1(input: &InputRef) -> Result<usize, Day21Error> {
+    Ok(solve_part1_inner(input.0, &input.1, 64))
 }
-fn solve_part1_inner(start: Pos, array: &Array2<bool>, steps: usize) -> usize {
+pub fn solve_part1_inner(start: Pos, array: &Grid<bool>, steps: usize) -> usize {
     let mut current_positions = HashSet::new();
     let mut next_positions = HashSet::new();
     next_positions.insert(start);
 
-    let shape = array.shape();
     for _ in 0..steps {
         std::mem::swap(&mut current_positions, &mut next_positions);
 
         for &pos in &current_positions {
-            for neighbor in pos.neighbors(shape) {
+            for (_, neighbor) in array.neighbors(pos) {
                 if !array[neighbor] {
                     next_positions.insert(neighbor);
                 }
@@ -157,18 +71,17 @@ fn solve_part1_inner(start: Pos, array: &Array2<bool>, steps: usize) -> usize {
 
     next_positions.len()
 }
-fn solve_part1_inner_iterated(start: Pos, array: &Array2<bool>, maximum_steps: usize) -> (usize, usize) {
+pub fn solve_part1_inner_iterated(start: Pos, array: &Grid<bool>, maximum_steps: usize) -> (usize, usize) {
     let mut current_positions = HashSet::new();
     let mut next_positions = HashSet::new();
     let mut next_next_positions = HashSet::new();
     next_positions.insert(start);
 
-    let shape = array.shape();
     if maximum_steps % 2 == 1 {
         std::mem::swap(&mut current_positions, &mut next_positions);
 
         for &pos in &current_positions {
-            for neighbor in pos.neighbors(shape) {
+            for (_, neighbor) in array.neighbors(pos) {
                 if !array[neighbor] {
                     next_positions.insert(neighbor);
                 }
@@ -181,7 +94,7 @@ fn solve_part1_inner_iterated(start: Pos, array: &Array2<bool>, maximum_steps: u
         std::mem::swap(&mut current_positions, &mut next_next_positions);
 
         for &pos in &current_positions {
-            for neighbor in pos.neighbors(shape) {
+            for (_, neighbor) in array.neighbors(pos) {
                 if !array[neighbor] {
                     next_positions.insert(neighbor);
                 }
@@ -189,7 +102,7 @@ fn solve_part1_inner_iterated(start: Pos, array: &Array2<bool>, maximum_steps: u
         }
 
         for &pos in &next_positions {
-            for neighbor in pos.neighbors(shape) {
+            for (_, neighbor) in array.neighbors(pos) {
                 if !array[neighbor] {
                     next_next_positions.insert(neighbor);
                 }
@@ -206,102 +119,73 @@ fn solve_part1_inner_iterated(start: Pos, array: &Array2<bool>, maximum_steps: u
 }
 
 #[aoc(day21, part2)]
-fn solve_part2(input: &InputRef) -> usize {
+pub fn solve_part2(input: &InputRef) -> Result<usize, Day21Error> {
     solve_part2_inner(input, 26501365)
 }
-fn solve_part2_inner(input: &InputRef, maximum_steps: usize) -> usize {
+pub fn solve_part2_inner(input: &InputRef, maximum_steps: usize) -> Result<usize, Day21Error> {
     let side_length = {
-        let shape = input.1.shape();
-        assert_eq!(shape[0], shape[1]);
-        shape[0]
+        let (rows, cols) = input.1.bounds();
+        if rows != cols {
+            return Err(Day21Error::NotSquare { rows, cols });
+        }
+        rows
     };
     let start = input.0;
-    assert_eq!(start.i, side_length / 2);
-    assert_eq!(start.j, side_length / 2);
+    if start.col != side_length / 2 || start.row != side_length / 2 {
+        return Err(Day21Error::OffCenter(start));
+    }
+    // The tiling below counts on reaching every copy of the garden by walking
+    // straight along these lines, as in the shortest path on an empty plot.
+    let obstruction = input.1.indexed_iter()
+        .find(|&(pos, &rock)| {
+            let on_line = |n: usize| n == 0 || n == side_length / 2 || n == side_length - 1;
+            rock && (on_line(pos.row) || on_line(pos.col))
+        });
+    if let Some((pos, _)) = obstruction {
+        return Err(Day21Error::Obstructed(pos));
+    }
     let array = &input.1;
-    // Split it into cases
-    // Center
-    let center = solve_part1_inner_iterated(input.0, &input.1, maximum_steps).1;
-
-    // Compass Points
-    // Up
-    let up = if maximum_steps >= start.j + 1 {
-        let initial_steps = maximum_steps - input.0.j - 1;
-        let start = Pos { i: start.i, j: side_length - 1 };
-
-        solve_cardinal(array, start, initial_steps, side_length)
-    } else { 0 };
-    // Down
-    let down = if maximum_steps >= start.j + 1 {
-        let initial_steps = maximum_steps - input.0.j - 1;
-        let start = Pos { i: start.i, j: 0 };
-
-        solve_cardinal(array, start, initial_steps, side_length)
-    } else { 0 };
-    // Left
-    let left = if maximum_steps >= start.j + 1 {
-        let initial_steps = maximum_steps - input.0.j - 1;
-        let start = Pos { i: side_length - 1, j: start.j, };
-
-        solve_cardinal(array, start, initial_steps, side_length)
-    } else { 0 };
-    // Right
-    let right = if maximum_steps >= start.j + 1 {
-        let initial_steps = maximum_steps - input.0.j - 1;
-        let start = Pos { i: 0, j: start.j, };
-
-        solve_cardinal(array, start, initial_steps, side_length)
-    } else { 0 };
-
-    // Diagonals
-    // Up Left
-    let up_left = if maximum_steps >= start.j + start.i + 2 {
-        let initial_steps = maximum_steps - input.0.j - input.0.i - 2;
-        let start = Pos { i: side_length - 1, j: side_length - 1 };
-
-        solve_diagonal(array, start, initial_steps, side_length)
-    } else { 0 };
-    // Up Right
-    let up_right = if maximum_steps >= start.j + start.i + 2 {
-        let initial_steps = maximum_steps - input.0.j - input.0.i - 2;
-        let start = Pos { i: 0, j: side_length - 1 };
-
-        solve_diagonal(array, start, initial_steps, side_length)
-    } else { 0 };
-    // Down Right
-    let down_right = if maximum_steps >= start.j + start.i + 2 {
-        let initial_steps = maximum_steps - input.0.j - input.0.i - 2;
-        let start = Pos { i: 0, j: 0 };
-
-        solve_diagonal(array, start, initial_steps, side_length)
-    } else { 0 };
-    // Down Left
-    let down_left = if maximum_steps >= start.j + start.i + 2 {
-        let initial_steps = maximum_steps - input.0.j - input.0.i - 2;
-        let start = Pos { i: side_length - 1, j: 0 };
-
-        solve_diagonal(array, start, initial_steps, side_length)
-    } else { 0 };
-
-    let reachable_points =
-        dbg!{center} +
-        dbg!{up} +
-        dbg!{down} +
-        dbg!{left} +
-        dbg!{right} +
-        dbg!{up_left} +
-        dbg!{up_right} +
-        dbg!{down_right} +
-        dbg!{down_left};
-    reachable_points
+    // Split it into cases: the starting garden, the four straight lines of
+    // copies entered from one edge, and the four quadrants entered from a corner.
+    use Region::*;
+    let cardinal_steps = maximum_steps.checked_sub(start.row + 1);
+    let diagonal_steps = maximum_steps.checked_sub(start.row + start.col + 2);
+    let last = side_length - 1;
+    let regions = vec![
+        Center,
+        // Up, down, left and right
+        Cardinal(Pos { row: last, col: start.col }),
+        Cardinal(Pos { row: 0, col: start.col }),
+        Cardinal(Pos { row: start.row, col: last }),
+        Cardinal(Pos { row: start.row, col: 0 }),
+        // Up left, up right, down right and down left
+        Diagonal(Pos { row: last, col: last }),
+        Diagonal(Pos { row: last, col: 0 }),
+        Diagonal(Pos { row: 0, col: 0 }),
+        Diagonal(Pos { row: 0, col: last }),
+    ];
+
+    let reachable_points = par::map(regions, |region| match region {
+        Center => solve_part1_inner_iterated(start, array, maximum_steps).1,
+        Cardinal(entry) => cardinal_steps.map_or(0, |steps| solve_cardinal(array, entry, steps, side_length)),
+        Diagonal(entry) => diagonal_steps.map_or(0, |steps| solve_diagonal(array, entry, steps, side_length)),
+    });
+
+    Ok(reachable_points.into_iter().sum())
+}
+
+/// A part of the infinite garden counted on its own, with the plot where the
+/// walk first enters each copy of the garden in it.
+enum Region {
+    Center,
+    Cardinal(Pos),
+    Diagonal(Pos),
 }
 
-fn solve_cardinal(array: &Array2<bool>, start: Pos, initial_steps: usize, side_length: usize) -> usize {
+fn solve_cardinal(array: &Grid<bool>, start: Pos, initial_steps: usize, side_length: usize) -> usize {
     let mut reachable_points = 0;
     let mut steps = initial_steps % side_length;
 
-    dbg!{initial_steps, steps};
-
     let (stable_steps_start, maximum_reachable_start) = solve_part1_inner_iterated(start, array, initial_steps);
     let (stable_steps_off, maximum_reachable_off) = if initial_steps >= side_length {
         solve_part1_inner_iterated(start, array, initial_steps - side_length)
@@ -327,7 +211,7 @@ fn solve_cardinal(array: &Array2<bool>, start: Pos, initial_steps: usize, side_l
     }
     reachable_points
 }
-fn solve_diagonal(array: &Array2<bool>, start: Pos, initial_steps: usize, side_length: usize) -> usize {
+fn solve_diagonal(array: &Grid<bool>, start: Pos, initial_steps: usize, side_length: usize) -> usize {
     let mut reachable_points = 0;
     let mut steps = initial_steps % side_length;
 
@@ -363,8 +247,21 @@ fn solve_diagonal(array: &Array2<bool>, start: Pos, initial_steps: usize, side_l
     reachable_points
 }
 
+crate::registry::solutions! {
+    (21, 1, input_generator, solve_part1),
+    (21, 2, input_generator, solve_part2),
+}
+
 #[cfg(test)]
 mod test {
+    #[cfg(not(target_arch = "wasm32"))]
+    use proptest::prelude::*;
+
+    #[cfg(not(target_arch = "wasm32"))]
+    use crate::grid::Grid;
+    #[cfg(not(target_arch = "wasm32"))]
+    use crate::grid::Pos;
+
     const TEST_INPUT: &'static str =
 r#"
 ...........
@@ -409,32 +306,49 @@ r#"
     fn test_part2_simple() {
         let input = super::input_generator(TEST_SIMPLE).unwrap();
 
-        let result = super::solve_part2_inner(&input, 1);
+        let result = super::solve_part2_inner(&input, 1).unwrap();
         assert_eq!(result, 4);
 
-        let result = super::solve_part2_inner(&input, 2);
+        let result = super::solve_part2_inner(&input, 2).unwrap();
         assert_eq!(result, 9);
 
-        let result = super::solve_part2_inner(&input, 3);
+        let result = super::solve_part2_inner(&input, 3).unwrap();
         assert_eq!(result, 16);
 
-        let result = super::solve_part2_inner(&input, 4);
+        let result = super::solve_part2_inner(&input, 4).unwrap();
         assert_eq!(result, 25);
 
-        let result = super::solve_part2_inner(&input, 5);
+        let result = super::solve_part2_inner(&input, 5).unwrap();
         assert_eq!(result, 36);
 
-        let result = super::solve_part2_inner(&input, 12);
+        let result = super::solve_part2_inner(&input, 12).unwrap();
         assert_eq!(result, 169);
     }
 
+    #[test]
+    fn test_invalid_garden() {
+        let input = super::input_generator("...\nS..\n...\n").unwrap();
+        let error = super::solve_part2(&input).unwrap_err();
+        assert_eq!(error.to_string(), "The start at row 2, column 1 isn't in the middle of the garden");
+
+        let input = super::input_generator("...\n.S.\n").unwrap();
+        let error = super::solve_part2(&input).unwrap_err();
+        assert_eq!(error.to_string(), "The garden is 2 by 3 but it has to be square");
+
+        let input = super::input_generator(TEST_INPUT).unwrap();
+        let error = super::solve_part2(&input).unwrap_err();
+        assert_eq!(error.to_string(), "The rock at row 2, column 6 is on the edge or a middle line of the garden, which have to be clear");
+    }
+
     #[test]
     fn test_part2_wall() {
         let input = super::input_generator(TEST_WALL).unwrap();
 
-        dbg!{super::solve_part2_inner(&input, 12)};
-
-        assert!(false);
+        // The rocks are off the edges and middle lines, so they're allowed, and
+        // the counts match walking the tiled garden step by step.
+        assert_eq!(super::solve_part2_inner(&input, 12).unwrap(), 161);
+        assert_eq!(super::solve_part2_inner(&input, 30).unwrap(), 907);
+        assert_eq!(super::solve_part2_inner(&input, 100).unwrap(), 9574);
     }
 
     
@@ -443,26 +357,89 @@ r#"
     fn test_part2_example() {
         let input = super::input_generator(TEST_INPUT).unwrap();
 
-        let result = super::solve_part2_inner(&input, 6);
+        let result = super::solve_part2_inner(&input, 6).unwrap();
         assert_eq!(result, 16);
 
-        let result = super::solve_part2_inner(&input, 10);
+        let result = super::solve_part2_inner(&input, 10).unwrap();
         assert_eq!(result, 50);
 
-        let result = super::solve_part2_inner(&input, 50);
+        let result = super::solve_part2_inner(&input, 50).unwrap();
         assert_eq!(result, 1594);
 
-        let result = super::solve_part2_inner(&input, 100);
+        let result = super::solve_part2_inner(&input, 100).unwrap();
         assert_eq!(result, 6536);
 
-        let result = super::solve_part2_inner(&input, 500);
+        let result = super::solve_part2_inner(&input, 500).unwrap();
         assert_eq!(result, 167004);
 
-        let result = super::solve_part2_inner(&input, 1000);
+        let result = super::solve_part2_inner(&input, 1000).unwrap();
         assert_eq!(result, 668697);
 
-        let result = super::solve_part2_inner(&input, 5000);
+        let result = super::solve_part2_inner(&input, 5000).unwrap();
         assert_eq!(result, 16733044);
     }
     */
+
+    /// Square gardens with the start in the middle. Unless `anywhere` is set,
+    /// the border, middle row and middle column are kept clear like in the
+    /// real inputs.
+    #[cfg(not(target_arch = "wasm32"))]
+    fn garden(anywhere: bool) -> impl Strategy<Value = String> {
+        (1usize..=4)
+            .prop_flat_map(|half| {
+                let side = 2 * half + 1;
+                (Just(side), proptest::collection::vec(proptest::bool::weighted(0.3), side * side))
+            })
+            .prop_map(move |(side, rocks)| {
+                let mut text = String::new();
+                for row in 0..side {
+                    for col in 0..side {
+                        let clear = row == 0 || col == 0 || row == side - 1 || col == side - 1 || row == side / 2 || col == side / 2;
+                        text.push(match () {
+                            _ if row == side / 2 && col == side / 2 => 'S',
+                            _ if (anywhere || !clear) && rocks[row * side + col] => '#',
+                            _ => '.',
+                        });
+                    }
+                    text.push('\n');
+                }
+                text
+            })
+    }
+
+    /// Walks the garden tiled `copies` times in each direction, which is
+    /// exact as long as the steps can't reach the edge of the tiling.
+    #[cfg(not(target_arch = "wasm32"))]
+    fn walk_tiled(start: Pos, grid: &Grid<bool>, copies: usize, steps: usize) -> usize {
+        let (rows, cols) = grid.bounds();
+        let tiles = 2 * copies + 1;
+        let tiled = Grid::from_fn((rows * tiles, cols * tiles), |pos| grid[Pos::new(pos.row % rows, pos.col % cols)]);
+        let start = Pos::new(start.row + rows * copies, start.col + cols * copies);
+
+        super::solve_part1_inner(start, &tiled, steps)
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    proptest! {
+        #[test]
+        fn test_part2_matches_tiled_walk(garden in garden(false), steps in 0usize..40) {
+            let (start, grid) = super::input_generator(&garden).unwrap();
+            let copies = steps / grid.rows() + 1;
+
+            let expected = walk_tiled(start, &grid, copies, steps);
+            let result = super::solve_part2_inner(&(start, grid), steps).unwrap();
+            prop_assert_eq!(result, expected, "after {} steps in\n{}", steps, garden);
+        }
+
+        #[test]
+        fn test_part2_rejects_or_matches(garden in garden(true), steps in 0usize..40) {
+            let (start, grid) = super::input_generator(&garden).unwrap();
+            let copies = steps / grid.rows() + 1;
+
+            let expected = walk_tiled(start, &grid, copies, steps);
+            if let Ok(result) = super::solve_part2_inner(&(start, grid), steps) {
+                prop_assert_eq!(result, expected, "after {} steps in\n{}", steps, garden);
+            }
+        }
+    }
 }
@@ -1,49 +1,52 @@
 use std::collections::HashMap;
 use std::collections::HashSet;
+use std::io::BufRead;
 
 use aoc_runner_derive::aoc_generator;
 use aoc_runner_derive::aoc;
 
 use anyhow::Result;
-use anyhow::anyhow;
 
-type Input = Vec<Data>;
-type InputRef = [Data];
-type Data = (Pos3, Pos3);
+use thiserror::Error;
+
+use crate::par;
+
+pub type Input = Vec<Data>;
+pub type InputRef = [Data];
+pub type Data = (Pos3, Pos3);
 
 #[derive(Debug, Clone, Copy)]
 #[derive(PartialEq, Eq)]
 #[derive(Hash)]
-struct Pos3 {
-    x: i64,
-    y: i64,
-    z: i64,
+pub struct Pos3 {
+    pub x: i64,
+    pub y: i64,
+    pub z: i64,
+}
+
+#[derive(Debug, Error)]
+pub enum Day22Error {
+    #[error("Brick {brick} starts at height {z}, below the lowest layer")]
+    BelowGround { brick: usize, z: i64 },
 }
 
 
 #[aoc_generator(day22)]
-fn input_generator(input: &str) -> Result<Input> {
-    let (input, result) = parse_input(input)
-        .map_err(|err| err.map(|err| anyhow!(nom::error::convert_error(input, err))))?;
-    if !input.is_empty() {
-        return Err(anyhow!("Had unparsed input after parsing: {}", input));
-    }
-    Ok(result)
+pub fn input_generator(input: &str) -> Result<Input> {
+    run_parser(input, parse_input)
 }
 
-type IResult<I, T> = nom::IResult<I, T, nom::error::VerboseError<I>>;
-use nom::bytes::complete::take_while1;
-use nom::combinator::opt;
-use nom::multi::separated_list1;
 use nom::bytes::complete::tag;
 use nom::sequence::tuple;
 
-fn parse_input(input: &str) -> IResult<&str, Input> {
-    let (input, _) = opt(tag("\n"))(input)?;
-    let (input, result) = separated_list1(tag("\n"), parse_block)(input)?;
-    let (input, _) = opt(tag("\n"))(input)?;
+use crate::parse::IResult;
+use crate::parse::lines;
+use crate::parse::run_parser;
+use crate::parse::signed;
+use crate::parse::stream_lines;
 
-    Ok((input, result))
+fn parse_input(input: &str) -> IResult<&str, Input> {
+    lines(parse_block)(input)
 }
 
 fn parse_block(input: &str) -> IResult<&str, Data> {
@@ -56,11 +59,11 @@ fn parse_block(input: &str) -> IResult<&str, Data> {
 
 fn parse_pos3(input: &str) -> IResult<&str, Pos3> {
     let (input, (x, _, y, _, z)) = tuple((
-        parse_i64,
+        signed,
         tag(","),
-        parse_i64,
+        signed,
         tag(","),
-        parse_i64,
+        signed,
     ))(input)?;
 
     Ok((input, Pos3 {
@@ -70,18 +73,22 @@ fn parse_pos3(input: &str) -> IResult<&str, Pos3> {
     }))
 }
 
-fn parse_i64(input: &str) -> IResult<&str, i64> {
-    let (input, number_string) = take_while1(is_number)(input)?;
-    let number = number_string.parse().unwrap();
+/// Bricks settle on the ground at height 1, so none can start any lower.
+fn check_bricks(input: &InputRef) -> Result<(), Day22Error> {
+    for (i, (p1, p2)) in input.iter().enumerate() {
+        let z = std::cmp::min(p1.z, p2.z);
+        if z < 1 {
+            return Err(Day22Error::BelowGround { brick: i + 1, z });
+        }
+    }
 
-    Ok((input, number))
-}
-fn is_number(c: char) -> bool {
-    ('0'..='9').contains(&c)
+    Ok(())
 }
 
 #[aoc(day22, part1)]
-fn solve_part1(input: &InputRef) -> usize {
+pub fn solve_part1(input: &InputRef) -> Result<usize, Day22Error> {
+    check_bricks(input)?;
+
     let mut heights: Vec<_> = input.iter().enumerate()
         .map(|(i, (p1, p2))| {
             (std::cmp::min(p1.z, p2.z), i)
@@ -127,7 +134,7 @@ fn solve_part1(input: &InputRef) -> usize {
         }
     }
 
-    (0..input.len()).filter(|i| !supporting_blocks.contains(i)).count()
+    Ok((0..input.len()).filter(|i| !supporting_blocks.contains(i)).count())
 }
 
 fn footprint((p1, p2): &(Pos3, Pos3)) -> impl Iterator<Item = (i64, i64)> {
@@ -139,7 +146,9 @@ fn footprint((p1, p2): &(Pos3, Pos3)) -> impl Iterator<Item = (i64, i64)> {
 }
 
 #[aoc(day22, part2)]
-fn solve_part2(input: &InputRef) -> usize {
+pub fn solve_part2(input: &InputRef) -> Result<usize, Day22Error> {
+    check_bricks(input)?;
+
     let mut heights: Vec<_> = input.iter().enumerate()
         .map(|(i, (p1, p2))| {
             (std::cmp::min(p1.z, p2.z), i)
@@ -187,9 +196,7 @@ fn solve_part2(input: &InputRef) -> usize {
         }
     }
 
-    let mut total_fallen = 0;
-
-    for i in 0..input.len() {
+    let fallen = par::map((0..input.len()).collect(), |i| {
         let mut chain = HashSet::new();
         chain.insert(i);
 
@@ -209,10 +216,37 @@ fn solve_part2(input: &InputRef) -> usize {
             }
         }
 
-        total_fallen += chain.len() - 1;
-    }
+        chain.len() - 1
+    });
 
-    total_fallen
+    Ok(fallen.into_iter().sum())
+}
+
+/// Parses the bricks a line at a time, so the input text is never held
+/// whole. Any brick can land on any other, though, so every brick still has
+/// to be kept, and memory grows with the number of bricks.
+fn stream_input(input: impl BufRead) -> Result<Input> {
+    stream_lines(input).parse(parse_block)
+        .map(|brick| Ok(brick?.1))
+        .collect()
+}
+
+fn stream_part1(input: impl BufRead) -> Result<usize> {
+    Ok(solve_part1(&stream_input(input)?)?)
+}
+
+fn stream_part2(input: impl BufRead) -> Result<usize> {
+    Ok(solve_part2(&stream_input(input)?)?)
+}
+
+crate::registry::solutions! {
+    (22, 1, input_generator, solve_part1),
+    (22, 2, input_generator, solve_part2),
+}
+
+crate::registry::streaming_solutions! {
+    (22, 1, stream_part1),
+    (22, 2, stream_part2),
 }
 
 #[cfg(test)]
@@ -230,7 +264,7 @@ r#"
     #[test]
     fn test_part1_example() {
         let input = super::input_generator(TEST_INPUT).unwrap();
-        let result = super::solve_part1(&input);
+        let result = super::solve_part1(&input).unwrap();
 
         assert_eq!(result, 5);
     }
@@ -238,8 +272,18 @@ r#"
     #[test]
     fn test_part2_example() {
         let input = super::input_generator(TEST_INPUT).unwrap();
-        let result = super::solve_part2(&input);
+        let result = super::solve_part2(&input).unwrap();
 
         assert_eq!(result, 7);
     }
+
+    #[test]
+    fn test_below_ground() {
+        let input = super::input_generator("1,0,1~1,2,1\n0,0,0~2,0,0\n").unwrap();
+
+        let error = super::solve_part1(&input).unwrap_err();
+        assert_eq!(error.to_string(), "Brick 2 starts at height 0, below the lowest layer");
+
+        assert!(super::solve_part2(&input).is_err());
+    }
 }
@@ -0,0 +1,317 @@
+use aoc_runner_derive::aoc_generator;
+use aoc_runner_derive::aoc;
+
+use anyhow::Result;
+
+use num::BigInt;
+use num::BigRational;
+use num::Signed;
+use num::Zero;
+
+use thiserror::Error;
+
+pub type Input = Vec<Hailstone>;
+pub type InputRef = [Hailstone];
+
+#[derive(Debug, Error)]
+pub enum Day24Error {
+    #[error("No integer rock trajectory hits every hailstone")]
+    NoRock,
+    #[error("The sum of the rock's coordinates overflows")]
+    Overflow,
+}
+
+#[derive(Debug, Clone, Copy)]
+#[derive(PartialEq, Eq)]
+#[derive(Hash)]
+pub struct Vec3 {
+    pub x: i64,
+    pub y: i64,
+    pub z: i64,
+}
+
+#[derive(Debug, Clone, Copy)]
+#[derive(PartialEq, Eq)]
+#[derive(Hash)]
+pub struct Hailstone {
+    pub position: Vec3,
+    pub velocity: Vec3,
+}
+
+#[aoc_generator(day24)]
+pub fn input_generator(input: &str) -> Result<Input> {
+    run_parser(input, parse_input)
+}
+
+use nom::bytes::complete::tag;
+use nom::character::complete::space0;
+use nom::sequence::tuple;
+
+use crate::parse::IResult;
+use crate::parse::lines;
+use crate::parse::run_parser;
+use crate::parse::signed;
+
+fn parse_input(input: &str) -> IResult<&str, Input> {
+    lines(parse_hailstone)(input)
+}
+
+fn parse_hailstone(input: &str) -> IResult<&str, Hailstone> {
+    let (input, position) = parse_vec3(input)?;
+    let (input, _) = tuple((space0, tag("@"), space0))(input)?;
+    let (input, velocity) = parse_vec3(input)?;
+
+    Ok((input, Hailstone {
+        position,
+        velocity,
+    }))
+}
+
+fn parse_vec3(input: &str) -> IResult<&str, Vec3> {
+    let (input, (x, _, y, _, z)) = tuple((
+        signed,
+        tuple((tag(","), space0)),
+        signed,
+        tuple((tag(","), space0)),
+        signed,
+    ))(input)?;
+
+    Ok((input, Vec3 {
+        x,
+        y,
+        z,
+    }))
+}
+
+fn rational(n: impl Into<BigInt>) -> BigRational {
+    BigRational::from_integer(n.into())
+}
+
+/// Where the paths of `a` and `b` cross in the xy plane, if both reach that
+/// point in the future.
+fn future_intersection_xy(a: &Hailstone, b: &Hailstone) -> Option<(BigRational, BigRational)> {
+    let (pa, va) = (&a.position, &a.velocity);
+    let (pb, vb) = (&b.position, &b.velocity);
+
+    let denominator = rational(va.x) * rational(vb.y) - rational(va.y) * rational(vb.x);
+    if denominator.is_zero() {
+        return None;
+    }
+
+    let dx = rational(pb.x) - rational(pa.x);
+    let dy = rational(pb.y) - rational(pa.y);
+
+    let t = (dx.clone() * rational(vb.y) - dy.clone() * rational(vb.x)) / denominator.clone();
+    let s = (dx * rational(va.y) - dy * rational(va.x)) / denominator;
+    if t.is_negative() || s.is_negative() {
+        return None;
+    }
+
+    let x = rational(pa.x) + t.clone() * rational(va.x);
+    let y = rational(pa.y) + t * rational(va.y);
+
+    Some((x, y))
+}
+
+fn count_intersections_in_area(input: &InputRef, min: i64, max: i64) -> usize {
+    let area = rational(min)..=rational(max);
+
+    input.iter().enumerate()
+        .flat_map(|(i, a)| input[i + 1..].iter().map(move |b| (a, b)))
+        .filter_map(|(a, b)| future_intersection_xy(a, b))
+        .filter(|(x, y)| area.contains(x) && area.contains(y))
+        .count()
+}
+
+#[aoc(day24, part1)]
+pub fn solve_part1(input: &InputRef) -> Result<usize, Day24Error> {
+    Ok(count_intersections_in_area(input, 200_000_000_000_000, 400_000_000_000_000))
+}
+
+/// Solves `matrix * x = rhs` by Gauss-Jordan elimination, returning `None` if
+/// the system has no unique solution.
+fn solve_linear(mut matrix: Vec<Vec<BigRational>>, mut rhs: Vec<BigRational>) -> Option<Vec<BigRational>> {
+    let n = rhs.len();
+
+    for column in 0..n {
+        let pivot = (column..n).find(|&row| !matrix[row][column].is_zero())?;
+        matrix.swap(column, pivot);
+        rhs.swap(column, pivot);
+
+        let pivot_row = matrix[column].clone();
+        for row in 0..n {
+            if row == column || matrix[row][column].is_zero() { continue; }
+
+            let factor = matrix[row][column].clone() / pivot_row[column].clone();
+            for (value, pivot_value) in matrix[row].iter_mut().zip(&pivot_row).skip(column) {
+                *value -= factor.clone() * pivot_value;
+            }
+            let delta = factor * rhs[column].clone();
+            rhs[row] -= delta;
+        }
+    }
+
+    Some((0..n).map(|i| rhs[i].clone() / matrix[i][i].clone()).collect())
+}
+
+/// `a - b`, which always fits in an i128, as does its negation, where it might
+/// not in an i64.
+fn difference(a: &Vec3, b: &Vec3) -> [i128; 3] {
+    [
+        a.x as i128 - b.x as i128,
+        a.y as i128 - b.y as i128,
+        a.z as i128 - b.z as i128,
+    ]
+}
+
+/// For the rock at `p` moving at `v` to hit hailstone `i`, `(p - p_i) x (v - v_i)`
+/// must be zero. Subtracting that equation for two hailstones cancels the
+/// `p x v` term, leaving three equations linear in `p` and `v`:
+///
+/// `p x (v_i - v_j) + (p_i - p_j) x v = p_i x v_i - p_j x v_j`
+fn rock_equations(i: &Hailstone, j: &Hailstone) -> Vec<(Vec<BigRational>, BigRational)> {
+    let a = difference(&i.velocity, &j.velocity);
+    let b = difference(&i.position, &j.position);
+
+    let cross = |p: &Vec3, v: &Vec3| [
+        rational(p.y) * rational(v.z) - rational(p.z) * rational(v.y),
+        rational(p.z) * rational(v.x) - rational(p.x) * rational(v.z),
+        rational(p.x) * rational(v.y) - rational(p.y) * rational(v.x),
+    ];
+    let [ci_x, ci_y, ci_z] = cross(&i.position, &i.velocity);
+    let [cj_x, cj_y, cj_z] = cross(&j.position, &j.velocity);
+
+    // Unknowns are ordered px, py, pz, vx, vy, vz.
+    let rows = [
+        [0, a[2], -a[1], 0, -b[2], b[1]],
+        [-a[2], 0, a[0], b[2], 0, -b[0]],
+        [a[1], -a[0], 0, -b[1], b[0], 0],
+    ];
+
+    rows.into_iter()
+        .map(|row| row.into_iter().map(rational).collect())
+        .zip([ci_x - cj_x, ci_y - cj_y, ci_z - cj_z])
+        .collect()
+}
+
+/// Whether `rock` hits `hailstone`, meaning `(p - p_i) x (v - v_i)` is zero.
+fn hits(rock: &Hailstone, hailstone: &Hailstone) -> bool {
+    let [px, py, pz] = difference(&rock.position, &hailstone.position).map(BigInt::from);
+    let [vx, vy, vz] = difference(&rock.velocity, &hailstone.velocity).map(BigInt::from);
+
+    &py * &vz == &pz * &vy && &pz * &vx == &px * &vz && &px * &vy == &py * &vx
+}
+
+fn find_rock(input: &InputRef) -> Option<Hailstone> {
+    let first = input.first()?;
+
+    // Some choices of hailstones give a degenerate system, or one that fits
+    // them but not the rest, so keep trying pairs until one pins down a rock
+    // that hits every hailstone.
+    for j in 1..input.len() {
+        for k in j + 1..input.len() {
+            let (matrix, rhs): (Vec<_>, Vec<_>) = rock_equations(first, &input[j]).into_iter()
+                .chain(rock_equations(first, &input[k]))
+                .unzip();
+
+            let Some(solution) = solve_linear(matrix, rhs) else { continue; };
+
+            let values: Option<Vec<i64>> = solution.iter()
+                .map(|value| if value.is_integer() { i64::try_from(value.to_integer()).ok() } else { None })
+                .collect();
+            let Some(values) = values else { continue; };
+
+            let rock = Hailstone {
+                position: Vec3 { x: values[0], y: values[1], z: values[2] },
+                velocity: Vec3 { x: values[3], y: values[4], z: values[5] },
+            };
+            if input.iter().all(|hailstone| hits(&rock, hailstone)) {
+                return Some(rock);
+            }
+        }
+    }
+
+    None
+}
+
+#[aoc(day24, part2)]
+pub fn solve_part2(input: &InputRef) -> Result<i64, Day24Error> {
+    let rock = find_rock(input).ok_or(Day24Error::NoRock)?;
+
+    rock.position.x.checked_add(rock.position.y)
+        .and_then(|sum| sum.checked_add(rock.position.z))
+        .ok_or(Day24Error::Overflow)
+}
+
+crate::registry::solutions! {
+    (24, 1, input_generator, solve_part1),
+    (24, 2, input_generator, solve_part2),
+}
+
+#[cfg(test)]
+mod test {
+    const TEST_INPUT: &'static str =
+r#"
+19, 13, 30 @ -2,  1, -2
+18, 19, 22 @ -1, -1, -2
+20, 25, 34 @ -2, -2, -4
+12, 31, 28 @ -1, -2, -1
+20, 19, 15 @  1, -5, -3
+"#;
+
+    #[test]
+    fn test_part1_example() {
+        let input = super::input_generator(TEST_INPUT).unwrap();
+        let result = super::count_intersections_in_area(&input, 7, 27);
+
+        assert_eq!(result, 2);
+    }
+
+    #[test]
+    fn test_part2_example() {
+        let input = super::input_generator(TEST_INPUT).unwrap();
+        let rock = super::find_rock(&input).unwrap();
+
+        assert_eq!((rock.position.x, rock.position.y, rock.position.z), (24, 13, 10));
+        assert_eq!((rock.velocity.x, rock.velocity.y, rock.velocity.z), (-3, 1, 2));
+
+        let result = super::solve_part2(&input).unwrap();
+
+        assert_eq!(result, 47);
+    }
+
+    #[test]
+    fn test_missed_hailstone() {
+        // The rock through the first three hailstones misses the last.
+        let input = super::input_generator(
+r#"
+19, 13, 30 @ -2,  1, -2
+18, 19, 22 @ -1, -1, -2
+20, 25, 34 @ -2, -2, -4
+0, 0, 0 @ 1, 1, 1
+"#).unwrap();
+
+        assert!(super::find_rock(&input).is_none());
+        assert!(matches!(super::solve_part2(&input), Err(super::Day24Error::NoRock)));
+    }
+
+    #[test]
+    fn test_extreme_coordinates() {
+        let input = super::input_generator(&format!("{max}, {max}, 0 @ -1, 0, 0\n{min}, {min}, 0 @ 0, 1, 0\n{max}, {min}, {max} @ {min}, {max}, {min}\n", max = i64::MAX, min = i64::MIN)).unwrap();
+
+        assert_eq!(super::count_intersections_in_area(&input[..2], i64::MIN, i64::MAX), 1);
+        assert!(super::solve_part2(&input).is_err());
+
+        // Both differences are 2^64 - 1 in every axis, so the products overflow an i128
+        let input = super::input_generator(&format!("{max}, {max}, {max} @ {max}, {max}, {max}\n{min}, {min}, {min} @ {min}, {min}, {min}\n", max = i64::MAX, min = i64::MIN + 1)).unwrap();
+        assert!(super::hits(&input[0], &input[1]));
+    }
+
+    #[test]
+    fn test_no_rock() {
+        let input = super::input_generator("19, 13, 30 @ -2, 1, -2\n").unwrap();
+
+        let error = super::solve_part2(&input).unwrap_err();
+        assert_eq!(error.to_string(), "No integer rock trajectory hits every hailstone");
+    }
+}
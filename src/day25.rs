@@ -0,0 +1,233 @@
+use std::collections::BinaryHeap;
+use std::collections::HashMap;
+use std::collections::HashSet;
+
+use aoc_runner_derive::aoc_generator;
+use aoc_runner_derive::aoc;
+
+use anyhow::Result;
+
+use thiserror::Error;
+
+use petgraph::graph::EdgeIndex;
+use petgraph::graph::NodeIndex;
+use petgraph::graph::UnGraph;
+use petgraph::visit::EdgeRef;
+
+pub type Input = UnGraph<String, ()>;
+
+#[derive(Debug, Error)]
+pub enum Day25Error {
+    #[error("There are only {0} nodes, so there's nothing to cut")]
+    TooFewNodes(usize),
+    #[error("The smallest cut needs {0} wires instead of 3")]
+    WrongCutSize(usize),
+}
+
+#[aoc_generator(day25)]
+pub fn input_generator(input: &str) -> Result<Input> {
+    let lines = run_parser(input, parse_input)?;
+
+    let mut graph = UnGraph::new_undirected();
+    let mut nodes = HashMap::new();
+
+    let mut node_for = |graph: &mut Input, name: &str| {
+        *nodes.entry(name.to_owned()).or_insert_with(|| graph.add_node(name.to_owned()))
+    };
+
+    for (name, connections) in lines {
+        let node = node_for(&mut graph, name);
+        for connection in connections {
+            let other = node_for(&mut graph, connection);
+            graph.update_edge(node, other, ());
+        }
+    }
+
+    Ok(graph)
+}
+
+use nom::bytes::complete::tag;
+use nom::character::complete::alpha1;
+
+use crate::parse::IResult;
+use crate::parse::lines;
+use crate::parse::run_parser;
+use crate::parse::space_separated;
+
+fn parse_input(input: &str) -> IResult<&str, Vec<(&str, Vec<&str>)>> {
+    lines(parse_line)(input)
+}
+
+fn parse_line(input: &str) -> IResult<&str, (&str, Vec<&str>)> {
+    let (input, name) = alpha1(input)?;
+    let (input, _) = tag(": ")(input)?;
+    let (input, connections) = space_separated(alpha1)(input)?;
+
+    Ok((input, (name, connections)))
+}
+
+/// The smallest set of edges whose removal splits the graph in two.
+#[derive(Debug, Clone)]
+pub struct MinCut {
+    /// The nodes on one side of the cut.
+    pub side: HashSet<NodeIndex>,
+    /// The edges crossing the cut.
+    pub edges: Vec<EdgeIndex>,
+}
+
+impl MinCut {
+    pub fn weight(&self) -> usize {
+        self.edges.len()
+    }
+
+    /// The names of the nodes at either end of each edge crossing the cut.
+    pub fn edge_names<'g>(&self, graph: &'g Input) -> Vec<(&'g str, &'g str)> {
+        self.edges.iter()
+            .map(|&edge| {
+                let (a, b) = graph.edge_endpoints(edge).unwrap();
+                (graph[a].as_str(), graph[b].as_str())
+            })
+            .collect()
+    }
+}
+
+/// Finds a global minimum cut with Stoer-Wagner. Each phase orders the
+/// remaining vertices by how tightly they connect to those already added; the
+/// last two are merged, and the last one on its own gives a candidate cut.
+pub fn minimum_cut(graph: &Input) -> Option<MinCut> {
+    let count = graph.node_count();
+    if count < 2 {
+        return None;
+    }
+
+    let mut adjacency: Vec<HashMap<usize, usize>> = vec![HashMap::new(); count];
+    for edge in graph.edge_references() {
+        let (a, b) = (edge.source().index(), edge.target().index());
+        if a == b { continue; }
+        *adjacency[a].entry(b).or_insert(0) += 1;
+        *adjacency[b].entry(a).or_insert(0) += 1;
+    }
+
+    let mut members: Vec<Vec<NodeIndex>> = graph.node_indices().map(|node| vec![node]).collect();
+    let mut active: Vec<usize> = (0..count).collect();
+
+    let mut best: Option<(usize, Vec<NodeIndex>)> = None;
+
+    while active.len() > 1 {
+        let mut weights = vec![0; count];
+        let mut added = vec![false; count];
+        let mut heap: BinaryHeap<_> = active.iter().map(|&v| (0, v)).collect();
+
+        let mut previous = None;
+        let mut last = None;
+
+        while let Some((weight, vertex)) = heap.pop() {
+            if added[vertex] || weight != weights[vertex] { continue; }
+
+            added[vertex] = true;
+            previous = last;
+            last = Some(vertex);
+
+            for (&neighbor, &edge_weight) in &adjacency[vertex] {
+                if !added[neighbor] {
+                    weights[neighbor] += edge_weight;
+                    heap.push((weights[neighbor], neighbor));
+                }
+            }
+        }
+
+        let (s, t) = (previous.unwrap(), last.unwrap());
+
+        if best.as_ref().is_none_or(|(weight, _)| weights[t] < *weight) {
+            best = Some((weights[t], members[t].clone()));
+        }
+
+        let t_edges = std::mem::take(&mut adjacency[t]);
+        for (neighbor, edge_weight) in t_edges {
+            adjacency[neighbor].remove(&t);
+            if neighbor != s {
+                *adjacency[s].entry(neighbor).or_insert(0) += edge_weight;
+                *adjacency[neighbor].entry(s).or_insert(0) += edge_weight;
+            }
+        }
+
+        let t_members = std::mem::take(&mut members[t]);
+        members[s].extend(t_members);
+        active.retain(|&v| v != t);
+    }
+
+    let (_, side) = best?;
+    let side: HashSet<_> = side.into_iter().collect();
+
+    let edges = graph.edge_references()
+        .filter(|edge| side.contains(&edge.source()) != side.contains(&edge.target()))
+        .map(|edge| edge.id())
+        .collect();
+
+    Some(MinCut { side, edges })
+}
+
+#[aoc(day25, part1)]
+pub fn solve_part1(input: &Input) -> Result<usize, Day25Error> {
+    let cut = minimum_cut(input).ok_or(Day25Error::TooFewNodes(input.node_count()))?;
+    if cut.weight() != 3 {
+        return Err(Day25Error::WrongCutSize(cut.weight()));
+    }
+
+    Ok(cut.side.len() * (input.node_count() - cut.side.len()))
+}
+
+crate::registry::solutions! {
+    (25, 1, input_generator, solve_part1),
+}
+
+#[cfg(test)]
+mod test {
+    const TEST_INPUT: &'static str =
+r#"
+jqt: rhn xhk nvd
+rsh: frs pzl lsr
+xhk: hfx
+cmg: qnr nvd lhk bvb
+rhn: xhk bvb hfx
+bvb: xhk hfx
+pzl: lsr hfx nvd
+qnr: nvd
+ntq: jqt hfx bvb xhk
+nvd: lhk
+lsr: lhk
+rzs: qnr cmg lsr rsh
+frs: qnr lhk lsr
+"#;
+
+    #[test]
+    fn test_part1_example() {
+        let input = super::input_generator(TEST_INPUT).unwrap();
+        let result = super::solve_part1(&input).unwrap();
+
+        assert_eq!(result, 54);
+    }
+
+    #[test]
+    fn test_cut_edges() {
+        let input = super::input_generator(TEST_INPUT).unwrap();
+        let cut = super::minimum_cut(&input).unwrap();
+
+        assert_eq!(cut.weight(), 3);
+
+        let mut edges: Vec<_> = cut.edge_names(&input).into_iter()
+            .map(|(a, b)| if a < b { (a, b) } else { (b, a) })
+            .collect();
+        edges.sort();
+
+        assert_eq!(edges, vec![("bvb", "cmg"), ("hfx", "pzl"), ("jqt", "nvd")]);
+    }
+
+    #[test]
+    fn test_wrong_cut_size() {
+        let input = super::input_generator("aaa: bbb ccc\nbbb: ccc\n").unwrap();
+
+        let error = super::solve_part1(&input).unwrap_err();
+        assert_eq!(error.to_string(), "The smallest cut needs 2 wires instead of 3");
+    }
+}
@@ -1,39 +1,62 @@
-use std::collections::HashMap;
-
 use aoc_runner_derive::aoc_generator;
 use aoc_runner_derive::aoc;
 
 use anyhow::Result;
 
+use thiserror::Error;
+
+use crate::grid;
+use crate::grid::Grid;
+
+/// A schematic's numbers and symbols, each in the order they're read, with
+/// an index of which is where so that neighbours can be found without
+/// scanning through all of them.
 #[derive(Debug, Clone)]
-struct Data {
-    symbols: Vec<Symbol>,
-    numbers: Vec<Number>,
+pub struct Data {
+    pub symbols: Vec<Symbol>,
+    pub numbers: Vec<Number>,
+    pub cells: Grid<Option<Cell>>,
 }
 
-#[derive(Debug, Clone)]
-struct Symbol {
-    position: Pos,
-    #[allow(unused)]
-    value: char,
+/// What's on a cell of the schematic, as an index into `Data::numbers` or
+/// `Data::symbols`.
+#[derive(Debug, Clone, Copy)]
+#[derive(PartialEq, Eq)]
+pub enum Cell {
+    Number(usize),
+    Symbol(usize),
 }
 
 #[derive(Debug, Clone)]
-struct Number {
-    position: Pos,
-    length: i32,
-    value: usize,
+pub struct Symbol {
+    pub position: Pos,
+    pub value: char,
 }
 
 #[derive(Debug, Clone)]
-struct Pos {
-    x: i32,
-    y: i32,
+pub struct Number {
+    pub position: Pos,
+    pub length: i32,
+    pub value: usize,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Pos {
+    pub x: i32,
+    pub y: i32,
+}
+
+#[derive(Debug, Error)]
+pub enum Day3Error {
+    #[error("Number {text:?} at line {line}, column {column} doesn't fit in a usize")]
+    NumberTooLarge { line: usize, column: usize, text: String },
+    #[error("Symbol {symbol:?} at line {line}, column {column} touches more than two numbers")]
+    TooManyNumbers { line: usize, column: usize, symbol: char },
 }
 
 
 #[aoc_generator(day3)]
-fn input_generator(input: &str) -> Result<Data> {
+pub fn input_generator(input: &str) -> Result<Data> {
     let mut symbols = Vec::new();
     let mut numbers = Vec::new();
 
@@ -51,7 +74,12 @@ fn input_generator(input: &str) -> Result<Data> {
             };
 
             if is_digit(c) {
-                let (input, length, value) = parse_usize(remaining_line);
+                let (input, length, value) = parse_usize(remaining_line)
+                    .ok_or_else(|| Day3Error::NumberTooLarge {
+                        line: y + 1,
+                        column: position.x as usize + 1,
+                        text: line[position.x as usize..].chars().take_while(|c| c.is_ascii_digit()).collect(),
+                    })?;
                 let new_number = Number {
                     position,
                     length,
@@ -75,18 +103,77 @@ fn input_generator(input: &str) -> Result<Data> {
     }
 
 
+    let rows = input.lines().count();
+    let cols = input.lines().map(str::len).max().unwrap_or(0);
+    let mut cells = Grid::from_elem((rows, cols), None);
+
+    for (i, number) in numbers.iter().enumerate() {
+        for x in number.position.x..number.position.x + number.length {
+            cells[grid::Pos::new(number.position.y as usize, x as usize)] = Some(Cell::Number(i));
+        }
+    }
+    for (i, symbol) in symbols.iter().enumerate() {
+        cells[grid::Pos::new(symbol.position.y as usize, symbol.position.x as usize)] = Some(Cell::Symbol(i));
+    }
+
     Ok(Data {
         symbols,
         numbers,
+        cells,
     })
 }
 
+impl Data {
+    /// Whatever is on the cells from (`x_start`, `y_start`) to (`x_end`,
+    /// `y_end`) inclusive, read row by row, skipping any off the schematic.
+    fn cells_in(&self, (x_start, y_start): (i32, i32), (x_end, y_end): (i32, i32)) -> impl Iterator<Item = Cell> + '_ {
+        (y_start.max(0)..=y_end)
+            .flat_map(move |y| (x_start.max(0)..=x_end).map(move |x| grid::Pos::new(y as usize, x as usize)))
+            .filter_map(|pos| self.cells.get(pos).copied().flatten())
+    }
+
+    /// The symbols touching the `number`th number, in the order they're read.
+    pub fn number_neighbors(&self, number: usize) -> Vec<usize> {
+        let Pos { x, y } = self.numbers[number].position;
+        let length = self.numbers[number].length;
+
+        self.cells_in((x - 1, y - 1), (x + length, y + 1))
+            .filter_map(|cell| match cell {
+                Cell::Symbol(symbol) => Some(symbol),
+                Cell::Number(_) => None,
+            })
+            .collect()
+    }
+
+    /// The numbers touching the `symbol`th symbol, in the order they're read.
+    pub fn symbol_neighbors(&self, symbol: usize) -> Vec<usize> {
+        let Pos { x, y } = self.symbols[symbol].position;
+
+        let mut numbers: Vec<_> = self.cells_in((x - 1, y - 1), (x + 1, y + 1))
+            .filter_map(|cell| match cell {
+                Cell::Number(number) => Some(number),
+                Cell::Symbol(_) => None,
+            })
+            .collect();
+        numbers.dedup();
+        numbers
+    }
+
+    /// Every `symbol` touching exactly `parts` numbers, with those numbers.
+    pub fn gears(&self, symbol: char, parts: usize) -> impl Iterator<Item = (usize, Vec<usize>)> + '_ {
+        self.symbols.iter().enumerate()
+            .filter(move |(_, candidate)| candidate.value == symbol)
+            .map(|(i, _)| (i, self.symbol_neighbors(i)))
+            .filter(move |(_, numbers)| numbers.len() == parts)
+    }
+}
+
 fn is_digit(c: u8) -> bool {
     let c = c as char;
     c.is_digit(10)
 }
 
-fn parse_usize(input: &[u8]) -> (&[u8], i32, usize) {
+fn parse_usize(input: &[u8]) -> Option<(&[u8], i32, usize)> {
     let mut i = 1;
 
     while i < input.len() {
@@ -95,36 +182,23 @@ fn parse_usize(input: &[u8]) -> (&[u8], i32, usize) {
     }
 
     let num_str = &input[0..i];
-    let num_str = std::str::from_utf8(num_str).unwrap();
+    let num_str = std::str::from_utf8(num_str).ok()?;
     let length = i as i32;
 
-    (&input[i..], length, num_str.parse().unwrap())
+    Some((&input[i..], length, num_str.parse().ok()?))
 }
 
 #[aoc(day3, part1)]
-fn solve_part1(input: &Data) -> usize {
+pub fn solve_part1(input: &Data) -> Result<usize, Day3Error> {
     let mut total = 0;
 
-    for number in &input.numbers {
-        if is_any_symbol_adjacent(number, &input.symbols) {
+    for (i, number) in input.numbers.iter().enumerate() {
+        if !input.number_neighbors(i).is_empty() {
             total += number.value;
         }
     }
 
-    total
-}
-
-fn is_any_symbol_adjacent(number: &Number, symbols: &[Symbol]) -> bool {
-    for symbol in symbols {
-        if is_this_symbol_adjacent(number, symbol) { return true; }
-    }
-
-    false
-}
-
-fn is_this_symbol_adjacent(number: &Number, symbol: &Symbol) -> bool {
-    (number.position.x - 1 ..= number.position.x + number.length).contains(&symbol.position.x)
-        && number.position.y.abs_diff(symbol.position.y) <= 1
+    Ok(total)
 }
 
 #[derive(Debug, Clone, Copy)]
@@ -134,45 +208,47 @@ enum SymbolSlot {
     TwoFound(usize),
 }
 
+/// Pairs each number with the first symbol it touches, whatever that symbol
+/// is, and adds up the products of the pairs.
 #[aoc(day3, part2)]
-fn solve_part2(input: &Data) -> usize {
-    let mut symbol_map = HashMap::new();
-
+pub fn solve_part2(input: &Data) -> Result<usize, Day3Error> {
     use SymbolSlot::*;
 
-    for (i, _symbol) in input.symbols.iter().enumerate() {
-        symbol_map.insert(i, NoneFound);
-    }
+    let mut symbol_slots = vec![NoneFound; input.symbols.len()];
 
-    for number in &input.numbers {
-        let symbol_index = find_adjacent_symbol(number, &input.symbols);
+    for (i, number) in input.numbers.iter().enumerate() {
+        let symbol_index = input.number_neighbors(i).first().copied();
 
         if let Some(symbol_index) = symbol_index {
-            let entry = symbol_map.get_mut(&symbol_index).unwrap();
+            let entry = &mut symbol_slots[symbol_index];
             match *entry {
                 NoneFound => *entry = OneFound(number.value),
                 OneFound(value) => *entry = TwoFound(value * number.value),
-                TwoFound(_) => unreachable!(),
+                TwoFound(_) => {
+                    let symbol = &input.symbols[symbol_index];
+                    return Err(Day3Error::TooManyNumbers {
+                        line: symbol.position.y as usize + 1,
+                        column: symbol.position.x as usize + 1,
+                        symbol: symbol.value,
+                    });
+                },
             }
         }
     }
 
-    symbol_map.iter()
-        .map(|(_, v)| {
+    Ok(symbol_slots.iter()
+        .map(|v| {
             match v {
                 &TwoFound(value) => value,
                 _ => 0,
             }
         })
-        .sum()
+        .sum())
 }
 
-fn find_adjacent_symbol(number: &Number, symbols: &[Symbol]) -> Option<usize> {
-    for (i, symbol) in symbols.iter().enumerate() {
-        if is_this_symbol_adjacent(number, symbol) { return Some(i); }
-    }
-
-    None
+crate::registry::solutions! {
+    (3, 1, input_generator, solve_part1),
+    (3, 2, input_generator, solve_part2),
 }
 
 #[cfg(test)]
@@ -193,7 +269,7 @@ r#"
     #[test]
     fn test_part1_example() {
         let input = super::input_generator(TEST_INPUT).unwrap();
-        let result = super::solve_part1(&input);
+        let result = super::solve_part1(&input).unwrap();
 
         assert_eq!(result, 4361);
     }
@@ -201,8 +277,44 @@ r#"
     #[test]
     fn test_part2_example() {
         let input = super::input_generator(TEST_INPUT).unwrap();
-        let result = super::solve_part2(&input);
+        let result = super::solve_part2(&input).unwrap();
 
         assert_eq!(result, 467835);
     }
+
+    #[test]
+    fn test_queries() {
+        let input = super::input_generator(TEST_INPUT).unwrap();
+        let number = |value| input.numbers.iter().position(|number| number.value == value).unwrap();
+        let symbol = |x, y| input.symbols.iter().position(|symbol| symbol.position.x == x && symbol.position.y == y).unwrap();
+
+        assert_eq!(input.number_neighbors(number(467)), [symbol(3, 2)]);
+        assert!(input.number_neighbors(number(114)).is_empty());
+        assert_eq!(input.symbol_neighbors(symbol(3, 2)), [number(467), number(35)]);
+        assert_eq!(input.symbol_neighbors(symbol(3, 5)), [number(617)]);
+
+        let gears: Vec<_> = input.gears('*', 2).map(|(i, _)| i).collect();
+        assert_eq!(gears, [symbol(3, 2), symbol(5, 9)]);
+        let lone_stars: Vec<_> = input.gears('*', 1).map(|(_, numbers)| numbers).collect();
+        assert_eq!(lone_stars, [[number(617)]]);
+        assert_eq!(input.gears('#', 1).count(), 1);
+        assert_eq!(input.gears('$', 0).count(), 0);
+    }
+
+    #[test]
+    fn test_ragged_lines() {
+        let input = super::input_generator("12\n.*\n3.....4\n").unwrap();
+        assert_eq!(super::solve_part1(&input).unwrap(), 15);
+        assert_eq!(super::solve_part2(&input).unwrap(), 36);
+    }
+
+    #[test]
+    fn test_invalid_schematic() {
+        let input = super::input_generator("1.2\n.*.\n3..\n").unwrap();
+        let error = super::solve_part2(&input).unwrap_err();
+        assert_eq!(error.to_string(), "Symbol '*' at line 2, column 2 touches more than two numbers");
+
+        let error = super::input_generator("..99999999999999999999999\n").unwrap_err();
+        assert!(error.to_string().contains("line 1, column 3"));
+    }
 }
@@ -1,56 +1,59 @@
 use std::collections::HashSet;
 use std::collections::VecDeque;
+use std::io::BufRead;
 
 use aoc_runner_derive::aoc_generator;
 use aoc_runner_derive::aoc;
 
 use anyhow::Result;
-use anyhow::anyhow;
 
-type Data = Card;
+use thiserror::Error;
+
+pub type Data = Card;
+
+#[derive(Debug, Error)]
+pub enum Day4Error {
+    #[error("Card {card} wins copies of cards past the end of the table")]
+    CopiesPastEnd { card: usize },
+}
 
 #[derive(Debug, Clone)]
-struct Card {
-    winners: Vec<usize>,
-    haves: Vec<usize>,
+pub struct Card {
+    pub winners: Vec<usize>,
+    pub haves: Vec<usize>,
 }
 
 
 #[aoc_generator(day4)]
-fn input_generator(input: &str) -> Result<Vec<Data>> {
-    let (input, result) = parse_input(input).map_err(|err| err.to_owned())?;
-    if !input.is_empty() {
-        return Err(anyhow!("Had unparsed input after parsing: {}", input));
-    }
-    Ok(result)
+pub fn input_generator(input: &str) -> Result<Vec<Data>> {
+    run_parser(input, parse_input)
 }
 
-use nom::IResult;
-use nom::bytes::complete::take_while1;
-use nom::combinator::opt;
-use nom::multi::separated_list1;
-use nom::multi::many1;
+use nom::character::complete::space1;
 use nom::bytes::complete::tag;
 
-fn parse_input(input: &str) -> IResult<&str, Vec<Data>> {
-    let (input, _) = opt(tag("\n"))(input)?;
-    let (input, result) = separated_list1(tag("\n"), parse_card)(input)?;
-    let (input, _) = opt(tag("\n"))(input)?;
+use crate::parse::IResult;
+use crate::parse::lines;
+use crate::parse::run_parser;
+use crate::parse::space_separated;
+use crate::parse::stream_lines;
+use crate::parse::unsigned;
 
-    Ok((input, result))
+fn parse_input(input: &str) -> IResult<&str, Vec<Data>> {
+    lines(parse_card)(input)
 }
 
 fn parse_card(input: &str) -> IResult<&str, Card> {
     let (input, _) = tag("Card")(input)?;
-    let (input, _) = many1(tag(" "))(input)?;
-    let (input, _id) = parse_u32(input)?;
+    let (input, _) = space1(input)?;
+    let (input, _id) = unsigned::<u32>(input)?;
     let (input, _) = tag(":")(input)?;
-    let (input, _) = many1(tag(" "))(input)?;
+    let (input, _) = space1(input)?;
 
-    let (input, winners) = separated_list1(many1(tag(" ")), parse_number)(input)?;
+    let (input, winners) = space_separated(unsigned)(input)?;
     let (input, _) = tag(" |")(input)?;
-    let (input, _) = many1(tag(" "))(input)?;
-    let (input, haves) = separated_list1(many1(tag(" ")), parse_number)(input)?;
+    let (input, _) = space1(input)?;
+    let (input, haves) = space_separated(unsigned)(input)?;
 
     Ok((input, Card {
         winners,
@@ -58,25 +61,8 @@ fn parse_card(input: &str) -> IResult<&str, Card> {
     }))
 }
 
-fn parse_number(input: &str) -> IResult<&str, usize> {
-    let (input, number_string) = take_while1(is_number)(input)?;
-    let number = number_string.parse().unwrap();
-
-    Ok((input, number))
-}
-
-fn parse_u32(input: &str) -> IResult<&str, u32> {
-    let (input, number_string) = take_while1(is_number)(input)?;
-    let number = number_string.parse().unwrap();
-
-    Ok((input, number))
-}
-fn is_number(c: char) -> bool {
-    ('0'..='9').contains(&c)
-}
-
 #[aoc(day4, part1)]
-fn solve_part1(input: &[Data]) -> usize {
+pub fn solve_part1(input: &[Data]) -> Result<usize, Day4Error> {
     let mut total = 0;
 
     for card in input {
@@ -98,7 +84,20 @@ fn solve_part1(input: &[Data]) -> usize {
         }
     }
 
-    total
+    Ok(total)
+}
+
+fn stream_part1(input: impl BufRead) -> Result<u64> {
+    let mut total = 0;
+    for card in stream_lines(input).parse(parse_card) {
+        let (_, card) = card?;
+        let matches = count_matches(&card);
+        if matches > 0 {
+            total += 1 << (matches - 1);
+        }
+    }
+
+    Ok(total)
 }
 
 fn count_matches(card: &Card) -> usize {
@@ -108,17 +107,20 @@ fn count_matches(card: &Card) -> usize {
 }
 
 #[aoc(day4, part2)]
-fn solve_part2(input: &[Data]) -> usize {
+pub fn solve_part2(input: &[Data]) -> Result<usize, Day4Error> {
     let mut total = 0;
 
     let mut extra_copies = VecDeque::new();
 
-    for card in input {
+    for (card_index, card) in input.iter().enumerate() {
         let copies_of_this_card = extra_copies.pop_front().unwrap_or(0) + 1;
 
         total += copies_of_this_card;
 
         let matches = count_matches(card);
+        if card_index + matches >= input.len() {
+            return Err(Day4Error::CopiesPastEnd { card: card_index + 1 });
+        }
 
         if extra_copies.len() < matches {
             extra_copies.resize(matches, 0);
@@ -129,7 +131,58 @@ fn solve_part2(input: &[Data]) -> usize {
         }
     }
 
-    total
+    Ok(total)
+}
+
+/// Like `solve_part2`, but without knowing how many cards there are until
+/// the end, so the cards that might win copies past it are kept until then.
+fn stream_part2(input: impl BufRead) -> Result<usize> {
+    let mut total = 0;
+    let mut card_count = 0;
+
+    let mut extra_copies = VecDeque::new();
+    // The index of each card that wins copies of cards not yet read, and how far it reaches.
+    let mut reaching: Vec<(usize, usize)> = Vec::new();
+
+    for card in stream_lines(input).parse(parse_card) {
+        let (_, card) = card?;
+        let card_index = card_count;
+        card_count += 1;
+
+        let copies_of_this_card = extra_copies.pop_front().unwrap_or(0) + 1;
+
+        total += copies_of_this_card;
+
+        let matches = count_matches(&card);
+        reaching.retain(|&(_, reach)| reach > card_index);
+        if matches > 0 {
+            reaching.push((card_index, card_index + matches));
+        }
+
+        if extra_copies.len() < matches {
+            extra_copies.resize(matches, 0);
+        }
+
+        for extra in extra_copies.iter_mut().take(matches) {
+            *extra += copies_of_this_card;
+        }
+    }
+
+    if let Some(&(card_index, _)) = reaching.iter().find(|&&(_, reach)| reach >= card_count) {
+        return Err(Day4Error::CopiesPastEnd { card: card_index + 1 }.into());
+    }
+
+    Ok(total)
+}
+
+crate::registry::solutions! {
+    (4, 1, input_generator, solve_part1),
+    (4, 2, input_generator, solve_part2),
+}
+
+crate::registry::streaming_solutions! {
+    (4, 1, stream_part1),
+    (4, 2, stream_part2),
 }
 
 #[cfg(test)]
@@ -147,7 +200,7 @@ Card 6: 31 18 13 56 72 | 74 77 10 23 35 67 36 11
     fn test_part1_example() {
         let input = super::input_generator(TEST_INPUT).unwrap();
         dbg!{&input};
-        let result = super::solve_part1(&input);
+        let result = super::solve_part1(&input).unwrap();
 
         assert_eq!(result, 13);
     }
@@ -155,8 +208,25 @@ Card 6: 31 18 13 56 72 | 74 77 10 23 35 67 36 11
     #[test]
     fn test_part2_example() {
         let input = super::input_generator(TEST_INPUT).unwrap();
-        let result = super::solve_part2(&input);
+        let result = super::solve_part2(&input).unwrap();
 
         assert_eq!(result, 30);
     }
+
+    #[test]
+    fn test_copies_past_end() {
+        let input = super::input_generator("Card 1: 1 2 | 1 2\nCard 2: 3 | 4\n").unwrap();
+        let error = super::solve_part2(&input).unwrap_err();
+
+        assert_eq!(error.to_string(), "Card 1 wins copies of cards past the end of the table");
+    }
+
+    #[test]
+    fn test_streaming_copies_past_end() {
+        let error = super::stream_part2("Card 1: 1 | 1\nCard 2: 1 2 | 1 2\nCard 3: 3 | 4\n".as_bytes()).unwrap_err();
+        assert_eq!(error.to_string(), "Card 2 wins copies of cards past the end of the table");
+
+        let total = super::stream_part2("Card 1: 1 | 1\nCard 2: 1 2 | 1 2\nCard 3: 3 | 4\nCard 4: 5 | 6\n".as_bytes()).unwrap();
+        assert_eq!(total, 1 + 2 + 3 + 3);
+    }
 }
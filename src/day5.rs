@@ -2,27 +2,43 @@ use aoc_runner_derive::aoc_generator;
 use aoc_runner_derive::aoc;
 
 use anyhow::Result;
-use anyhow::anyhow;
 
 use std::collections::HashMap;
+use std::ops::Range;
+
+use thiserror::Error;
+
+use crate::ranges::RangeSet;
+
+#[derive(Debug, Error)]
+pub enum Day5Error {
+    #[error("There are no seeds")]
+    NoSeeds,
+    #[error("Seed ranges need a start and a length, but there are {0} seed numbers")]
+    OddSeedCount(usize),
+    #[error("There is no map from {0:?}")]
+    MissingMap(String),
+    #[error("The maps loop back to {0:?} without reaching a location")]
+    MapCycle(String),
+}
 
 #[derive(Clone)]
 #[derive(Debug)]
-struct Data {
-    seeds: Vec<Item>,
-    starting_type: ItemType,
-    maps: HashMap<ItemType, ItemMap>,
+pub struct Data {
+    pub seeds: Vec<Item>,
+    pub starting_type: ItemType,
+    pub maps: HashMap<ItemType, ItemMap>,
 }
 
 #[derive(Clone, Copy)]
 #[derive(Debug)]
-struct Item(usize);
+pub struct Item(pub usize);
 
 #[derive(Clone)]
 #[derive(Debug)]
-struct ItemMap {
-    result_type: ItemType,
-    ranges: Vec<MapRange>,
+pub struct ItemMap {
+    pub result_type: ItemType,
+    pub ranges: Vec<MapRange>,
 }
 
 impl ItemMap {
@@ -38,37 +54,26 @@ impl ItemMap {
         *item
     }
 
-    fn map_ranges(&self, ranges: Vec<RangeType>) -> Vec<RangeType> {
-        let mut remaining_ranges = ranges;
-
-        let starting_length: usize = remaining_ranges.iter().map(|r| r.length).sum();
-
-        let mut output_ranges = Vec::new();
+    fn map_ranges(&self, ranges: &RangeSet<usize>) -> RangeSet<usize> {
+        let mut remaining_ranges = ranges.clone();
+        let mut output_ranges = RangeSet::new();
 
         for map_range in &self.ranges {
-            remaining_ranges = remaining_ranges.into_iter().flat_map(|range| {
-                let (rem, out) = map_range.try_map_range(range);
-                output_ranges.extend(out);
-                rem
-            }).collect();
-
-            let remaining_length: usize = remaining_ranges.iter().map(|r| r.length).sum();
-            let output_length: usize = output_ranges.iter().map(|r| r.length).sum();
-            assert_eq!(starting_length, remaining_length + output_length);
+            let (remaining, output) = map_range.try_map_range(&remaining_ranges);
+            remaining_ranges = remaining;
+            output_ranges = output_ranges.union(&output);
         }
 
-        output_ranges.extend(remaining_ranges.into_iter());
-
-        output_ranges
+        output_ranges.union(&remaining_ranges)
     }
 }
 
 #[derive(Clone)]
 #[derive(Debug)]
-struct MapRange {
-    source_start: usize,
-    dest_start: usize,
-    length: usize,
+pub struct MapRange {
+    pub source_start: usize,
+    pub dest_start: usize,
+    pub length: usize,
 }
 
 impl MapRange {
@@ -80,108 +85,20 @@ impl MapRange {
         }
     }
 
-    fn source_range(&self) -> RangeType {
-        RangeType {
-            start: self.source_start,
-            length: self.length,
-        }
+    fn source_range(&self) -> Range<usize> {
+        self.source_start..self.source_start + self.length
     }
 
-    fn try_map_range(&self, range: RangeType) -> (impl Iterator<Item = RangeType>, impl Iterator<Item = RangeType>) {
-        let contains_start = self.source_range().to_range().contains(&range.start);
-        let contains_end = self.source_range().to_range().contains(&(range.end() - 1));
-
-        match (contains_start, contains_end) {
-            (true, true) => {
-                // This range fully contains the source values, map the whole range
-                (vec![].into_iter(), vec![RangeType {
-                    start: range.start - self.source_start + self.dest_start,
-                    length: range.length,
-                }].into_iter())
-            },
-            (true, false) => {
-                // This range overlaps at the start
-                let first_untransformed = self.source_start + self.length;
-                // exclusive
-                let last_untransformed = range.end();
-                (
-                    vec![RangeType {
-                            start: first_untransformed,
-                            length: last_untransformed - first_untransformed,
-                    }].into_iter(),
-                    vec![RangeType {
-                            start: range.start - self.source_start + self.dest_start,
-                            length: first_untransformed - range.start,
-                    }].into_iter()
-                )
-            },
-            (false, true) => {
-                // This range overlaps at the end
-                let first_transformed = self.source_start;
-                // exclusive
-                let last_transformed = range.start + range.length;
-                (
-                    vec![RangeType {
-                            start: range.start,
-                            length: first_transformed - range.start,
-                    }].into_iter(),
-                    vec![RangeType {
-                            start: first_transformed - self.source_start + self.dest_start,
-                            length: last_transformed - first_transformed,
-                    }].into_iter(),
-                )
-            },
-            (false, false) => {
-                // either we're inside the start range or we're not
-                if self.source_start > range.start && self.source_range().end() < range.end() {
-                    // Contained by the input slice :<, two untranslated halves
-                    let first_transformed = self.source_start;
-                    // exclusive
-                    let last_transformed = self.source_range().end();
-
-                    (
-                        vec![
-                            RangeType {
-                                start: range.start,
-                                length: first_transformed - range.start,
-                            },
-                            RangeType {
-                                start: last_transformed,
-                                length: range.end() - last_transformed,
-                            }
-                        ].into_iter(),
-                        vec![RangeType {
-                                start: first_transformed - self.source_start + self.dest_start,
-                                length: last_transformed - first_transformed,
-                        }].into_iter()
-                    )
-                } else {
-                    // No overlap
-                    (
-                        vec![range].into_iter(),
-                        vec![].into_iter()
-                    )
-                }
-
-            },
-        }
-    }
-}
+    /// Splits `ranges` into the values this map leaves alone and the mapped
+    /// values of those it covers.
+    fn try_map_range(&self, ranges: &RangeSet<usize>) -> (RangeSet<usize>, RangeSet<usize>) {
+        let source = RangeSet::from(self.source_range());
 
-#[derive(Clone, Copy)]
-#[derive(Debug)]
-#[derive(PartialEq, Eq)]
-struct RangeType {
-    start: usize,
-    length: usize,
-}
+        let output = ranges.intersection(&source).ranges().iter()
+            .map(|range| range.start - self.source_start + self.dest_start..range.end - self.source_start + self.dest_start)
+            .collect();
 
-impl RangeType {
-    fn to_range(&self) -> std::ops::Range<usize> {
-        self.start..self.end()
-    }
-    fn end(&self) -> usize {
-        self.start + self.length
+        (ranges.difference(&source), output)
     }
 }
 
@@ -189,31 +106,29 @@ impl RangeType {
 #[derive(Debug)]
 #[derive(PartialEq, Eq)]
 #[derive(Hash)]
-struct ItemType(String);
+pub struct ItemType(pub String);
 
 
 #[aoc_generator(day5)]
-fn input_generator(input: &str) -> Result<Data> {
-    let (input, result) = parse_almanac(input).map_err(|err| err.to_owned())?;
-    if !input.is_empty() {
-        return Err(anyhow!("Had unparsed input after parsing: {}", input));
-    }
-    Ok(result)
+pub fn input_generator(input: &str) -> Result<Data> {
+    run_parser(input, parse_almanac)
 }
 
-use nom::IResult;
 use nom::bytes::complete::take_while1;
-use nom::combinator::opt;
 use nom::combinator::map;
-use nom::multi::separated_list1;
 use nom::bytes::complete::tag;
 
+use crate::parse::IResult;
+use crate::parse::blocks;
+use crate::parse::lines;
+use crate::parse::run_parser;
+use crate::parse::space_separated;
+use crate::parse::unsigned;
+
 fn parse_almanac(input: &str) -> IResult<&str, Data> {
-    let (input, _) = opt(tag("\n"))(input)?;
     let (input, seeds) = parse_seeds(input)?;
     let (input, _) = tag("\n\n")(input)?;
-    let (input, vec_of_maps) = separated_list1(tag("\n\n"), parse_map)(input)?;
-    let (input, _) = opt(tag("\n"))(input)?;
+    let (input, vec_of_maps) = blocks(parse_map)(input)?;
 
     let maps: HashMap<_, _> = vec_of_maps.into_iter().collect();
 
@@ -226,7 +141,7 @@ fn parse_almanac(input: &str) -> IResult<&str, Data> {
 
 fn parse_seeds(input: &str) -> IResult<&str, Vec<Item>> {
     let (input, _) = tag("seeds: ")(input)?;
-    let (input, seeds) = separated_list1(tag(" "), parse_item)(input)?;
+    let (input, seeds) = space_separated(parse_item)(input)?;
 
     Ok((input, seeds))
 }
@@ -237,7 +152,7 @@ fn parse_map(input: &str) -> IResult<&str, (ItemType, ItemMap)> {
     let (input, dest) = parse_item_type(input)?;
     let (input, _) = tag(" map:\n")(input)?;
 
-    let (input, ranges) = separated_list1(tag("\n"), parse_range)(input)?;
+    let (input, ranges) = lines(parse_range)(input)?;
 
     Ok((input, (source, ItemMap {
         result_type: dest,
@@ -246,11 +161,11 @@ fn parse_map(input: &str) -> IResult<&str, (ItemType, ItemMap)> {
 }
 
 fn parse_range(input: &str) -> IResult<&str, MapRange> {
-    let (input, dest_start) = parse_usize(input)?;
+    let (input, dest_start) = unsigned(input)?;
     let (input, _) = tag(" ")(input)?;
-    let (input, source_start) = parse_usize(input)?;
+    let (input, source_start) = unsigned(input)?;
     let (input, _) = tag(" ")(input)?;
-    let (input, length) = parse_usize(input)?;
+    let (input, length) = unsigned(input)?;
 
     Ok((input, MapRange {
         source_start,
@@ -264,131 +179,135 @@ fn parse_item_type(input: &str) -> IResult<&str, ItemType> {
 }
 
 fn parse_item(input: &str) -> IResult<&str, Item> {
-    map(parse_usize, |n| Item(n))(input)
-}
-
-fn parse_usize(input: &str) -> IResult<&str, usize> {
-    let (input, number_string) = take_while1(is_number)(input)?;
-    let number = number_string.parse().unwrap();
-
-    Ok((input, number))
-}
-fn is_number(c: char) -> bool {
-    ('0'..='9').contains(&c)
+    map(unsigned, Item)(input)
 }
 
 #[aoc(day5, part1)]
-fn solve_part1(input: &Data) -> usize {
+pub fn solve_part1(input: &Data) -> Result<usize, Day5Error> {
     let start_type = &input.starting_type;
     let end_type = &ItemType("location".to_owned());
 
+    let chain = map_chain(input, start_type, end_type)?;
+
     let minimum_location = input.seeds.iter().map(|seed| {
-        find_mapped_value(input, start_type, end_type, *seed).0
-    }).min().unwrap();
+        find_mapped_value(&chain, *seed).0
+    }).min().ok_or(Day5Error::NoSeeds)?;
 
-    minimum_location
+    Ok(minimum_location)
 }
 
-fn find_mapped_value(data: &Data, start_type: &ItemType, end_type: &ItemType, value: Item) -> Item {
+/// The maps to apply, in order, to get from `start_type` to `end_type`.
+fn map_chain<'d>(data: &'d Data, start_type: &ItemType, end_type: &ItemType) -> Result<Vec<&'d ItemMap>, Day5Error> {
     let mut current_type = start_type;
-    let mut current_value = value;
+    let mut chain = Vec::new();
 
     while current_type != end_type {
-        let map = &data.maps[current_type];
-        let next_value = map.map_value(&current_value);
-        let next_type = &map.result_type;
+        if chain.len() > data.maps.len() {
+            return Err(Day5Error::MapCycle(current_type.0.clone()));
+        }
 
-        current_type = next_type;
-        current_value = next_value;
+        let map = data.maps.get(current_type).ok_or_else(|| Day5Error::MissingMap(current_type.0.clone()))?;
+        chain.push(map);
+        current_type = &map.result_type;
     }
 
-    current_value
+    Ok(chain)
+}
+
+fn find_mapped_value(chain: &[&ItemMap], value: Item) -> Item {
+    chain.iter().fold(value, |current_value, map| map.map_value(&current_value))
 }
 
 #[aoc(day5, part2)]
-fn solve_part2(input: &Data) -> usize {
-    let ranges: Vec<_> = input.seeds
-        .chunks(2)
-        .map(|arr| RangeType { start: arr[0].0, length: arr[1].0 })
-        .collect();
+pub fn solve_part2(input: &Data) -> Result<usize, Day5Error> {
+    let ranges = seed_ranges(input)?.into_iter().collect();
 
     let start_type = &input.starting_type;
     let end_type = &ItemType("location".to_owned());
 
-    let final_ranges = find_mapped_ranges(input, start_type, end_type, ranges);
+    let final_ranges = map_ranges(input, start_type, end_type, ranges)?;
 
-    let minimum_location = final_ranges.iter()
-        .map(|range| range.start)
-        .min()
-        .unwrap();
-
-    minimum_location
+    final_ranges.min().ok_or(Day5Error::NoSeeds)
 }
 
-fn find_mapped_ranges(data: &Data, start_type: &ItemType, end_type: &ItemType, ranges: Vec<RangeType>) -> Vec<RangeType> {
-    let mut current_type = start_type;
-    let mut current_ranges = ranges;
-
-    while current_type != end_type {
-        dbg!{current_type};
-        let map = &data.maps[current_type];
-        let next_ranges = map.map_ranges(current_ranges);
-        let next_type = &map.result_type;
+/// Maps `ranges` of `start_type` values through the chain of maps to the
+/// `end_type` values they correspond to.
+pub fn map_ranges(input: &Data, start_type: &ItemType, end_type: &ItemType, ranges: RangeSet<usize>) -> Result<RangeSet<usize>, Day5Error> {
+    let chain = map_chain(input, start_type, end_type)?;
+    Ok(find_mapped_ranges(&chain, ranges))
+}
 
-        current_type = next_type;
-        current_ranges = next_ranges;
+fn seed_ranges(input: &Data) -> Result<Vec<Range<usize>>, Day5Error> {
+    if !input.seeds.len().is_multiple_of(2) {
+        return Err(Day5Error::OddSeedCount(input.seeds.len()));
     }
 
-    current_ranges
+    Ok(input.seeds
+        .chunks(2)
+        .map(|arr| arr[0].0..arr[0].0 + arr[1].0)
+        .collect())
 }
 
-#[cfg(disable)]
-#[aoc(day5, part2, memory_hog)]
-fn solve_part2_memory_intensive(input: &Data) -> usize {
-    let ranges: Vec<_> = input.seeds
-        .chunks(2)
-        .map(|arr| RangeType { start: arr[0].0, length: arr[1].0 })
-        .collect();
+fn find_mapped_ranges(chain: &[&ItemMap], ranges: RangeSet<usize>) -> RangeSet<usize> {
+    chain.iter().fold(ranges, |current_ranges, map| map.map_ranges(&current_ranges))
+}
+
+/// Maps every seed individually. Only reachable as the `memory_hog`
+/// alternative through the registry, since it needs gigabytes on a real input.
+pub fn solve_part2_memory_intensive(input: &Data) -> Result<usize, Day5Error> {
+    let ranges = seed_ranges(input)?;
 
     let start_type = &input.starting_type;
     let end_type = &ItemType("location".to_owned());
 
-    ranges.iter().map(|range| {
-        let final_values = find_mapped_large_memory(input, start_type, end_type, range);
-        final_values.into_iter().map(|i| i.0).min().unwrap()
-    }).min().unwrap()
+    let chain = map_chain(input, start_type, end_type)?;
+
+    ranges.iter()
+        .filter_map(|range| {
+            let final_values = find_mapped_large_memory(&chain, range);
+            final_values.into_iter().map(|i| i.0).min()
+        })
+        .min()
+        .ok_or(Day5Error::NoSeeds)
 }
 
-#[cfg(disable)]
-fn find_mapped_large_memory(data: &Data, start_type: &ItemType, end_type: &ItemType, range: &RangeType) -> Vec<Item> {
-    let mut current_type = start_type;
+fn find_mapped_large_memory(chain: &[&ItemMap], range: &Range<usize>) -> Vec<Item> {
     let mut current_values = vec![];
 
-    for n in range.to_range() {
+    for n in range.clone() {
         current_values.push(Item(n));
     }
 
-    dbg!{current_values.len()};
-
-    while current_type != end_type {
-        let map = &data.maps[current_type];
+    for map in chain {
         for current_value in &mut current_values {
-            *current_value = map.map_value(&current_value);
-
+            *current_value = map.map_value(current_value);
         }
-        let next_type = &map.result_type;
-
-        current_type = next_type;
     }
 
     current_values
 }
 
+crate::registry::solutions! {
+    (5, 1, input_generator, solve_part1),
+    (5, 2, input_generator, solve_part2),
+    (5, 2, "memory_hog", input_generator, solve_part2_memory_intensive),
+}
 
 #[cfg(test)]
 mod test {
-    use super::RangeType;
+    #[cfg(not(target_arch = "wasm32"))]
+    use std::collections::BTreeSet;
+    use std::ops::Range;
+
+    #[cfg(not(target_arch = "wasm32"))]
+    use proptest::prelude::*;
+
+    #[cfg(not(target_arch = "wasm32"))]
+    use super::Item;
+    #[cfg(not(target_arch = "wasm32"))]
+    use super::ItemType;
     use super::MapRange;
+    use super::RangeSet;
 
     const TEST_INPUT: &'static str =
 r#"
@@ -430,7 +349,7 @@ humidity-to-location map:
     fn test_part1_example() {
         let input = super::input_generator(TEST_INPUT).unwrap();
         dbg!{&input};
-        let result = super::solve_part1(&input);
+        let result = super::solve_part1(&input).unwrap();
 
         assert_eq!(result, 35);
     }
@@ -438,17 +357,28 @@ humidity-to-location map:
     #[test]
     fn test_part2_example() {
         let input = super::input_generator(TEST_INPUT).unwrap();
-        let result = super::solve_part2(&input);
+        let result = super::solve_part2(&input).unwrap();
 
         assert_eq!(result, 46);
     }
 
-    fn run_test(map_range: &MapRange, range: RangeType) -> (Vec<RangeType>, Vec<RangeType>) {
-        let (a, b) = map_range.try_map_range(range);
+    #[test]
+    fn test_broken_almanac() {
+        let input = super::input_generator("seeds: 1 2 3\n\nseed-to-soil map:\n1 2 3\n").unwrap();
+
+        let error = super::solve_part1(&input).unwrap_err();
+        assert_eq!(error.to_string(), "There is no map from \"soil\"");
+
+        let error = super::solve_part2(&input).unwrap_err();
+        assert_eq!(error.to_string(), "Seed ranges need a start and a length, but there are 3 seed numbers");
+    }
+
+    fn run_test(map_range: &MapRange, range: Range<usize>) -> (Vec<Range<usize>>, Vec<Range<usize>>) {
+        let (a, b) = map_range.try_map_range(&RangeSet::from(range));
 
-        (a.collect(), b.collect())
+        (a.ranges().to_vec(), b.ranges().to_vec())
     }
-    
+
     #[test]
     fn test_map_range() {
         let map_range = MapRange {
@@ -457,36 +387,79 @@ humidity-to-location map:
             length: 20,
         };
 
-        let range = RangeType {
-            start: 10,
-            length: 0,
-        };
-        let (remaining, output) = run_test(&map_range, range);
-        assert_eq!(remaining, vec![range]);
-        assert_eq!(output, vec![]);
+        let (remaining, output) = run_test(&map_range, 10..10);
+        assert!(remaining.is_empty());
+        assert!(output.is_empty());
 
-        let range = RangeType {
-            start: 40,
-            length: 10,
-        };
-        let (remaining, output) = run_test(&map_range, range);
-        assert_eq!(remaining, vec![range]);
-        assert_eq!(output, vec![]);
+        let (remaining, output) = run_test(&map_range, 40..50);
+        assert_eq!(remaining, vec![40..50]);
+        assert!(output.is_empty());
 
-        let range = RangeType {
-            start: 10,
-            length: 40,
-        };
-        let (remaining, output) = run_test(&map_range, range);
-        assert_eq!(remaining, vec![RangeType { start: 10, length: 10}, RangeType { start: 40, length: 10 }]);
-        assert_eq!(output, vec![RangeType { start: 40, length: 20}]);
+        let (remaining, output) = run_test(&map_range, 10..50);
+        assert_eq!(remaining, vec![10..20, 40..50]);
+        assert_eq!(output, vec![40..60]);
 
-        let range = RangeType {
-            start: 20,
-            length: 10,
-        };
-        let (remaining, output) = run_test(&map_range, range);
-        assert_eq!(remaining, vec![]);
-        assert_eq!(output, vec![RangeType { start: 40, length: 10}]);
+        let (remaining, output) = run_test(&map_range, 20..30);
+        assert!(remaining.is_empty());
+        assert_eq!(output, vec![40..50]);
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    const ITEM_TYPES: [&str; 8] = ["seed", "soil", "fertilizer", "water", "light", "temperature", "humidity", "location"];
+
+    /// The lines of one map, with source ranges that don't overlap.
+    #[cfg(not(target_arch = "wasm32"))]
+    fn map_ranges() -> impl Strategy<Value = Vec<String>> {
+        proptest::collection::vec((0usize..10, 1usize..20, 0usize..150), 1..=5)
+            .prop_map(|ranges| {
+                let mut source_start = 0;
+                ranges.into_iter()
+                    .map(|(gap, length, dest_start)| {
+                        source_start += gap;
+                        let line = format!("{} {} {}", dest_start, source_start, length);
+                        source_start += length;
+                        line
+                    })
+                    .collect()
+            })
+    }
+
+    /// Small almanacs with a chain of one or more maps from seeds to locations.
+    #[cfg(not(target_arch = "wasm32"))]
+    fn almanac() -> impl Strategy<Value = String> {
+        let seeds = proptest::collection::vec((0usize..120, 1usize..30), 1..=3);
+        let maps = proptest::collection::vec(map_ranges(), 1..ITEM_TYPES.len());
+
+        (seeds, maps).prop_map(|(seeds, maps)| {
+            let seeds: Vec<_> = seeds.iter().map(|(start, length)| format!("{} {}", start, length)).collect();
+            let mut text = format!("seeds: {}\n", seeds.join(" "));
+
+            let last = maps.len();
+            for (i, lines) in maps.iter().enumerate() {
+                let dest = if i + 1 == last { "location" } else { ITEM_TYPES[i + 1] };
+                text.push_str(&format!("\n{}-to-{} map:\n{}\n", ITEM_TYPES[i], dest, lines.join("\n")));
+            }
+            text
+        })
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    proptest! {
+        #[test]
+        fn test_mapped_ranges_match_values(almanac in almanac()) {
+            let input = super::input_generator(&almanac).unwrap();
+            let chain = super::map_chain(&input, &input.starting_type, &ItemType("location".to_owned())).unwrap();
+
+            let seed_ranges = super::seed_ranges(&input).unwrap();
+            let values: BTreeSet<_> = seed_ranges.iter()
+                .flat_map(|range| range.clone())
+                .map(|seed| super::find_mapped_value(&chain, Item(seed)).0)
+                .collect();
+            let ranges = super::find_mapped_ranges(&chain, seed_ranges.into_iter().collect());
+
+            prop_assert_eq!(ranges.measure(), values.len(), "{:?} in\n{}", ranges, almanac);
+            prop_assert!(values.iter().all(|&value| ranges.contains(value)), "{:?} in\n{}", ranges, almanac);
+            prop_assert_eq!(super::solve_part2(&input).unwrap(), super::solve_part2_memory_intensive(&input).unwrap());
+        }
     }
 }
@@ -1,16 +1,42 @@
+use std::io::BufRead;
+
 use aoc_runner_derive::aoc_generator;
 use aoc_runner_derive::aoc;
 
 use anyhow::Result;
-type Data = (u64, u64);
+
+use thiserror::Error;
+
+use crate::algorithms::day6::ways_to_win_the_race;
+use crate::parse::stream_lines;
+
+pub type Data = (u64, u64);
+
+#[derive(Debug, Error)]
+pub enum Day6Error {
+    #[error("The {0} line is missing")]
+    MissingLine(&'static str),
+    #[error("There are {times} times but {distances} distances")]
+    MismatchedRaces { times: usize, distances: usize },
+    #[error("The number of ways to win overflows")]
+    Overflow,
+}
 
 
 #[aoc_generator(day6)]
-fn input_generator(input: &str) -> Result<(Vec<Data>, Data)> {
+pub fn input_generator(input: &str) -> Result<(Vec<Data>, Data)> {
     let mut iter = input.lines().filter(|l| !l.is_empty());
 
-    let times = iter.next().unwrap().split_ascii_whitespace().skip(1);
-    let distances = iter.next().unwrap().split_ascii_whitespace().skip(1);
+    let time_line = iter.next().ok_or(Day6Error::MissingLine("Time"))?;
+    let distance_line = iter.next().ok_or(Day6Error::MissingLine("Distance"))?;
+
+    let times = time_line.split_ascii_whitespace().skip(1);
+    let distances = distance_line.split_ascii_whitespace().skip(1);
+
+    let (time_count, distance_count) = (times.clone().count(), distances.clone().count());
+    if time_count != distance_count {
+        return Err(Day6Error::MismatchedRaces { times: time_count, distances: distance_count }.into());
+    }
 
     let pairs: Vec<_> = times.zip(distances).map(|(time, distance)| {
         let time: u64 = time.parse()?;
@@ -20,52 +46,56 @@ fn input_generator(input: &str) -> Result<(Vec<Data>, Data)> {
     })
     .collect::<Result<Vec<_>>>()?;
 
-    let mut iter = input.lines().filter(|l| !l.is_empty());
-
-    let time = iter.next().unwrap().split_ascii_whitespace().skip(1).fold(String::new(), |mut l, r| { l += r; l }).parse()?;
-    let distance = iter.next().unwrap().split_ascii_whitespace().skip(1).fold(String::new(), |mut l, r| { l += r; l }).parse()?;
+    let time = time_line.split_ascii_whitespace().skip(1).fold(String::new(), |mut l, r| { l += r; l }).parse()?;
+    let distance = distance_line.split_ascii_whitespace().skip(1).fold(String::new(), |mut l, r| { l += r; l }).parse()?;
 
     Ok((pairs, (time, distance)))
 }
 
 #[aoc(day6, part1)]
-fn solve_part1(input: &(Vec<Data>, Data)) -> u32 {
+pub fn solve_part1(input: &(Vec<Data>, Data)) -> Result<u64, Day6Error> {
     // (t - n) * n - d > 0
     // -n^2 + tn - d > 0
 
-    let mut ways = 1;
+    let mut ways: u64 = 1;
 
     for &(time, distance) in &input.0 {
-        ways *= ways_to_win_the_race(time, distance);
+        ways = ways.checked_mul(ways_to_win_the_race(time, distance)).ok_or(Day6Error::Overflow)?;
     }
 
-    ways
+    Ok(ways)
+}
+
+#[aoc(day6, part2)]
+pub fn solve_part2(input: &(Vec<Data>, Data)) -> Result<u64, Day6Error> {
+    Ok(ways_to_win_the_race(input.1.0, input.1.1))
 }
 
-fn ways_to_win_the_race(time: u64, distance: u64) -> u32 {
-    let time: f64 = time as f64;
-    let distance: f64 = distance as f64;
+/// The input is only ever two lines, so this reads just those and parses them as usual.
+fn stream_input(input: impl BufRead) -> Result<(Vec<Data>, Data)> {
+    let lines = stream_lines(input).take(2)
+        .map(|line| Ok(line?.1))
+        .collect::<Result<Vec<_>>>()?;
 
-    let a = -1.0;
-    let b = time;
-    let c = -distance;
+    input_generator(&lines.join("\n"))
+}
 
-    let positive_zero = (-b - (b*b - 4.0 * a * c).sqrt()) / 2.0 / a;
-    let negative_zero = (-b + (b*b - 4.0 * a * c).sqrt()) / 2.0 / a;
+fn stream_part1(input: impl BufRead) -> Result<u64> {
+    Ok(solve_part1(&stream_input(input)?)?)
+}
 
-    let lowest_solution: u32 = negative_zero.floor() as u32 + 1;
-    let highest_solution: u32 = positive_zero.ceil() as u32 - 1;
+fn stream_part2(input: impl BufRead) -> Result<u64> {
+    Ok(solve_part2(&stream_input(input)?)?)
+}
 
-    if highest_solution >= lowest_solution {
-        highest_solution - lowest_solution + 1
-    } else {
-        0
-    }
+crate::registry::solutions! {
+    (6, 1, input_generator, solve_part1),
+    (6, 2, input_generator, solve_part2),
 }
 
-#[aoc(day6, part2)]
-fn solve_part2(input: &(Vec<Data>, Data)) -> u32 {
-    ways_to_win_the_race(input.1.0, input.1.1)
+crate::registry::streaming_solutions! {
+    (6, 1, stream_part1),
+    (6, 2, stream_part2),
 }
 
 #[cfg(test)]
@@ -78,7 +108,7 @@ Distance:  9  40  200
     #[test]
     fn test_part1_example() {
         let input = super::input_generator(TEST_INPUT).unwrap();
-        let result = super::solve_part1(&input);
+        let result = super::solve_part1(&input).unwrap();
 
         assert_eq!(result, 288);
     }
@@ -86,8 +116,20 @@ Distance:  9  40  200
     #[test]
     fn test_part2_example() {
         let input = super::input_generator(TEST_INPUT).unwrap();
-        let result = super::solve_part2(&input);
+        let result = super::solve_part2(&input).unwrap();
 
         assert_eq!(result, 71503);
     }
+
+    #[test]
+    fn test_invalid_input() {
+        let error = super::input_generator("Time: 7 15\n").unwrap_err();
+        assert_eq!(error.to_string(), "The Distance line is missing");
+
+        let error = super::input_generator("Time: 7 15\nDistance: 9\n").unwrap_err();
+        assert_eq!(error.to_string(), "There are 2 times but 1 distances");
+
+        let input = super::input_generator("Time: 3\nDistance: 100\n").unwrap();
+        assert_eq!(super::solve_part1(&input).unwrap(), 0);
+    }
 }
@@ -1,148 +1,111 @@
+use std::collections::BTreeMap;
+use std::io::BufRead;
+
 use aoc_runner_derive::aoc_generator;
 use aoc_runner_derive::aoc;
 
 use anyhow::Result;
-use anyhow::anyhow;
 
-type Data = (Hand, usize);
+use thiserror::Error;
 
-type Hand = [Card; 5];
-type HandWithJokers = [CardWithJoker; 5];
+use crate::algorithms::day7::Card;
+use crate::algorithms::day7::Hand;
+use crate::algorithms::day7::HandType;
+use crate::algorithms::day7::hand_type;
+use crate::parse::stream_lines;
 
-#[derive(Debug, Clone, Copy)]
-#[derive(PartialEq, Eq, PartialOrd, Ord)]
-enum Card {
-    Ace,
-    King,
-    Queen,
-    Jack,
-    Ten,
-    Nine,
-    Eight,
-    Seven,
-    Six,
-    Five,
-    Four,
-    Three,
-    Two,
-}
+pub type Data = (Hand, usize);
 
-#[derive(PartialEq, Eq, PartialOrd, Ord)]
-enum HandType {
-    FiveOfAKind,
-    FourOfAKind,
-    FullHouse,
-    ThreeOfAKind,
-    TwoPair,
-    OnePair,
-    HighCard,
-}
+type HandWithJokers = [CardWithJoker; 5];
 
+#[derive(Debug, Error)]
+pub enum Day7Error {
+    #[error("Line {line} should be a hand and a bid: {text:?}")]
+    InvalidLine { line: usize, text: String },
+    #[error("Line {line} has invalid card {card:?}")]
+    InvalidCard { line: usize, card: char },
+    #[error("Line {line} has a hand of {size} cards instead of 5")]
+    WrongHandSize { line: usize, size: usize },
+    #[error("Line {line} has invalid bid {text:?}")]
+    InvalidBid { line: usize, text: String },
+    #[error("Hand {0} doesn't match any hand type")]
+    UnknownHandType(String),
+}
 
 #[aoc_generator(day7)]
-fn input_generator(input: &str) -> Result<Vec<Data>> {
+pub fn input_generator(input: &str) -> Result<Vec<Data>> {
     let mut result = Vec::new();
 
-    for line in input.lines() {
+    for (i, line) in input.lines().enumerate() {
         if line.is_empty() { continue; }
 
-        let (hand, bid) = line.split_once(" ").map_or(Err(anyhow!("Invalid line")), |a| Ok(a))?;
+        result.push(parse_line(i + 1, line)?);
+    }
 
-        let hand = hand.chars().take(5).map(char_to_card).collect::<Result<Vec<Card>>>()?;
-        use std::convert::TryInto;
-        let hand: [Card; 5] = hand.try_into().unwrap();
+    Ok(result)
+}
 
-        let bid = bid.parse()?;
+fn parse_line(line_number: usize, line: &str) -> Result<Data, Day7Error> {
+    let (hand, bid) = line.split_once(" ")
+        .ok_or_else(|| Day7Error::InvalidLine { line: line_number, text: line.to_owned() })?;
 
-        result.push((hand, bid));
-    }
+    let hand = hand.chars()
+        .map(|c| char_to_card(c).ok_or(Day7Error::InvalidCard { line: line_number, card: c }))
+        .collect::<Result<Vec<Card>, _>>()?;
+    let size = hand.len();
+    let hand: [Card; 5] = hand.try_into()
+        .map_err(|_| Day7Error::WrongHandSize { line: line_number, size })?;
 
-    Ok(result)
+    let bid = bid.parse()
+        .map_err(|_| Day7Error::InvalidBid { line: line_number, text: bid.to_owned() })?;
+
+    Ok((hand, bid))
 }
 
-fn char_to_card(c: char) -> Result<Card> {
+fn char_to_card(c: char) -> Option<Card> {
     use Card::*;
 
     match c {
-        'A' => Ok(Ace),
-        'K' => Ok(King),
-        'Q' => Ok(Queen),
-        'J' => Ok(Jack),
-        'T' => Ok(Ten),
-        '9' => Ok(Nine),
-        '8' => Ok(Eight),
-        '7' => Ok(Seven),
-        '6' => Ok(Six),
-        '5' => Ok(Five),
-        '4' => Ok(Four),
-        '3' => Ok(Three),
-        '2' => Ok(Two),
-        _ => Err(anyhow!("Invalid card character")),
+        'A' => Some(Ace),
+        'K' => Some(King),
+        'Q' => Some(Queen),
+        'J' => Some(Jack),
+        'T' => Some(Ten),
+        '9' => Some(Nine),
+        '8' => Some(Eight),
+        '7' => Some(Seven),
+        '6' => Some(Six),
+        '5' => Some(Five),
+        '4' => Some(Four),
+        '3' => Some(Three),
+        '2' => Some(Two),
+        _ => None,
     }
 }
 
 #[aoc(day7, part1)]
-fn solve_part1(input: &[Data]) -> usize {
+pub fn solve_part1(input: &[Data]) -> Result<usize, Day7Error> {
     let mut hands: Vec<_> = input.iter()
         .map(|&(hand, bid)| {
-            (hand, hand_type(&hand), bid)
+            let hand_type = hand_type(&hand).ok_or_else(|| Day7Error::UnknownHandType(format!("{:?}", hand)))?;
+            Ok((hand, hand_type, bid))
         })
-        .collect();
+        .collect::<Result<_, Day7Error>>()?;
 
     hands.sort_by(|l, r| {
         l.1.cmp(&r.1).then(l.0.cmp(&r.0)).reverse()
     });
 
-    hands.iter().enumerate()
+    Ok(hands.iter().enumerate()
         .map(|(i, (_hand, _hand_type, bid))| {
             let rank = i + 1;
             rank * bid
         })
-        .sum()
-}
-
-const CARD_VALUES: [Card; 13] = [
-    Card::Ace,
-    Card::King,
-    Card::Queen,
-    Card::Jack,
-    Card::Ten,
-    Card::Nine,
-    Card::Eight,
-    Card::Seven,
-    Card::Six,
-    Card::Five,
-    Card::Four,
-    Card::Three,
-    Card::Two,
-];
-
-fn hand_type(hand: &Hand) -> HandType {
-    let mut counts: Vec<_> = CARD_VALUES.iter()
-        .map(|card_value| {
-            hand.iter().filter(|&card| card == card_value).count()
-        })
-        .filter(|&count| count > 0)
-        .collect();
-
-    counts.sort();
-
-    use HandType::*;
-
-    match counts.as_slice() {
-        &[5] => FiveOfAKind,
-        &[1, 4] => FourOfAKind,
-        &[2, 3] => FullHouse,
-        &[1, 1, 3] => ThreeOfAKind,
-        &[1, 2, 2] => TwoPair,
-        &[1, 1, 1, 2] => OnePair,
-        &[1, 1, 1, 1, 1] => HighCard,
-        _ => unreachable!()
-    }
+        .sum())
 }
 
 #[aoc(day7, part2)]
-fn solve_part2(input: &[Data]) -> usize {
+pub fn solve_part2(input: &[Data]) -> Result<usize, Day7Error> {
     let mut hands: Vec<_> = input.iter()
         .map(|&(hand, bid)| {
             ([
@@ -154,20 +117,21 @@ fn solve_part2(input: &[Data]) -> usize {
             ], bid)
         })
         .map(|(hand, bid): ([CardWithJoker; 5], usize)| {
-            (hand, hand_type_with_jokers(&hand), bid)
+            let hand_type = hand_type_with_jokers(&hand).ok_or_else(|| Day7Error::UnknownHandType(format!("{:?}", hand)))?;
+            Ok((hand, hand_type, bid))
         })
-        .collect();
+        .collect::<Result<_, Day7Error>>()?;
 
     hands.sort_by(|l, r| {
         l.1.cmp(&r.1).then(l.0.cmp(&r.0)).reverse()
     });
 
-    hands.iter().enumerate()
+    Ok(hands.iter().enumerate()
         .map(|(i, (_hand, _hand_type, bid))| {
             let rank = i + 1;
             rank * bid
         })
-        .sum()
+        .sum())
 }
 
 #[derive(Debug, Clone, Copy)]
@@ -224,7 +188,7 @@ const CARD_VALUES_WITH_JOKERS: [CardWithJoker; 12] = [
     CardWithJoker::Two,
 ];
 
-fn hand_type_with_jokers(hand: &HandWithJokers) -> HandType {
+fn hand_type_with_jokers(hand: &HandWithJokers) -> Option<HandType> {
     let jokers = hand.iter().filter(|&card| card == &CardWithJoker::Joker).count();
     let mut counts: Vec<_> = CARD_VALUES_WITH_JOKERS.iter()
         .map(|card_value| {
@@ -237,7 +201,7 @@ fn hand_type_with_jokers(hand: &HandWithJokers) -> HandType {
 
     use HandType::*;
 
-    match (jokers, counts.as_slice()) {
+    let hand_type = match (jokers, counts.as_slice()) {
         (0, &[5]) => FiveOfAKind,
         (0, &[1, 4]) => FourOfAKind,
         (0, &[2, 3]) => FullHouse,
@@ -257,10 +221,81 @@ fn hand_type_with_jokers(hand: &HandWithJokers) -> HandType {
         (3, &[1, 1]) => FourOfAKind,
         (4, &[1]) => FiveOfAKind,
         (5, &[]) => FiveOfAKind,
-        _ => unreachable!()
+        _ => return None,
+    };
+
+    Some(hand_type)
+}
+
+/// The bids on every copy of one hand. Copies rank in the order they were
+/// read, like the stable sort in the solvers, so this keeps each bid weighted
+/// by how many copies came before it.
+#[derive(Debug, Default)]
+struct Bids {
+    count: usize,
+    total: usize,
+    weighted: usize,
+}
+
+impl Bids {
+    fn add(&mut self, bid: usize) {
+        self.weighted += self.count * bid;
+        self.total += bid;
+        self.count += 1;
     }
 }
 
+/// The total winnings from the bids on each distinct hand, which there are at
+/// most 13^5 of however long the input is.
+fn total_winnings<H>(bids: &BTreeMap<(HandType, H), Bids>) -> usize {
+    let mut lower_ranks = 0;
+
+    bids.values().rev()
+        .map(|bids| {
+            let winnings = (lower_ranks + 1) * bids.total + bids.weighted;
+            lower_ranks += bids.count;
+            winnings
+        })
+        .sum()
+}
+
+fn stream_part1(input: impl BufRead) -> Result<usize> {
+    let mut bids = BTreeMap::new();
+
+    for line in stream_lines(input) {
+        let (line, text) = line?;
+        let (hand, bid) = parse_line(line, &text)?;
+        let hand_type = hand_type(&hand).ok_or_else(|| Day7Error::UnknownHandType(format!("{:?}", hand)))?;
+        bids.entry((hand_type, hand)).or_insert_with(Bids::default).add(bid);
+    }
+
+    Ok(total_winnings(&bids))
+}
+
+fn stream_part2(input: impl BufRead) -> Result<usize> {
+    let mut bids = BTreeMap::new();
+
+    for line in stream_lines(input) {
+        let (line, text) = line?;
+        let (hand, bid) = parse_line(line, &text)?;
+        let hand: HandWithJokers = hand.map(CardWithJoker::from);
+        let hand_type = hand_type_with_jokers(&hand).ok_or_else(|| Day7Error::UnknownHandType(format!("{:?}", hand)))?;
+        bids.entry((hand_type, hand)).or_insert_with(Bids::default).add(bid);
+    }
+
+    Ok(total_winnings(&bids))
+}
+
+crate::registry::solutions! {
+    (7, 1, input_generator, solve_part1),
+    (7, 2, input_generator, solve_part2),
+}
+
+crate::registry::streaming_solutions! {
+    (7, 1, stream_part1),
+    (7, 2, stream_part2),
+}
+
 #[cfg(test)]
 mod test {
     const TEST_INPUT: &'static str =
@@ -274,7 +309,7 @@ QQQJA 483
     #[test]
     fn test_part1_example() {
         let input = super::input_generator(TEST_INPUT).unwrap();
-        let result = super::solve_part1(&input);
+        let result = super::solve_part1(&input).unwrap();
 
         assert_eq!(result, 6440);
     }
@@ -282,8 +317,27 @@ QQQJA 483
     #[test]
     fn test_part2_example() {
         let input = super::input_generator(TEST_INPUT).unwrap();
-        let result = super::solve_part2(&input);
+        let result = super::solve_part2(&input).unwrap();
 
         assert_eq!(result, 5905);
     }
+
+    #[test]
+    fn test_invalid_hands() {
+        let error = super::input_generator("32T3K 765\n32T3 684\n").unwrap_err();
+        assert_eq!(error.to_string(), "Line 2 has a hand of 4 cards instead of 5");
+
+        let error = super::input_generator("32X3K 765\n").unwrap_err();
+        assert_eq!(error.to_string(), "Line 1 has invalid card 'X'");
+    }
+
+    #[test]
+    fn test_streaming_repeated_hands() {
+        // Copies of a hand rank in input order, so which bid gets which rank matters.
+        let input = "KK677 28\n32T3K 765\nKK677 220\n32T3K 1\n";
+        let hands = super::input_generator(input).unwrap();
+
+        assert_eq!(super::stream_part1(input.as_bytes()).unwrap(), super::solve_part1(&hands).unwrap());
+        assert_eq!(super::stream_part2(input.as_bytes()).unwrap(), super::solve_part2(&hands).unwrap());
+    }
 }
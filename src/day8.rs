@@ -5,28 +5,46 @@ use aoc_runner_derive::aoc_generator;
 use aoc_runner_derive::aoc;
 
 use anyhow::Result;
-use anyhow::anyhow;
 
 use num::Integer;
 
-type Data = (Instructions, Labels, Network);
+use thiserror::Error;
 
-type Instructions = Vec<Direction>;
+use crate::cycle;
+use crate::cycle::Cycle;
 
-type Labels = Vec<String>;
+pub type Data = (Instructions, Labels, Network);
 
-type Network = HashMap<Node, NodePair>;
+pub type Instructions = Vec<Direction>;
+
+pub type Labels = Vec<String>;
+
+pub type Network = HashMap<Node, NodePair>;
+
+#[derive(Debug, Error)]
+pub enum Day8Error {
+    #[error("There are no instructions")]
+    NoInstructions,
+    #[error("There is no node labelled {0}")]
+    MissingNode(&'static str),
+    #[error("Node {0} is referenced but never defined")]
+    UndefinedNode(String),
+    #[error("There are no nodes ending in A to start from")]
+    NoStartNodes,
+    #[error("No goal node can be reached from {0}")]
+    GoalUnreachable(String),
+}
 
 #[derive(Debug, Clone, Copy)]
-enum Direction {
+pub enum Direction {
     Left,
     Right,
 }
 
 #[derive(Debug, Clone, Copy)]
-struct NodePair {
-    left: Node,
-    right: Node,
+pub struct NodePair {
+    pub left: Node,
+    pub right: Node,
 }
 
 impl NodePair {
@@ -40,36 +58,31 @@ impl NodePair {
 
 #[derive(Debug, Clone, Copy)]
 #[derive(PartialEq, Eq, Hash)]
-struct Node {
-    label: NodeId,
+pub struct Node {
+    pub label: NodeId,
 }
 
 #[derive(Debug, Clone, Copy)]
 #[derive(PartialEq, Eq, Hash)]
-struct NodeId(usize);
+pub struct NodeId(pub usize);
 
 
 #[aoc_generator(day8)]
-fn input_generator(input: &str) -> Result<Data> {
-    let (input, result) = parse_input(input).map_err(|err| err.to_owned())?;
-    if !input.is_empty() {
-        return Err(anyhow!("Had unparsed input after parsing: {}", input));
-    }
-    Ok(result)
+pub fn input_generator(input: &str) -> Result<Data> {
+    run_parser(input, parse_input)
 }
 
-use nom::IResult;
 use nom::bytes::complete::take;
-use nom::combinator::opt;
-use nom::multi::separated_list1;
 use nom::bytes::complete::tag;
 
+use crate::parse::IResult;
+use crate::parse::lines;
+use crate::parse::run_parser;
+
 fn parse_input(input: &str) -> IResult<&str, Data> {
-    let (input, _) = opt(tag("\n"))(input)?;
     let (input, directions) = parse_directions(input)?;
     let (input, _) = tag("\n\n")(input)?;
     let (input, (labels, network)) = parse_network(input)?;
-    let (input, _) = opt(tag("\n"))(input)?;
 
     Ok((input, (directions, labels, network)))
 }
@@ -129,7 +142,7 @@ fn parse_network(input: &str) -> IResult<&str, (Labels, Network)> {
 }
 
 fn parse_str_network(input: &str) -> IResult<&str, Vec<(&str, (&str, &str))>> {
-    separated_list1(tag("\n"), parse_single_node)(input)
+    lines(parse_single_node)(input)
 }
 
 fn parse_single_node(input: &str) -> IResult<&str, (&str, (&str, &str))> {
@@ -148,29 +161,46 @@ fn parse_label(input: &str) -> IResult<&str, &str> {
 }
 
 
-#[aoc(day8, part1)]
-fn solve_part1(input: &Data) -> usize {
-    let goal_node = input.1.iter()
-        .enumerate()
-        .filter(|&(_, label)| label == "ZZZ")
-        .map(|(i, _)| Node {
-            label: NodeId(i),
-        })
-        .next()
-        .unwrap();
+/// Checks that every step of a walk through the network can be taken.
+fn check_network(input: &Data) -> Result<(), Day8Error> {
+    if input.0.is_empty() {
+        return Err(Day8Error::NoInstructions);
+    }
 
-    let mut current_node = input.1.iter()
-        .enumerate()
-        .filter(|&(_, label)| label == "AAA")
-        .map(|(i, _)| Node {
+    for (i, label) in input.1.iter().enumerate() {
+        if !input.2.contains_key(&Node { label: NodeId(i) }) {
+            return Err(Day8Error::UndefinedNode(label.clone()));
+        }
+    }
+
+    Ok(())
+}
+
+fn find_node(input: &Data, name: &'static str) -> Result<Node, Day8Error> {
+    input.1.iter()
+        .position(|label| label == name)
+        .map(|i| Node {
             label: NodeId(i),
         })
-        .next()
-        .unwrap();
+        .ok_or(Day8Error::MissingNode(name))
+}
+
+#[aoc(day8, part1)]
+pub fn solve_part1(input: &Data) -> Result<usize, Day8Error> {
+    check_network(input)?;
+
+    let goal_node = find_node(input, "ZZZ")?;
+    let mut current_node = find_node(input, "AAA")?;
 
     let mut steps = 0;
+    // After this many steps every (node, instruction) pair has been visited
+    let step_limit = input.1.len() * input.0.len();
 
     while current_node != goal_node {
+        if steps > step_limit {
+            return Err(Day8Error::GoalUnreachable("AAA".to_owned()));
+        }
+
         let index = steps % input.0.len();
         let direction = input.0[index];
 
@@ -180,7 +210,7 @@ fn solve_part1(input: &Data) -> usize {
         steps += 1;
     }
 
-    steps
+    Ok(steps)
 }
 
 #[derive(Debug, Clone)]
@@ -190,12 +220,13 @@ struct CycleInfo {
     ///  other out and you're left with a multiple of cycle length.
     #[allow(unused)]
     solve_times: HashMap<Node, usize>,
-    cycle_start_time: usize,
-    cycle_end_time: usize,
+    cycle: Cycle,
 }
 
 #[aoc(day8, part2)]
-fn solve_part2(input: &Data) -> usize {
+pub fn solve_part2(input: &Data) -> Result<usize, Day8Error> {
+    check_network(input)?;
+
     let starts: Vec<_> = input.1.iter()
         .enumerate()
         .filter(|&(_, label)| label.ends_with("A"))
@@ -203,6 +234,10 @@ fn solve_part2(input: &Data) -> usize {
             label: NodeId(i),
         })
         .collect();
+    if starts.is_empty() {
+        return Err(Day8Error::NoStartNodes);
+    }
+
     let goals: HashSet<_> = input.1.iter()
         .enumerate()
         .filter(|&(_, label)| label.ends_with("Z"))
@@ -213,42 +248,40 @@ fn solve_part2(input: &Data) -> usize {
 
     let info: Vec<_> = starts.iter()
         .map(|&start_node| {
-            let mut current_node = start_node;
-            let mut steps = 0;
-
-            let mut solve_times = HashMap::new();
-
-            let mut visited = HashMap::new();
-
-            loop {
-                let index = steps % input.0.len();
-                if visited.contains_key(&(current_node, index)) {
-                    break;
-                }
-                visited.insert((current_node, index), steps);
+            let history = cycle::record(&(start_node, 0), |&(current_node, index)| {
                 let direction = input.0[index];
 
                 let next_nodes = &input.2[&current_node];
 
-                current_node = next_nodes.select_path(direction);
-                steps += 1;
+                (next_nodes.select_path(direction), (index + 1) % input.0.len())
+            });
 
-                if goals.contains(&current_node) {
-                    solve_times.insert(current_node, steps);
+            let mut solve_times = HashMap::new();
+            for (steps, (node, _)) in history.states.iter().enumerate() {
+                if goals.contains(node) {
+                    solve_times.insert(*node, steps);
                 }
             }
 
-            CycleInfo {
-                solve_times,
-                cycle_start_time: visited[&(current_node, steps % input.0.len())],
-                cycle_end_time: steps,
+            if solve_times.is_empty() {
+                return Err(Day8Error::GoalUnreachable(input.1[start_node.label.0].clone()));
             }
+
+            Ok(CycleInfo {
+                solve_times,
+                cycle: history.cycle,
+            })
         })
-        .collect();
+        .collect::<Result<_, _>>()?;
 
-    info.iter()
-        .map(|info| info.cycle_end_time - info.cycle_start_time)
-        .fold(1, |a, b| a.lcm(&b))
+    Ok(info.iter()
+        .map(|info| info.cycle.period)
+        .fold(1, |a, b| a.lcm(&b)))
+}
+
+crate::registry::solutions! {
+    (8, 1, input_generator, solve_part1),
+    (8, 2, input_generator, solve_part2),
 }
 
 #[cfg(test)]
@@ -290,12 +323,12 @@ XXX = (XXX, XXX)
     #[test]
     fn test_part1_example() {
         let input = super::input_generator(TEST_INPUT).unwrap();
-        let result = super::solve_part1(&input);
+        let result = super::solve_part1(&input).unwrap();
 
         assert_eq!(result, 6);
 
         let input = super::input_generator(TEST_INPUT2).unwrap();
-        let result = super::solve_part1(&input);
+        let result = super::solve_part1(&input).unwrap();
 
         assert_eq!(result, 2);
     }
@@ -303,8 +336,19 @@ XXX = (XXX, XXX)
     #[test]
     fn test_part2_example() {
         let input = super::input_generator(TEST_INPUT3).unwrap();
-        let result = super::solve_part2(&input);
+        let result = super::solve_part2(&input).unwrap();
 
         assert_eq!(result, 6);
     }
+
+    #[test]
+    fn test_broken_network() {
+        let input = super::input_generator("L\n\nAAA = (BBB, BBB)\n").unwrap();
+        let error = super::solve_part1(&input).unwrap_err();
+        assert_eq!(error.to_string(), "Node BBB is referenced but never defined");
+
+        let input = super::input_generator("L\n\nAAA = (AAA, ZZZ)\nZZZ = (ZZZ, ZZZ)\n").unwrap();
+        let error = super::solve_part1(&input).unwrap_err();
+        assert_eq!(error.to_string(), "No goal node can be reached from AAA");
+    }
 }
@@ -1,62 +1,85 @@
+use std::io::BufRead;
+
 use aoc_runner_derive::aoc_generator;
 use aoc_runner_derive::aoc;
 
 use anyhow::Result;
 
-type Data = Sequence;
+use thiserror::Error;
+
+use crate::algorithms::day9::predict_next;
+use crate::algorithms::day9::predict_prev;
+use crate::parse::stream_lines;
+
+pub type Data = Sequence;
 
-type Sequence = Vec<i64>;
+pub type Sequence = Vec<i64>;
+
+#[derive(Debug, Error)]
+pub enum Day9Error {
+    #[error("Sequence {0} is empty")]
+    EmptySequence(usize),
+}
 
 
 #[aoc_generator(day9)]
-fn input_generator(input: &str) -> Result<Vec<Data>> {
+pub fn input_generator(input: &str) -> Result<Vec<Data>> {
     let result: Vec<Vec<i64>> = input.lines()
         .filter(|line| !line.is_empty())
-        .map(|line| line
-            .split_ascii_whitespace()
-            .map(|n_str| Ok(n_str.parse::<i64>()?))
-            .collect::<Result<Sequence>>())
+        .map(parse_sequence)
         .collect::<Result<Vec<Data>>>()?;
 
     Ok(result)
 }
 
-#[aoc(day9, part1)]
-fn solve_part1(input: &[Data]) -> i64 {
-    input.iter().map(|sequence| predict_next(sequence)).sum()
+fn parse_sequence(line: &str) -> Result<Sequence> {
+    line.split_ascii_whitespace()
+        .map(|n_str| Ok(n_str.parse::<i64>()?))
+        .collect()
 }
 
-fn predict_next(sequence: &[i64]) -> i64 {
-    let difference_sequence: Vec<_> = sequence.windows(2)
-        .map(|window| window[1] - window[0])
-        .collect();
+/// Sums `predict` over each line of `input`, one sequence at a time.
+fn stream_sum(input: impl BufRead, predict: fn(&[i64]) -> Option<i64>) -> Result<i64> {
+    let mut total = 0;
+    for line in stream_lines(input) {
+        let (line, text) = line?;
+        let sequence = parse_sequence(&text)?;
+        total += predict(&sequence).ok_or(Day9Error::EmptySequence(line))?;
+    }
 
-    let next_difference = if difference_sequence.iter().filter(|&&n| n != 0).next().is_none() {
-        0
-    } else {
-        predict_next(&difference_sequence)
-    };
+    Ok(total)
+}
 
-    sequence.last().unwrap() + next_difference
+fn stream_part1(input: impl BufRead) -> Result<i64> {
+    stream_sum(input, predict_next)
 }
 
-#[aoc(day9, part2)]
-fn solve_part2(input: &[Data]) -> i64 {
-    input.iter().map(|sequence| predict_prev(sequence)).sum()
+fn stream_part2(input: impl BufRead) -> Result<i64> {
+    stream_sum(input, predict_prev)
 }
 
-fn predict_prev(sequence: &[i64]) -> i64 {
-    let difference_sequence: Vec<_> = sequence.windows(2)
-        .map(|window| window[1] - window[0])
-        .collect();
+#[aoc(day9, part1)]
+pub fn solve_part1(input: &[Data]) -> Result<i64, Day9Error> {
+    input.iter().enumerate()
+        .map(|(i, sequence)| predict_next(sequence).ok_or(Day9Error::EmptySequence(i + 1)))
+        .sum()
+}
 
-    let prev_difference = if difference_sequence.iter().filter(|&&n| n != 0).next().is_none() {
-        0
-    } else {
-        predict_prev(&difference_sequence)
-    };
+#[aoc(day9, part2)]
+pub fn solve_part2(input: &[Data]) -> Result<i64, Day9Error> {
+    input.iter().enumerate()
+        .map(|(i, sequence)| predict_prev(sequence).ok_or(Day9Error::EmptySequence(i + 1)))
+        .sum()
+}
+
+crate::registry::solutions! {
+    (9, 1, input_generator, solve_part1),
+    (9, 2, input_generator, solve_part2),
+}
 
-    sequence.first().unwrap() - prev_difference
+crate::registry::streaming_solutions! {
+    (9, 1, stream_part1),
+    (9, 2, stream_part2),
 }
 
 #[cfg(test)]
@@ -70,7 +93,7 @@ r#"
     #[test]
     fn test_part1_example() {
         let input = super::input_generator(TEST_INPUT).unwrap();
-        let result = super::solve_part1(&input);
+        let result = super::solve_part1(&input).unwrap();
 
         assert_eq!(result, 114);
     }
@@ -78,8 +101,16 @@ r#"
     #[test]
     fn test_part2_example() {
         let input = super::input_generator(TEST_INPUT).unwrap();
-        let result = super::solve_part2(&input);
+        let result = super::solve_part2(&input).unwrap();
 
         assert_eq!(result, 2);
     }
+
+    #[test]
+    fn test_empty_sequence() {
+        let input = super::input_generator("1 2\n \n").unwrap();
+        let error = super::solve_part1(&input).unwrap_err();
+
+        assert_eq!(error.to_string(), "Sequence 2 is empty");
+    }
 }
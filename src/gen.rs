@@ -0,0 +1,319 @@
+//! Synthesizes random puzzle inputs in each day's format, for stress tests
+//! and benchmarks that need more than the examples. Every generator is
+//! deterministic in its seed, and its output is shaped to satisfy whatever
+//! the solvers assume about the real inputs.
+
+use std::collections::HashSet;
+use std::ops::RangeInclusive;
+
+use crate::grid::Direction;
+use crate::grid::Grid;
+use crate::grid::Pos;
+
+mod day1;
+mod day2;
+mod day3;
+mod day4;
+mod day5;
+mod day6;
+mod day7;
+mod day8;
+mod day9;
+mod day10;
+mod day11;
+mod day12;
+mod day13;
+mod day14;
+mod day15;
+mod day16;
+mod day17;
+mod day18;
+mod day19;
+mod day20;
+mod day21;
+mod day22;
+mod day23;
+mod day24;
+mod day25;
+
+/// A small, fast SplitMix64 generator. Its output only depends on the seed,
+/// so generated inputs stay the same across platforms and releases.
+#[derive(Debug, Clone)]
+pub struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        Rng { state: seed }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    /// A uniformly chosen value in `range`.
+    pub fn range<T: Uniform>(&mut self, range: RangeInclusive<T>) -> T {
+        T::sample(self, range)
+    }
+
+    /// A uniformly chosen index into a slice of length `len`, which must not be zero.
+    pub fn index(&mut self, len: usize) -> usize {
+        assert!(len > 0, "can't pick from an empty slice");
+        self.range(0..=len - 1)
+    }
+
+    /// `true` with probability `p`.
+    pub fn chance(&mut self, p: f64) -> bool {
+        ((self.next_u64() >> 11) as f64 / (1u64 << 53) as f64) < p
+    }
+
+    pub fn choose<'a, T>(&mut self, items: &'a [T]) -> &'a T {
+        &items[self.index(items.len())]
+    }
+
+    pub fn shuffle<T>(&mut self, items: &mut [T]) {
+        for i in (1..items.len()).rev() {
+            let j = self.range(0..=i);
+            items.swap(i, j);
+        }
+    }
+}
+
+/// Integer types that `Rng::range` can sample.
+pub trait Uniform: Copy {
+    fn sample(rng: &mut Rng, range: RangeInclusive<Self>) -> Self;
+}
+
+macro_rules! uniform {
+    ($($t:ty),*) => {
+        $(
+            impl Uniform for $t {
+                fn sample(rng: &mut Rng, range: RangeInclusive<Self>) -> Self {
+                    let (low, high) = range.into_inner();
+                    assert!(low <= high, "empty range {}..={}", low, high);
+                    // Widening to i128 keeps the span exact for every type here.
+                    let span = (high as i128 - low as i128 + 1) as u128;
+                    let offset = (rng.next_u64() as u128 * span) >> 64;
+                    (low as i128 + offset as i128) as $t
+                }
+            }
+        )*
+    };
+}
+
+uniform!(u8, i32, u32, u64, usize, i64);
+
+/// Writes a random input for one day.
+#[derive(Debug, Clone, Copy)]
+pub struct Generator {
+    pub day: u32,
+    /// What `size` controls, for usage text.
+    pub size: &'static str,
+    /// A size that gives inputs about as large as the real ones.
+    pub default_size: usize,
+    pub generate: fn(&mut Rng, usize) -> String,
+}
+
+impl Generator {
+    /// Generates an input from `seed`. Sizes below 1 are treated as 1, and
+    /// some days raise or cap the size further to keep the input valid.
+    pub fn run(&self, seed: u64, size: usize) -> String {
+        (self.generate)(&mut Rng::new(seed), size.max(1))
+    }
+}
+
+const GENERATORS: &[Generator] = &[
+    Generator { day: 1, size: "lines", default_size: 1000, generate: day1::generate },
+    Generator { day: 2, size: "games", default_size: 100, generate: day2::generate },
+    Generator { day: 3, size: "schematic side", default_size: 140, generate: day3::generate },
+    Generator { day: 4, size: "cards", default_size: 200, generate: day4::generate },
+    Generator { day: 5, size: "ranges per map", default_size: 30, generate: day5::generate },
+    Generator { day: 6, size: "races, 1 to 4", default_size: 4, generate: day6::generate },
+    Generator { day: 7, size: "hands", default_size: 1000, generate: day7::generate },
+    Generator { day: 8, size: "instructions, at most 400", default_size: 263, generate: day8::generate },
+    Generator { day: 9, size: "sequences", default_size: 200, generate: day9::generate },
+    Generator { day: 10, size: "map side", default_size: 140, generate: day10::generate },
+    Generator { day: 11, size: "image side", default_size: 140, generate: day11::generate },
+    Generator { day: 12, size: "records", default_size: 1000, generate: day12::generate },
+    Generator { day: 13, size: "patterns", default_size: 100, generate: day13::generate },
+    Generator { day: 14, size: "dish side", default_size: 100, generate: day14::generate },
+    Generator { day: 15, size: "steps", default_size: 4000, generate: day15::generate },
+    Generator { day: 16, size: "contraption side", default_size: 110, generate: day16::generate },
+    Generator { day: 17, size: "city side", default_size: 141, generate: day17::generate },
+    Generator { day: 18, size: "lagoon side in cells", default_size: 40, generate: day18::generate },
+    Generator { day: 19, size: "workflows", default_size: 500, generate: day19::generate },
+    Generator { day: 20, size: "bits per counter, 5 to 20", default_size: 12, generate: day20::generate },
+    Generator { day: 21, size: "garden side", default_size: 131, generate: day21::generate },
+    Generator { day: 22, size: "bricks", default_size: 1200, generate: day22::generate },
+    Generator { day: 23, size: "maze side in cells", default_size: 20, generate: day23::generate },
+    Generator { day: 24, size: "hailstones", default_size: 300, generate: day24::generate },
+    Generator { day: 25, size: "components", default_size: 1500, generate: day25::generate },
+];
+
+/// Every generator, ordered by day.
+pub fn all() -> impl Iterator<Item = &'static Generator> {
+    GENERATORS.iter()
+}
+
+pub fn find(day: u32) -> Option<&'static Generator> {
+    all().find(|generator| generator.day == day)
+}
+
+/// Renders a grid one row per line.
+fn grid_text<T>(grid: &Grid<T>, mut f: impl FnMut(&T) -> char) -> String {
+    let mut text = String::with_capacity(grid.rows() * (grid.cols() + 1));
+    for row in grid.as_array().rows() {
+        text.extend(row.iter().map(&mut f));
+        text.push('\n');
+    }
+    text
+}
+
+/// `count` distinct random names, with the `i`th character of each drawn from
+/// `alphabets[i]`, skipping any in `reserved`.
+fn names(rng: &mut Rng, count: usize, alphabets: &[&[u8]], reserved: &[&str]) -> Vec<String> {
+    let mut seen: HashSet<String> = reserved.iter().map(|&name| name.to_owned()).collect();
+    let mut names = Vec::with_capacity(count);
+    while names.len() < count {
+        let name: String = alphabets.iter().map(|alphabet| char::from(*rng.choose(alphabet))).collect();
+        if seen.insert(name.clone()) {
+            names.push(name);
+        }
+    }
+    names
+}
+
+fn is_prime(n: usize) -> bool {
+    n >= 2 && (2..).take_while(|d| d * d <= n).all(|d| !n.is_multiple_of(d))
+}
+
+/// A random blob of roughly `cells` cells in a `rows` by `cols` grid, grown
+/// one cell at a time from the middle. A cell is only added when the blob
+/// touches it along one unbroken stretch of its 8-neighbourhood, and never
+/// only at a corner, so the blob never gets holes or pinch points and its
+/// outline is a single loop.
+fn blob(rng: &mut Rng, rows: usize, cols: usize, cells: usize) -> Grid<bool> {
+    // The ring around a cell, clockwise from straight up. Odd indices are diagonals.
+    const RING: [(isize, isize); 8] = [(-1, 0), (-1, 1), (0, 1), (1, 1), (1, 0), (1, -1), (0, -1), (-1, -1)];
+
+    let mut blob = Grid::from_elem((rows, cols), false);
+    let start = Pos::new(rows / 2, cols / 2);
+    blob[start] = true;
+    let mut size = 1;
+    let mut frontier: Vec<_> = blob.neighbors(start).map(|(_, pos)| pos).collect();
+
+    while size < cells && !frontier.is_empty() {
+        let pos = frontier.swap_remove(rng.index(frontier.len()));
+        if blob[pos] { continue; }
+
+        let ring = RING.map(|offset| pos.try_offset(offset, blob.bounds()).is_some_and(|pos| blob[pos]));
+        let runs = (0..8).filter(|&i| ring[i] && !ring[(i + 7) % 8]).count();
+        let pinched = (1..8).step_by(2).any(|i| ring[i] && !ring[i - 1] && !ring[(i + 1) % 8]);
+        if runs != 1 || pinched { continue; }
+
+        blob[pos] = true;
+        size += 1;
+        frontier.extend(blob.neighbors(pos).filter(|&(_, pos)| !blob[pos]).map(|(_, pos)| pos));
+    }
+
+    blob
+}
+
+/// Walks the outline of a `blob` clockwise, keeping the blob on the right.
+/// The outline runs along the corners of the cells, so it's on a grid one
+/// larger than the blob in each direction. Each corner is returned with the
+/// direction the walk leaves it in, starting from the top left corner of the
+/// topmost cell.
+fn outline(blob: &Grid<bool>) -> Vec<(Pos, Direction)> {
+    let filled = |row: usize, col: usize, d_row: isize, d_col: isize| {
+        Pos::new(row, col).try_offset((d_row, d_col), blob.bounds()).is_some_and(|pos| blob[pos])
+    };
+    // Whether the edge leaving this corner in a direction has the blob on its right
+    let leaves = |pos: Pos, direction: Direction| {
+        let Pos { row, col } = pos;
+        use Direction::*;
+        match direction {
+            Right => filled(row, col, 0, 0) && !filled(row, col, -1, 0),
+            Down => filled(row, col, 0, -1) && !filled(row, col, 0, 0),
+            Left => filled(row, col, -1, -1) && !filled(row, col, 0, -1),
+            Up => filled(row, col, -1, 0) && !filled(row, col, -1, -1),
+        }
+    };
+
+    let Some((start, _)) = blob.indexed_iter().find(|&(_, &filled)| filled) else {
+        return Vec::new();
+    };
+    let corners = (blob.rows() + 1, blob.cols() + 1);
+
+    let mut path = Vec::new();
+    let mut pos = start;
+    loop {
+        let direction = Direction::ALL.into_iter()
+            .find(|&direction| leaves(pos, direction))
+            .expect("every corner on the outline has an edge out");
+        path.push((pos, direction));
+        pos = pos.try_move(direction, corners).unwrap();
+        if pos == start {
+            return path;
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::registry;
+
+    #[test]
+    fn test_every_day_has_a_generator() {
+        for day in 1..=25 {
+            assert!(super::find(day).is_some(), "missing day {}", day);
+        }
+    }
+
+    #[test]
+    fn test_deterministic() {
+        for generator in super::all() {
+            assert_eq!(generator.run(7, 10), generator.run(7, 10), "day {}", generator.day);
+        }
+        let generator = super::find(7).unwrap();
+        assert_ne!(generator.run(1, 10), generator.run(2, 10));
+    }
+
+    #[test]
+    fn test_generated_inputs_solve() {
+        for generator in super::all() {
+            for seed in 0..3 {
+                let input = generator.run(seed, 8);
+                for solution in registry::all().filter(|solution| solution.day == generator.day && solution.name.is_none()) {
+                    if let Err(e) = (solution.run)(&input) {
+                        panic!("day {} part {} failed on seed {}: {}\n{}", solution.day, solution.part, seed, e, input);
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_outline() {
+        let mut rng = super::Rng::new(3);
+        for _ in 0..20 {
+            let blob = super::blob(&mut rng, 12, 9, 50);
+            let outline = super::outline(&blob);
+
+            let corners: std::collections::HashSet<_> = outline.iter().map(|&(pos, _)| pos).collect();
+            assert_eq!(corners.len(), outline.len(), "the outline crosses itself");
+
+            let cells = blob.iter().filter(|&&filled| filled).count();
+            let perimeter: usize = blob.indexed_iter()
+                .filter(|&(_, &filled)| filled)
+                .map(|(pos, _)| 4 - blob.neighbors(pos).filter(|&(_, pos)| blob[pos]).count())
+                .sum();
+            assert_eq!(outline.len(), perimeter, "a blob of {} cells", cells);
+        }
+    }
+}
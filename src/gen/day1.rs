@@ -0,0 +1,26 @@
+use super::Rng;
+
+const WORDS: [&str; 9] = ["one", "two", "three", "four", "five", "six", "seven", "eight", "nine"];
+
+/// Calibration lines of letters, digits and spelled-out digits. Every line has
+/// at least one real digit, so both parts can read it.
+pub(super) fn generate(rng: &mut Rng, lines: usize) -> String {
+    let mut text = String::new();
+
+    for _ in 0..lines {
+        let tokens = rng.range(1..=6);
+        let digit_at = rng.index(tokens);
+
+        for i in 0..tokens {
+            match rng.range(0..=2) {
+                _ if i == digit_at => text.push(char::from(rng.range(b'1'..=b'9'))),
+                0 => text.push_str(WORDS[rng.index(WORDS.len())]),
+                1 => text.push(char::from(rng.range(b'1'..=b'9'))),
+                _ => text.extend((0..rng.range(1..=4)).map(|_| char::from(rng.range(b'a'..=b'z')))),
+            }
+        }
+        text.push('\n');
+    }
+
+    text
+}
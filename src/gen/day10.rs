@@ -0,0 +1,54 @@
+use crate::grid::Direction;
+use crate::grid::Grid;
+
+use super::Rng;
+
+const PIPES: &[u8] = b"|-LJ7F.";
+
+/// A square field of pipes with one loop through `S`, traced around a random
+/// blob. The other tiles are random junk, except that none next to `S`
+/// connect to it.
+pub(super) fn generate(rng: &mut Rng, side: usize) -> String {
+    let side = side.max(3);
+    let area = (side - 1) * (side - 1);
+    let cells = rng.range(area / 4..=area / 2).max(1);
+    let blob = super::blob(rng, side - 1, side - 1, cells);
+    let outline = super::outline(&blob);
+
+    let mut field = Grid::from_fn((side, side), |_| char::from(*rng.choose(PIPES)));
+    for (i, &(pos, out)) in outline.iter().enumerate() {
+        let (_, into) = outline[(i + outline.len() - 1) % outline.len()];
+        field[pos] = pipe(into.invert(), out);
+    }
+
+    let (start, _) = *rng.choose(&outline);
+    field[start] = 'S';
+    for (direction, neighbor) in field.neighbors(start).collect::<Vec<_>>() {
+        let on_loop = outline.iter().any(|&(pos, _)| pos == neighbor);
+        if !on_loop && openings(field[neighbor]).contains(&direction.invert()) {
+            field[neighbor] = '.';
+        }
+    }
+
+    super::grid_text(&field, |&c| c)
+}
+
+fn pipe(a: Direction, b: Direction) -> char {
+    PIPES.iter()
+        .map(|&c| char::from(c))
+        .find(|&c| openings(c).contains(&a) && openings(c).contains(&b))
+        .unwrap()
+}
+
+fn openings(c: char) -> Vec<Direction> {
+    use Direction::*;
+    match c {
+        '|' => vec![Up, Down],
+        '-' => vec![Left, Right],
+        'L' => vec![Up, Right],
+        'J' => vec![Up, Left],
+        '7' => vec![Down, Left],
+        'F' => vec![Down, Right],
+        _ => vec![],
+    }
+}
@@ -0,0 +1,15 @@
+use crate::grid::Grid;
+
+use super::Rng;
+
+/// A sparse square image of galaxies, with about one row and column in ten left empty.
+pub(super) fn generate(rng: &mut Rng, side: usize) -> String {
+    let empty_rows: Vec<_> = (0..side).map(|_| rng.chance(0.1)).collect();
+    let empty_cols: Vec<_> = (0..side).map(|_| rng.chance(0.1)).collect();
+
+    let image = Grid::from_fn((side, side), |pos| {
+        !empty_rows[pos.row] && !empty_cols[pos.col] && rng.chance(0.03)
+    });
+
+    super::grid_text(&image, |&galaxy| if galaxy { '#' } else { '.' })
+}
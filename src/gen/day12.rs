@@ -0,0 +1,31 @@
+use super::Rng;
+
+/// Records of up to 20 springs, read off a random row with some springs
+/// hidden. The groups always match the hidden row, so every record has at
+/// least one arrangement.
+pub(super) fn generate(rng: &mut Rng, records: usize) -> String {
+    let mut text = String::new();
+
+    for _ in 0..records {
+        let length = rng.range(1..=20);
+        let mut damaged: Vec<bool> = (0..length).map(|_| rng.chance(0.4)).collect();
+        let at = rng.index(length);
+        damaged[at] = true;
+
+        let groups: Vec<_> = damaged.split(|&damaged| !damaged)
+            .filter(|group| !group.is_empty())
+            .map(|group| group.len().to_string())
+            .collect();
+        let row: String = damaged.iter()
+            .map(|&damaged| match (rng.chance(0.4), damaged) {
+                (true, _) => '?',
+                (false, true) => '#',
+                (false, false) => '.',
+            })
+            .collect();
+
+        text.push_str(&format!("{} {}\n", row, groups.join(",")));
+    }
+
+    text
+}
@@ -0,0 +1,82 @@
+use super::Rng;
+
+/// Patterns with a perfect line of reflection for part 1 and a different
+/// line that's off by exactly one smudge for part 2.
+pub(super) fn generate(rng: &mut Rng, patterns: usize) -> String {
+    (0..patterns).map(|_| pattern(rng)).collect::<Vec<_>>().join("\n")
+}
+
+/// Picks the two lines between rows, makes every pair of rows they reflect
+/// onto each other equal, apart from one pair across the smudged line, and
+/// fills in rows to match. Half the patterns are then turned on their side.
+fn pattern(rng: &mut Rng) -> String {
+    loop {
+        let rows = rng.range(5..=17);
+        let cols = rng.range(5..=17);
+        let perfect = rng.range(1..=rows - 1);
+        let smudged = rng.range(1..=rows - 1);
+        if perfect == smudged { continue; }
+
+        let mut classes = Classes::new(rows);
+        for (above, below) in reflected_pairs(perfect, rows) {
+            classes.merge(above, below);
+        }
+        let pairs = reflected_pairs(smudged, rows);
+        let smudge = rng.index(pairs.len());
+        for (i, &(above, below)) in pairs.iter().enumerate() {
+            if i != smudge {
+                classes.merge(above, below);
+            }
+        }
+
+        let (above, below) = pairs[smudge];
+        if classes.find(above) == classes.find(below) { continue; }
+
+        let mut values: Vec<u32> = (0..rows).map(|_| rng.range(0..=(1 << cols) - 1)).collect();
+        values[classes.find(below)] = values[classes.find(above)] ^ (1 << rng.index(cols));
+        let lines: Vec<u32> = (0..rows).map(|row| values[classes.find(row)]).collect();
+
+        let cell = |line: u32, bit: usize| if line & (1 << bit) != 0 { '#' } else { '.' };
+        let mut text = String::new();
+        if rng.chance(0.5) {
+            for &line in &lines {
+                text.extend((0..cols).map(|bit| cell(line, bit)));
+                text.push('\n');
+            }
+        } else {
+            for bit in 0..cols {
+                text.extend(lines.iter().map(|&line| cell(line, bit)));
+                text.push('\n');
+            }
+        }
+        return text;
+    }
+}
+
+/// The rows a line of reflection above row `line` maps onto each other.
+fn reflected_pairs(line: usize, rows: usize) -> Vec<(usize, usize)> {
+    (0..line).rev().zip(line..rows).collect()
+}
+
+/// A union-find over row indices.
+struct Classes {
+    parents: Vec<usize>,
+}
+
+impl Classes {
+    fn new(len: usize) -> Self {
+        Classes { parents: (0..len).collect() }
+    }
+
+    fn find(&mut self, i: usize) -> usize {
+        if self.parents[i] != i {
+            self.parents[i] = self.find(self.parents[i]);
+        }
+        self.parents[i]
+    }
+
+    fn merge(&mut self, a: usize, b: usize) {
+        let (a, b) = (self.find(a), self.find(b));
+        self.parents[a] = b;
+    }
+}
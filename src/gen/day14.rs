@@ -0,0 +1,14 @@
+use crate::grid::Grid;
+
+use super::Rng;
+
+/// A square dish with about a quarter rounded rocks and a tenth cube rocks.
+pub(super) fn generate(rng: &mut Rng, side: usize) -> String {
+    let dish = Grid::from_fn((side, side), |_| match rng.range(0..=19) {
+        0..=4 => 'O',
+        5..=6 => '#',
+        _ => '.',
+    });
+
+    super::grid_text(&dish, |&c| c)
+}
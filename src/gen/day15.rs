@@ -0,0 +1,22 @@
+use super::Rng;
+
+/// One line of steps that add, replace and remove lenses, drawing labels
+/// from a shared pool so that most labels come up more than once.
+pub(super) fn generate(rng: &mut Rng, steps: usize) -> String {
+    let labels: Vec<String> = (0..steps / 4 + 1)
+        .map(|_| (0..rng.range(2..=6)).map(|_| char::from(rng.range(b'a'..=b'z'))).collect())
+        .collect();
+
+    let steps: Vec<_> = (0..steps)
+        .map(|_| {
+            let label = rng.choose(&labels);
+            if rng.chance(0.3) {
+                format!("{}-", label)
+            } else {
+                format!("{}={}", label, rng.range(1..=9u32))
+            }
+        })
+        .collect();
+
+    format!("{}\n", steps.join(","))
+}
@@ -0,0 +1,12 @@
+use crate::grid::Grid;
+
+use super::Rng;
+
+/// A square contraption with a mirror or splitter on about one tile in ten.
+pub(super) fn generate(rng: &mut Rng, side: usize) -> String {
+    let contraption = Grid::from_fn((side, side), |_| {
+        if rng.chance(0.1) { char::from(*rng.choose(b"/\\|-")) } else { '.' }
+    });
+
+    super::grid_text(&contraption, |&c| c)
+}
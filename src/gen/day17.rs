@@ -0,0 +1,11 @@
+use crate::grid::Grid;
+
+use super::Rng;
+
+/// A square city of heat losses from 1 to 9. It's at least five blocks
+/// across, so that ultra crucibles can reach the factory too.
+pub(super) fn generate(rng: &mut Rng, side: usize) -> String {
+    let city = Grid::from_fn((side.max(5), side.max(5)), |_| char::from(rng.range(b'1'..=b'9')));
+
+    super::grid_text(&city, |&c| c)
+}
@@ -0,0 +1,55 @@
+use crate::grid::Direction;
+
+use super::Rng;
+
+/// The largest length the five hex digits of a part 2 dig can hold.
+const MAX_HEX_LENGTH: usize = 0xfffff;
+
+/// A dig plan that traces the outline of a random blob clockwise. The blob's
+/// rows and columns are stretched to random sizes twice over: once for the
+/// plain lengths of part 1, and once, much larger, for the lengths hidden in
+/// the colours for part 2. Both plans have the same shape, so both close up
+/// without crossing themselves.
+pub(super) fn generate(rng: &mut Rng, side: usize) -> String {
+    let area = side * side;
+    let cells = rng.range(area / 3..=area / 2).max(1);
+    let blob = super::blob(rng, side, side, cells);
+    let outline = super::outline(&blob);
+
+    let mut stretch = |max: usize| -> Vec<usize> {
+        (0..=side).map(|_| rng.range(1..=max)).collect()
+    };
+    let (small_rows, small_cols) = (stretch(6), stretch(6));
+    let (large_rows, large_cols) = (stretch(MAX_HEX_LENGTH / side), stretch(MAX_HEX_LENGTH / side));
+
+    // Merge runs of steps in the same direction into single digs
+    let mut digs: Vec<(Direction, usize, usize)> = Vec::new();
+    for &(pos, direction) in &outline {
+        let (small, large) = match direction {
+            Direction::Up => (small_rows[pos.row - 1], large_rows[pos.row - 1]),
+            Direction::Down => (small_rows[pos.row], large_rows[pos.row]),
+            Direction::Left => (small_cols[pos.col - 1], large_cols[pos.col - 1]),
+            Direction::Right => (small_cols[pos.col], large_cols[pos.col]),
+        };
+        match digs.last_mut() {
+            Some(dig) if dig.0 == direction => {
+                dig.1 += small;
+                dig.2 += large;
+            },
+            _ => digs.push((direction, small, large)),
+        }
+    }
+
+    let mut text = String::new();
+    for (direction, small, large) in digs {
+        let (letter, digit) = match direction {
+            Direction::Right => ('R', 0),
+            Direction::Down => ('D', 1),
+            Direction::Left => ('L', 2),
+            Direction::Up => ('U', 3),
+        };
+        text.push_str(&format!("{} {} (#{:05x}{})\n", letter, small, large, digit));
+    }
+
+    text
+}
@@ -0,0 +1,55 @@
+use super::Rng;
+
+const LOWERCASE: &[u8] = b"abcdefghijklmnopqrstuvwxyz";
+
+/// Workflows that form a tree under `in`, as part 2 needs, with every rule
+/// that doesn't lead to another workflow accepting or rejecting. They're
+/// followed by twice as many parts as there are workflows.
+pub(super) fn generate(rng: &mut Rng, workflows: usize) -> String {
+    let mut names = vec!["in".to_owned()];
+    names.extend(super::names(rng, workflows - 1, &[LOWERCASE; 3], &[]));
+
+    // Every workflow has up to four comparisons and a fallback, and each new
+    // workflow takes the place of a random free target among those already made
+    let mut targets: Vec<Vec<Option<usize>>> = Vec::new();
+    let mut free = Vec::new();
+    for workflow in 0..workflows {
+        if workflow > 0 {
+            let (parent, rule): (usize, usize) = free.swap_remove(rng.index(free.len()));
+            targets[parent][rule] = Some(workflow);
+        }
+        let rules = rng.range(2..=5);
+        targets.push(vec![None; rules]);
+        free.extend((0..rules).map(|rule| (workflow, rule)));
+    }
+
+    let mut lines = Vec::new();
+    for (workflow, rules) in targets.iter().enumerate() {
+        let rules: Vec<_> = rules.iter().enumerate()
+            .map(|(i, target)| {
+                let target = match target {
+                    Some(workflow) => names[*workflow].clone(),
+                    None => if rng.chance(0.5) { "A" } else { "R" }.to_owned(),
+                };
+                if i + 1 == rules.len() {
+                    target
+                } else {
+                    let category = char::from(*rng.choose(b"xmas"));
+                    let comparison = if rng.chance(0.5) { '<' } else { '>' };
+                    format!("{}{}{}:{}", category, comparison, rng.range(1..=4000u32), target)
+                }
+            })
+            .collect();
+        lines.push(format!("{}{{{}}}", names[workflow], rules.join(",")));
+    }
+    rng.shuffle(&mut lines);
+
+    let mut text = lines.join("\n");
+    text.push_str("\n\n");
+    for _ in 0..2 * workflows {
+        let [x, m, a, s] = [(); 4].map(|_| rng.range(1..=4000u32));
+        text.push_str(&format!("{{x={},m={},a={},s={}}}\n", x, m, a, s));
+    }
+
+    text
+}
@@ -0,0 +1,23 @@
+use super::Rng;
+
+/// Games of one to six draws, each showing a shuffled subset of the colours.
+pub(super) fn generate(rng: &mut Rng, games: usize) -> String {
+    let mut text = String::new();
+
+    for id in 1..=games {
+        let draws: Vec<_> = (0..rng.range(1..=6))
+            .map(|_| {
+                let mut colors = ["red", "green", "blue"];
+                rng.shuffle(&mut colors);
+                let shown = rng.range(1..=3);
+                colors[..shown].iter()
+                    .map(|color| format!("{} {}", rng.range(1..=20u32), color))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            })
+            .collect();
+        text.push_str(&format!("Game {}: {}\n", id, draws.join("; ")));
+    }
+
+    text
+}
@@ -0,0 +1,84 @@
+use super::Rng;
+
+const LOWERCASE: &[u8] = b"abcdefghijklmnopqrstuvwxyz";
+
+/// The four-counter network part 2 assumes. The broadcaster starts four
+/// binary ripple counters, each a chain of flip-flops from the lowest bit up.
+/// A conjunction watches the bits that are set in the counter's target, a
+/// random prime, and when they're all on it sets the remaining bits and
+/// bumps the lowest one, which carries through and resets the counter to
+/// zero. It also signals an inverter, and the inverters of all four counters
+/// feed the conjunction in front of `rx`.
+pub(super) fn generate(rng: &mut Rng, bits: usize) -> String {
+    network(rng, bits).0
+}
+
+/// The network, along with the period of each counter.
+fn network(rng: &mut Rng, bits: usize) -> (String, Vec<usize>) {
+    let bits = bits.clamp(5, 20);
+    let mut names = super::names(rng, 4 * (bits + 2) + 1, &[LOWERCASE; 2], &["rx"]).into_iter();
+    let mut next_name = || names.next().unwrap();
+
+    let output = next_name();
+    let mut lines = vec![format!("&{} -> rx", output)];
+    let mut starts = Vec::new();
+    let mut periods = Vec::new();
+
+    for _ in 0..4 {
+        let period = loop {
+            let candidate = rng.range(1 << (bits - 1)..=(1 << bits) - 1);
+            if super::is_prime(candidate) && !periods.contains(&candidate) {
+                break candidate;
+            }
+        };
+        periods.push(period);
+
+        let flip_flops: Vec<_> = (0..bits).map(|_| next_name()).collect();
+        let conjunction = next_name();
+        let inverter = next_name();
+
+        let mut resets = vec![inverter.clone()];
+        for (bit, flip_flop) in flip_flops.iter().enumerate() {
+            let mut connections = Vec::new();
+            if let Some(next) = flip_flops.get(bit + 1) {
+                connections.push(next.clone());
+            }
+            if period & (1 << bit) != 0 {
+                connections.push(conjunction.clone());
+            }
+            if period & (1 << bit) == 0 || bit == 0 {
+                resets.push(flip_flop.clone());
+            }
+            rng.shuffle(&mut connections);
+            lines.push(format!("%{} -> {}", flip_flop, connections.join(", ")));
+        }
+        rng.shuffle(&mut resets);
+        lines.push(format!("&{} -> {}", conjunction, resets.join(", ")));
+        lines.push(format!("&{} -> {}", inverter, output));
+        starts.push(flip_flops[0].clone());
+    }
+
+    lines.push(format!("broadcaster -> {}", starts.join(", ")));
+    rng.shuffle(&mut lines);
+
+    (lines.join("\n") + "\n", periods)
+}
+
+#[cfg(test)]
+mod test {
+    use num::Integer;
+
+    use crate::registry;
+
+    #[test]
+    fn test_counter_periods() {
+        let mut rng = super::Rng::new(20);
+        for bits in [5, 8, 12] {
+            let (input, periods) = super::network(&mut rng, bits);
+            let expected = periods.iter().fold(1, |a, b| a.lcm(b));
+
+            let run = (registry::find(20, 2, None).unwrap().run)(&input).unwrap();
+            assert_eq!(run.answer, expected.to_string(), "{}", input);
+        }
+    }
+}
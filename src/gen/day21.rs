@@ -0,0 +1,42 @@
+use std::collections::VecDeque;
+
+use crate::grid::Grid;
+use crate::grid::Pos;
+
+use super::Rng;
+
+/// A square garden of odd side with the start in the middle, and rocks on
+/// about one plot in six. The edges and middle lines are kept clear for part
+/// 2, and plots walled off from the start are filled in, as in the real
+/// gardens.
+pub(super) fn generate(rng: &mut Rng, side: usize) -> String {
+    let side = side.max(3) | 1;
+    let middle = side / 2;
+    let clear = |n: usize| n == 0 || n == middle || n == side - 1;
+    let mut rocks = Grid::from_fn((side, side), |pos| {
+        !clear(pos.row) && !clear(pos.col) && rng.chance(1.0 / 6.0)
+    });
+
+    let start = Pos::new(middle, middle);
+    let mut reached = Grid::from_elem(rocks.bounds(), false);
+    reached[start] = true;
+    let mut frontier = VecDeque::from([start]);
+    while let Some(pos) = frontier.pop_front() {
+        for (_, neighbor) in rocks.neighbors(pos) {
+            if !rocks[neighbor] && !reached[neighbor] {
+                reached[neighbor] = true;
+                frontier.push_back(neighbor);
+            }
+        }
+    }
+    for pos in rocks.positions().collect::<Vec<_>>() {
+        rocks[pos] |= !reached[pos];
+    }
+
+    let garden = Grid::from_fn(rocks.bounds(), |pos| match (pos == start, rocks[pos]) {
+        (true, _) => 'S',
+        (false, true) => '#',
+        (false, false) => '.',
+    });
+    super::grid_text(&garden, |&c| c)
+}
@@ -0,0 +1,32 @@
+use std::collections::HashSet;
+
+use super::Rng;
+
+/// Straight bricks up to four cubes long over a ten by ten footprint, none
+/// of which overlap. They're spread out in height so that they have room to
+/// fall.
+pub(super) fn generate(rng: &mut Rng, bricks: usize) -> String {
+    let top = bricks / 5 + 10;
+    let mut filled = HashSet::new();
+    let mut text = String::new();
+    let mut placed = 0;
+
+    while placed < bricks {
+        let start = [rng.range(0..=9i64), rng.range(0..=9), rng.range(1..=top as i64)];
+        let axis = rng.index(3);
+        let mut end = start;
+        end[axis] += rng.range(0..=3);
+        if end[0] > 9 || end[1] > 9 { continue; }
+
+        let cubes: Vec<_> = (start[0]..=end[0])
+            .flat_map(|x| (start[1]..=end[1]).flat_map(move |y| (start[2]..=end[2]).map(move |z| (x, y, z))))
+            .collect();
+        if cubes.iter().any(|cube| filled.contains(cube)) { continue; }
+
+        filled.extend(cubes);
+        text.push_str(&format!("{},{},{}~{},{},{}\n", start[0], start[1], start[2], end[0], end[1], end[2]));
+        placed += 1;
+    }
+
+    text
+}
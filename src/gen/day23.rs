@@ -0,0 +1,112 @@
+use std::collections::HashMap;
+use std::collections::VecDeque;
+
+use crate::grid::Direction;
+use crate::grid::Grid;
+use crate::grid::Pos;
+
+use super::Rng;
+
+/// The most passages opened on top of the spanning tree. Each one adds a
+/// loop, and part 2's search grows exponentially with those.
+const MAX_LOOPS: usize = 24;
+
+/// A maze of single-width corridors between a square of cells, open at the
+/// top left and bottom right. It starts as a random spanning tree, and then
+/// some extra walls are knocked through to make loops.
+///
+/// Corridors between junctions get slopes at both ends, pointing from the
+/// junction nearer the start to the one further away. Every junction can
+/// then be reached downhill along a shortest path, so part 1 always has a
+/// path, and no walk downhill can come back to where it's been.
+pub(super) fn generate(rng: &mut Rng, cells: usize) -> String {
+    let cells = cells.max(2);
+    let side = 2 * cells + 1;
+    let mut maze = Grid::from_elem((side, side), '#');
+    let cell = |row: usize, col: usize| Pos::new(2 * row + 1, 2 * col + 1);
+    let between = |a: Pos, b: Pos| Pos::new((a.row + b.row) / 2, (a.col + b.col) / 2);
+
+    // Randomized depth-first search for the spanning tree
+    let mut stack = vec![cell(0, 0)];
+    maze[cell(0, 0)] = '.';
+    while let Some(&pos) = stack.last() {
+        let unvisited: Vec<_> = Direction::ALL.into_iter()
+            .filter_map(|direction| {
+                let wall = maze.try_move(pos, direction)?;
+                let next = maze.try_move(wall, direction)?;
+                (maze[next] == '#').then_some(next)
+            })
+            .collect();
+        if unvisited.is_empty() {
+            stack.pop();
+            continue;
+        }
+        let next = *rng.choose(&unvisited);
+        maze[between(pos, next)] = '.';
+        maze[next] = '.';
+        stack.push(next);
+    }
+
+    let walls: Vec<_> = maze.positions()
+        .filter(|pos| pos.row > 0 && pos.col > 0 && pos.row < side - 1 && pos.col < side - 1)
+        .filter(|pos| (pos.row + pos.col) % 2 == 1 && maze[*pos] == '#')
+        .collect();
+    for _ in 0..cells.min(MAX_LOOPS) {
+        maze[*rng.choose(&walls)] = '.';
+    }
+
+    let start = Pos::new(0, 1);
+    let end = Pos::new(side - 1, side - 2);
+    maze[start] = '.';
+    maze[end] = '.';
+
+    let distances = distances(&maze, start);
+    let open_neighbors = |maze: &Grid<char>, pos: Pos| maze.neighbors(pos).filter(|&(_, pos)| maze[pos] != '#').count();
+    let junctions: Vec<_> = maze.positions().filter(|&pos| maze[pos] != '#' && open_neighbors(&maze, pos) > 2).collect();
+
+    let mut slopes = Vec::new();
+    for &junction in &junctions {
+        for (direction, first) in maze.neighbors(junction) {
+            if maze[first] == '#' { continue; }
+
+            // Follow the corridor to whatever's at the other end
+            let (mut prev, mut pos, mut last_direction) = (junction, first, direction);
+            while open_neighbors(&maze, pos) == 2 {
+                let (direction, next) = maze.neighbors(pos).find(|&(_, next)| next != prev && maze[next] != '#').unwrap();
+                (prev, pos, last_direction) = (pos, next, direction);
+            }
+            if !junctions.contains(&pos) { continue; }
+
+            let key = |pos: Pos| (distances[&pos], pos);
+            if key(junction) < key(pos) {
+                slopes.push((first, direction));
+                slopes.push((prev, last_direction));
+            }
+        }
+    }
+    for (pos, direction) in slopes {
+        maze[pos] = match direction {
+            Direction::Up => '^',
+            Direction::Right => '>',
+            Direction::Down => 'v',
+            Direction::Left => '<',
+        };
+    }
+
+    super::grid_text(&maze, |&c| c)
+}
+
+/// Steps from `start` to every open tile.
+fn distances(maze: &Grid<char>, start: Pos) -> HashMap<Pos, usize> {
+    let mut distances = HashMap::from([(start, 0)]);
+    let mut frontier = VecDeque::from([start]);
+    while let Some(pos) = frontier.pop_front() {
+        for (_, next) in maze.neighbors(pos) {
+            if maze[next] != '#' && !distances.contains_key(&next) {
+                distances.insert(next, distances[&pos] + 1);
+                frontier.push_back(next);
+            }
+        }
+    }
+    distances
+}
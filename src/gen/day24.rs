@@ -0,0 +1,29 @@
+use super::Rng;
+
+/// Hailstones that a single rock, thrown from an integer position at an
+/// integer velocity, hits one by one at distinct integer times. Positions
+/// are in the hundreds of trillions and velocities in the hundreds, like the
+/// real ones.
+pub(super) fn generate(rng: &mut Rng, hailstones: usize) -> String {
+    let rock_position = [(); 3].map(|_| rng.range(200_000_000_000_000..=400_000_000_000_000i64));
+    let rock_velocity = [(); 3].map(|_| rng.range(-300..=300i64));
+
+    let mut times = Vec::new();
+    let mut text = String::new();
+    while times.len() < hailstones {
+        let time = rng.range(10_000_000_000..=1_000_000_000_000i64);
+        let velocity = [(); 3].map(|_| rng.range(-300..=300i64));
+        if times.contains(&time) || velocity == rock_velocity { continue; }
+
+        let position: [i64; 3] = std::array::from_fn(|i| rock_position[i] + time * (rock_velocity[i] - velocity[i]));
+        if position.iter().any(|p| !(0..=600_000_000_000_000).contains(p)) { continue; }
+
+        times.push(time);
+        text.push_str(&format!(
+            "{}, {}, {} @ {}, {}, {}\n",
+            position[0], position[1], position[2], velocity[0], velocity[1], velocity[2],
+        ));
+    }
+
+    text
+}
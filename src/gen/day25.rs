@@ -0,0 +1,58 @@
+use std::collections::HashSet;
+
+use super::Rng;
+
+const LOWERCASE: &[u8] = b"abcdefghijklmnopqrstuvwxyz";
+
+/// Two tangles of components joined by exactly three wires. Each tangle is a
+/// ring of at least five components where every component is also wired to
+/// the one two along, which takes at least four cuts to split, plus some
+/// random extra wires.
+pub(super) fn generate(rng: &mut Rng, components: usize) -> String {
+    let components = components.max(10);
+    let names = super::names(rng, components, &[LOWERCASE; 3], &[]);
+    let split = rng.range((components * 2 / 5).max(5)..=(components * 3 / 5).min(components - 5));
+    let tangles = [(0, split), (split, components)];
+
+    let mut wires = HashSet::new();
+    for (start, end) in tangles {
+        let len = end - start;
+        for i in 0..len {
+            wires.insert((start + i, start + (i + 1) % len));
+            wires.insert((start + i, start + (i + 2) % len));
+        }
+        for _ in 0..len / 2 {
+            wires.insert((rng.range(start..=end - 1), rng.range(start..=end - 1)));
+        }
+    }
+    let mut left: Vec<_> = (0..split).collect();
+    let mut right: Vec<_> = (split..components).collect();
+    rng.shuffle(&mut left);
+    rng.shuffle(&mut right);
+    wires.extend(left.into_iter().zip(right).take(3));
+
+    let mut listed: Vec<Vec<usize>> = vec![Vec::new(); components];
+    let mut linked = HashSet::new();
+    let mut wires: Vec<_> = wires.into_iter().filter(|&(a, b)| a != b).collect();
+    wires.sort();
+    rng.shuffle(&mut wires);
+    for (a, b) in wires {
+        if !linked.insert((a.min(b), a.max(b))) { continue; }
+        if rng.chance(0.5) {
+            listed[a].push(b);
+        } else {
+            listed[b].push(a);
+        }
+    }
+
+    let mut lines: Vec<_> = listed.iter().enumerate()
+        .filter(|(_, others)| !others.is_empty())
+        .map(|(i, others)| {
+            let others: Vec<_> = others.iter().map(|&other| names[other].as_str()).collect();
+            format!("{}: {}", names[i], others.join(" "))
+        })
+        .collect();
+    rng.shuffle(&mut lines);
+
+    lines.join("\n") + "\n"
+}
@@ -0,0 +1,43 @@
+use crate::grid::Grid;
+use crate::grid::Pos;
+
+use super::Rng;
+
+const SYMBOLS: &[u8] = b"*#+$/@%=&-";
+
+/// A square schematic of part numbers and symbols. Symbols that would touch
+/// more than two numbers are left out, since part 2 can't pair those up.
+pub(super) fn generate(rng: &mut Rng, side: usize) -> String {
+    let side = side.max(3);
+    let mut schematic = Grid::from_elem((side, side), '.');
+    // Which number, if any, each cell belongs to
+    let mut numbers = Grid::from_elem((side, side), None);
+    let mut count = 0;
+
+    for row in 0..side {
+        let mut col = rng.range(0..=3);
+        while col < side {
+            let digits = rng.range(1..=3).min(side - col);
+            let value = rng.range(10usize.pow(digits as u32 - 1)..=10usize.pow(digits as u32) - 1);
+            for (i, c) in value.to_string().chars().enumerate() {
+                schematic[Pos::new(row, col + i)] = c;
+                numbers[Pos::new(row, col + i)] = Some(count);
+            }
+            count += 1;
+            col += digits + rng.range(1..=8);
+        }
+    }
+
+    for pos in schematic.positions().collect::<Vec<_>>() {
+        if schematic[pos] != '.' || !rng.chance(0.08) { continue; }
+
+        let mut touching: Vec<_> = schematic.neighbors8(pos).filter_map(|neighbor| numbers[neighbor]).collect();
+        touching.sort();
+        touching.dedup();
+        if touching.len() <= 2 {
+            schematic[pos] = char::from(*rng.choose(SYMBOLS));
+        }
+    }
+
+    super::grid_text(&schematic, |&c| c)
+}
@@ -0,0 +1,38 @@
+use std::collections::VecDeque;
+
+use super::Rng;
+
+/// Cards with more copies than this win nothing, so that no card ends up
+/// with more than about ten times as many.
+const MAX_WINNING_COPIES: usize = 1_000_000;
+
+/// Scratchcards with ten winning numbers and 25 numbers you have. No card
+/// wins copies of cards past the end of the table, and the copy counts stay
+/// far from overflowing however many cards there are.
+pub(super) fn generate(rng: &mut Rng, cards: usize) -> String {
+    let mut text = String::new();
+    let mut extra_copies = VecDeque::new();
+
+    for card in 0..cards {
+        let mut pool: Vec<u32> = (1..=99).collect();
+        rng.shuffle(&mut pool);
+        let (winners, others) = pool.split_at(10);
+
+        let copies = extra_copies.pop_front().unwrap_or(0) + 1;
+        let matches = if copies > MAX_WINNING_COPIES { 0 } else { rng.range(0..=10).min(cards - card - 1) };
+        if extra_copies.len() < matches {
+            extra_copies.resize(matches, 0);
+        }
+        for extra in extra_copies.iter_mut().take(matches) {
+            *extra += copies;
+        }
+
+        let mut haves: Vec<_> = winners[..matches].iter().chain(&others[..25 - matches]).copied().collect();
+        rng.shuffle(&mut haves);
+
+        let numbers = |numbers: &[u32]| numbers.iter().map(|n| format!("{:>2}", n)).collect::<Vec<_>>().join(" ");
+        text.push_str(&format!("Card {:>3}: {} | {}\n", card + 1, numbers(winners), numbers(&haves)));
+    }
+
+    text
+}
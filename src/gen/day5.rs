@@ -0,0 +1,50 @@
+use super::Rng;
+
+const TYPES: [&str; 8] = ["seed", "soil", "fertilizer", "water", "light", "temperature", "humidity", "location"];
+
+/// The largest value any seed or map reaches, as in the real almanacs.
+const LIMIT: u64 = 1 << 32;
+
+/// An almanac with five seed ranges and the usual chain of seven maps. Each
+/// map cuts part of `0..LIMIT` into `ranges` pieces and shuffles them.
+pub(super) fn generate(rng: &mut Rng, ranges: usize) -> String {
+    let mut text = String::from("seeds:");
+
+    for _ in 0..5 {
+        let length = rng.range(1..=LIMIT / 16);
+        let start = rng.range(0..=LIMIT - length);
+        text.push_str(&format!(" {} {}", start, length));
+    }
+    text.push('\n');
+
+    for types in TYPES.windows(2) {
+        // The first and last cut leave some values unmapped at either end
+        let mut cuts = Vec::new();
+        while cuts.len() < 2 {
+            cuts = (0..=ranges).map(|_| rng.range(0..=LIMIT)).collect();
+            cuts.sort();
+            cuts.dedup();
+        }
+
+        let pieces: Vec<_> = cuts.windows(2).map(|cut| (cut[0], cut[1] - cut[0])).collect();
+        let mut order: Vec<_> = (0..pieces.len()).collect();
+        rng.shuffle(&mut order);
+
+        let mut lines = Vec::new();
+        let mut dest = cuts[0];
+        for i in order {
+            let (source, length) = pieces[i];
+            lines.push(format!("{} {} {}", dest, source, length));
+            dest += length;
+        }
+        rng.shuffle(&mut lines);
+
+        text.push_str(&format!("\n{}-to-{} map:\n", types[0], types[1]));
+        for line in lines {
+            text.push_str(&line);
+            text.push('\n');
+        }
+    }
+
+    text
+}
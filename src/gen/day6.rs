@@ -0,0 +1,23 @@
+use super::Rng;
+
+/// Up to four races, each of which can be won. Joined up for part 2, the
+/// times and records still make a winnable race, and the answers fit in the
+/// types the solver uses.
+pub(super) fn generate(rng: &mut Rng, races: usize) -> String {
+    let races = races.clamp(1, 4);
+
+    loop {
+        let times: Vec<u64> = (0..races).map(|_| rng.range(7..=99)).collect();
+        let records: Vec<u64> = times.iter().map(|&time| rng.range(0..=(time / 2) * (time - time / 2) - 1)).collect();
+
+        let join = |values: &[u64]| values.iter().map(u64::to_string).collect::<String>().parse::<u64>().unwrap();
+        let (time, record) = (join(&times), join(&records));
+        if record >= (time / 2) * (time - time / 2) {
+            continue;
+        }
+
+        let widths: Vec<_> = times.iter().zip(&records).map(|(time, record)| record.to_string().len().max(time.to_string().len())).collect();
+        let row = |values: &[u64]| values.iter().zip(&widths).map(|(value, width)| format!("  {:>width$}", value, width = width)).collect::<String>();
+        return format!("Time:    {}\nDistance:{}\n", row(&times), row(&records));
+    }
+}
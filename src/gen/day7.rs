@@ -0,0 +1,21 @@
+use std::collections::HashSet;
+
+use super::Rng;
+
+const CARDS: &[u8] = b"AKQJT98765432";
+
+/// Distinct random hands with bids up to 1000.
+pub(super) fn generate(rng: &mut Rng, hands: usize) -> String {
+    let hands = hands.min(CARDS.len().pow(5));
+    let mut seen = HashSet::new();
+    let mut text = String::new();
+
+    while seen.len() < hands {
+        let hand: String = (0..5).map(|_| char::from(*rng.choose(CARDS))).collect();
+        if seen.insert(hand.clone()) {
+            text.push_str(&format!("{} {}\n", hand, rng.range(1..=1000u32)));
+        }
+    }
+
+    text
+}
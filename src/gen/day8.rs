@@ -0,0 +1,54 @@
+use super::Rng;
+
+/// Loop lengths for the ghosts, in units of the instruction count.
+const ROUNDS: [usize; 8] = [3, 5, 7, 11, 13, 17, 19, 23];
+
+const LABEL_START: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZ0123456789";
+const LABEL_END: &[u8] = b"BCDEFGHIJKLMNOPQRSTUVWXY0123456789";
+
+/// Six ghosts, each on its own loop of nodes, with `AAA` and `ZZZ` on the
+/// first. A ghost's loop is as long as its instructions times a small prime,
+/// and the only `Z` node on it is the one reached at the end of the loop. Its
+/// `A` node leads where the `Z` node does, so the steps to the first goal are
+/// exactly the loop length, as part 2 assumes.
+///
+/// Every node is only visited at one point in the instructions, so the turn
+/// not taken can go anywhere on the same loop.
+pub(super) fn generate(rng: &mut Rng, instructions: usize) -> String {
+    let length = (instructions.clamp(2, 400)..).find(|&n| super::is_prime(n)).unwrap();
+    let directions: Vec<bool> = (0..length).map(|_| rng.chance(0.5)).collect();
+
+    let mut rounds = ROUNDS;
+    rng.shuffle(&mut rounds);
+    let rounds = &rounds[..6];
+
+    let loop_nodes: usize = rounds.iter().map(|rounds| rounds * length).sum();
+    let mut labels = super::names(rng, loop_nodes, &[LABEL_START, LABEL_START, LABEL_END], &[]).into_iter();
+    let mut prefixes = super::names(rng, 6, &[LABEL_START, LABEL_START], &["AA", "ZZ"]);
+    prefixes[0] = "AA".to_owned();
+
+    let mut lines = Vec::new();
+    for (ghost, &rounds) in rounds.iter().enumerate() {
+        let prefix = &prefixes[ghost];
+        let goal = if ghost == 0 { "ZZZ".to_owned() } else { format!("{}Z", prefix) };
+        let start = if ghost == 0 { "AAA".to_owned() } else { format!("{}A", prefix) };
+
+        // Node i is left at step i of the loop, and the goal closes it
+        let mut nodes = vec![goal];
+        nodes.extend(labels.by_ref().take(rounds * length - 1));
+
+        for (i, node) in nodes.iter().enumerate() {
+            let next = &nodes[(i + 1) % nodes.len()];
+            let other = rng.choose(&nodes);
+            let (left, right) = if directions[i % length] { (other, next) } else { (next, other) };
+            lines.push(format!("{} = ({}, {})", node, left, right));
+            if i == 0 {
+                lines.push(format!("{} = ({}, {})", start, left, right));
+            }
+        }
+    }
+    rng.shuffle(&mut lines);
+
+    let directions: String = directions.iter().map(|&right| if right { 'R' } else { 'L' }).collect();
+    format!("{}\n\n{}\n", directions, lines.join("\n"))
+}
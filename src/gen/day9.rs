@@ -0,0 +1,27 @@
+use super::Rng;
+
+/// Sequences of 21 values of random polynomials, written in the Newton form
+/// `c_0 + c_1 * C(n, 1) + ... + c_d * C(n, d)` so that the differences bottom
+/// out at zero after `d + 1` rounds.
+pub(super) fn generate(rng: &mut Rng, sequences: usize) -> String {
+    let mut text = String::new();
+
+    for _ in 0..sequences {
+        let coefficients: Vec<i64> = (0..=rng.range(0..=7)).map(|_| rng.range(-10..=10)).collect();
+        let values: Vec<_> = (0..21)
+            .map(|n| {
+                let mut binomial = 1;
+                let mut value = 0;
+                for (k, coefficient) in coefficients.iter().enumerate() {
+                    value += coefficient * binomial;
+                    binomial = binomial * (n - k as i64) / (k as i64 + 1);
+                }
+                value.to_string()
+            })
+            .collect();
+        text.push_str(&values.join(" "));
+        text.push('\n');
+    }
+
+    text
+}
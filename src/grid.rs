@@ -0,0 +1,318 @@
+use anyhow::Result;
+use anyhow::anyhow;
+
+use ndarray::Array2;
+
+/// A cell position, in (row, col) order, with row 0 at the top.
+#[derive(Debug, Clone, Copy)]
+#[derive(PartialEq, Eq)]
+#[derive(PartialOrd, Ord)]
+#[derive(Hash)]
+pub struct Pos {
+    pub row: usize,
+    pub col: usize,
+}
+
+impl Pos {
+    pub const fn new(row: usize, col: usize) -> Self {
+        Pos { row, col }
+    }
+
+    /// Moves one step in `direction`, or returns `None` if that would leave
+    /// a grid of `bounds` = (rows, cols).
+    pub fn try_move(self, direction: Direction, bounds: (usize, usize)) -> Option<Pos> {
+        self.try_offset(direction.offset(), bounds)
+    }
+
+    pub fn try_offset(self, (d_row, d_col): (isize, isize), bounds: (usize, usize)) -> Option<Pos> {
+        let row = self.row.checked_add_signed(d_row)?;
+        let col = self.col.checked_add_signed(d_col)?;
+
+        if row < bounds.0 && col < bounds.1 {
+            Some(Pos { row, col })
+        } else {
+            None
+        }
+    }
+
+    pub fn manhattan_distance(self, other: Pos) -> usize {
+        self.row.abs_diff(other.row) + self.col.abs_diff(other.col)
+    }
+
+    /// The 4-neighbourhood of this position within `bounds`, with the direction taken to reach each one.
+    pub fn neighbors(self, bounds: (usize, usize)) -> impl Iterator<Item = (Direction, Pos)> {
+        Direction::ALL.into_iter()
+            .filter_map(move |direction| {
+                self.try_move(direction, bounds).map(|neighbor| (direction, neighbor))
+            })
+    }
+
+    /// The 8-neighbourhood of this position within `bounds`.
+    pub fn neighbors8(self, bounds: (usize, usize)) -> impl Iterator<Item = Pos> {
+        const OFFSETS: [(isize, isize); 8] = [
+            (-1, -1), (-1, 0), (-1, 1),
+            (0, -1), (0, 1),
+            (1, -1), (1, 0), (1, 1),
+        ];
+
+        OFFSETS.into_iter()
+            .filter_map(move |offset| self.try_offset(offset, bounds))
+    }
+}
+
+impl From<(usize, usize)> for Pos {
+    fn from((row, col): (usize, usize)) -> Self {
+        Pos { row, col }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+#[derive(PartialEq, Eq)]
+#[derive(PartialOrd, Ord)]
+#[derive(Hash)]
+pub enum Direction {
+    Up,
+    Right,
+    Down,
+    Left,
+}
+
+impl Direction {
+    /// All four directions, clockwise from `Up`.
+    pub const ALL: [Direction; 4] = [
+        Direction::Up,
+        Direction::Right,
+        Direction::Down,
+        Direction::Left,
+    ];
+
+    pub fn invert(self) -> Self {
+        use Direction::*;
+        match self {
+            Up => Down,
+            Down => Up,
+            Right => Left,
+            Left => Right,
+        }
+    }
+
+    pub fn turn_right(self) -> Self {
+        use Direction::*;
+        match self {
+            Up => Right,
+            Right => Down,
+            Down => Left,
+            Left => Up,
+        }
+    }
+
+    pub fn turn_left(self) -> Self {
+        self.turn_right().invert()
+    }
+
+    /// Position of this direction in `Direction::ALL`, for use as an array index.
+    pub fn index(self) -> usize {
+        self as usize
+    }
+
+    /// The (row, col) delta of a single step.
+    pub fn offset(self) -> (isize, isize) {
+        use Direction::*;
+        match self {
+            Up => (-1, 0),
+            Right => (0, 1),
+            Down => (1, 0),
+            Left => (0, -1),
+        }
+    }
+}
+
+/// A rectangular grid of cells addressed by `Pos`.
+#[derive(Debug, Clone)]
+#[derive(PartialEq, Eq)]
+#[derive(Hash)]
+pub struct Grid<T> {
+    cells: Array2<T>,
+}
+
+impl<T> Grid<T> {
+    pub fn new(cells: Array2<T>) -> Self {
+        Grid { cells }
+    }
+
+    pub fn from_elem(bounds: (usize, usize), elem: T) -> Self
+    where
+        T: Clone,
+    {
+        Grid { cells: Array2::from_elem(bounds, elem) }
+    }
+
+    pub fn from_fn(bounds: (usize, usize), mut f: impl FnMut(Pos) -> T) -> Self {
+        Grid { cells: Array2::from_shape_fn(bounds, |index| f(index.into())) }
+    }
+
+    /// Parses a character map, one row per line. Empty lines are skipped, and
+    /// every row must have the same width.
+    pub fn parse(input: &str, mut f: impl FnMut(char) -> Result<T>) -> Result<Self> {
+        Self::parse_indexed(input, |_, c| f(c))
+    }
+
+    /// Like `parse`, but also passes the position of each character.
+    pub fn parse_indexed(input: &str, mut f: impl FnMut(Pos, char) -> Result<T>) -> Result<Self> {
+        let mut data = Vec::new();
+        let mut rows = 0;
+        let mut cols = None;
+
+        for (line_number, line) in input.lines().enumerate().filter(|(_, line)| !line.is_empty()) {
+            let mut width = 0;
+            for (col, c) in line.chars().enumerate() {
+                let cell = f(Pos { row: rows, col }, c)
+                    .map_err(|err| anyhow!("line {}, column {}: {}", line_number + 1, col + 1, err))?;
+                data.push(cell);
+                width += 1;
+            }
+
+            match cols {
+                None => cols = Some(width),
+                Some(cols) if cols != width => {
+                    return Err(anyhow!("line {}: expected {} columns, found {}", line_number + 1, cols, width));
+                },
+                Some(_) => { },
+            }
+            rows += 1;
+        }
+
+        let cols = cols.ok_or_else(|| anyhow!("Grid input was empty"))?;
+
+        Ok(Grid { cells: Array2::from_shape_vec((rows, cols), data)? })
+    }
+
+    pub fn rows(&self) -> usize {
+        self.cells.nrows()
+    }
+
+    pub fn cols(&self) -> usize {
+        self.cells.ncols()
+    }
+
+    /// (rows, cols), as accepted by `Pos::try_move`.
+    pub fn bounds(&self) -> (usize, usize) {
+        self.cells.dim()
+    }
+
+    pub fn contains(&self, pos: Pos) -> bool {
+        pos.row < self.rows() && pos.col < self.cols()
+    }
+
+    pub fn get(&self, pos: Pos) -> Option<&T> {
+        self.cells.get((pos.row, pos.col))
+    }
+
+    pub fn get_mut(&mut self, pos: Pos) -> Option<&mut T> {
+        self.cells.get_mut((pos.row, pos.col))
+    }
+
+    pub fn try_move(&self, pos: Pos, direction: Direction) -> Option<Pos> {
+        pos.try_move(direction, self.bounds())
+    }
+
+    pub fn neighbors(&self, pos: Pos) -> impl Iterator<Item = (Direction, Pos)> {
+        pos.neighbors(self.bounds())
+    }
+
+    pub fn neighbors8(&self, pos: Pos) -> impl Iterator<Item = Pos> {
+        pos.neighbors8(self.bounds())
+    }
+
+    pub fn positions(&self) -> impl Iterator<Item = Pos> {
+        let cols = self.cols();
+        (0..self.rows()).flat_map(move |row| (0..cols).map(move |col| Pos { row, col }))
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &T> {
+        self.cells.iter()
+    }
+
+    /// Cells in row-major order, with their positions.
+    pub fn indexed_iter(&self) -> impl Iterator<Item = (Pos, &T)> {
+        self.cells.indexed_iter().map(|(index, value)| (index.into(), value))
+    }
+
+    pub fn map<U>(&self, f: impl FnMut(&T) -> U) -> Grid<U> {
+        Grid { cells: self.cells.map(f) }
+    }
+
+    pub fn as_array(&self) -> &Array2<T> {
+        &self.cells
+    }
+
+    pub fn into_array(self) -> Array2<T> {
+        self.cells
+    }
+}
+
+impl<T> From<Array2<T>> for Grid<T> {
+    fn from(cells: Array2<T>) -> Self {
+        Grid { cells }
+    }
+}
+
+impl<T> std::ops::Index<Pos> for Grid<T> {
+    type Output = T;
+
+    fn index(&self, index: Pos) -> &Self::Output {
+        &self.cells[(index.row, index.col)]
+    }
+}
+
+impl<T> std::ops::IndexMut<Pos> for Grid<T> {
+    fn index_mut(&mut self, index: Pos) -> &mut Self::Output {
+        &mut self.cells[(index.row, index.col)]
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::Direction;
+    use super::Grid;
+    use super::Pos;
+
+    #[test]
+    fn test_moves() {
+        let bounds = (3, 4);
+        let corner = Pos::new(0, 0);
+        assert_eq!(corner.try_move(Direction::Up, bounds), None);
+        assert_eq!(corner.try_move(Direction::Left, bounds), None);
+        assert_eq!(corner.try_move(Direction::Right, bounds), Some(Pos::new(0, 1)));
+        assert_eq!(corner.try_move(Direction::Down, bounds), Some(Pos::new(1, 0)));
+
+        let far_corner = Pos::new(2, 3);
+        assert_eq!(far_corner.try_move(Direction::Down, bounds), None);
+        assert_eq!(far_corner.try_move(Direction::Right, bounds), None);
+
+        assert_eq!(corner.neighbors(bounds).count(), 2);
+        assert_eq!(corner.neighbors8(bounds).count(), 3);
+        assert_eq!(Pos::new(1, 1).neighbors8(bounds).count(), 8);
+    }
+
+    #[test]
+    fn test_turns() {
+        for direction in Direction::ALL {
+            assert_eq!(direction.turn_left().turn_right(), direction);
+            assert_eq!(direction.turn_right().turn_right(), direction.invert());
+            assert_eq!(Direction::ALL[direction.index()], direction);
+        }
+    }
+
+    #[test]
+    fn test_parse() {
+        let grid = Grid::parse_indexed("\n#.\n.#\n..\n", |_, c| Ok(c == '#')).unwrap();
+
+        assert_eq!(grid.bounds(), (3, 2));
+        assert!(grid[Pos::new(0, 0)]);
+        assert!(grid[Pos::new(1, 1)]);
+        assert!(!grid[Pos::new(2, 1)]);
+
+        assert!(Grid::parse("#.\n#\n", |c| Ok(c == '#')).is_err());
+    }
+}
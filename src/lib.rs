@@ -1,29 +1,186 @@
+//! Solutions to Advent of Code 2023.
+//!
+//! [`solve`] runs the main solution for any day and part on a puzzle input.
+//! Each `dayN` module also exposes its parsed input types along with the
+//! `input_generator` that parses them and the `solve_part1` and `solve_part2`
+//! functions that solve them, for callers that want to work with the parsed
+//! input directly. Part 2 of days 17 and 18 lives in `day17_2` and `day18_2`.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
+
+#[cfg(feature = "std")]
+use std::fmt;
+#[cfg(feature = "std")]
+use std::str::FromStr;
+
+#[cfg(feature = "std")]
 use aoc_runner_derive::aoc_lib;
+#[cfg(feature = "std")]
+use thiserror::Error;
+
+pub mod algorithms;
+#[cfg(feature = "std")]
+pub mod answers;
+#[cfg(feature = "std")]
+pub mod cycle;
+#[cfg(feature = "std")]
+pub mod gen;
+#[cfg(feature = "std")]
+pub mod grid;
+#[cfg(feature = "std")]
+pub mod par;
+#[cfg(feature = "std")]
+pub mod parse;
+#[cfg(feature = "python")]
+mod python;
+#[cfg(feature = "std")]
+pub mod ranges;
+#[cfg(feature = "std")]
+pub mod registry;
+#[cfg(feature = "std")]
+pub mod render;
+#[cfg(feature = "std")]
+pub mod search;
+#[cfg(feature = "wasm")]
+pub mod wasm;
+
+#[cfg(feature = "std")]
+pub mod day1;
+#[cfg(feature = "std")]
+pub mod day2;
+#[cfg(feature = "std")]
+pub mod day3;
+#[cfg(feature = "std")]
+pub mod day4;
+#[cfg(feature = "std")]
+pub mod day5;
+#[cfg(feature = "std")]
+pub mod day6;
+#[cfg(feature = "std")]
+pub mod day7;
+#[cfg(feature = "std")]
+pub mod day8;
+#[cfg(feature = "std")]
+pub mod day9;
+#[cfg(feature = "std")]
+pub mod day10;
+#[cfg(feature = "std")]
+pub mod day11;
+#[cfg(feature = "std")]
+pub mod day12;
+#[cfg(feature = "std")]
+pub mod day13;
+#[cfg(feature = "std")]
+pub mod day14;
+#[cfg(feature = "std")]
+pub mod day15;
+#[cfg(feature = "std")]
+pub mod day16;
+#[cfg(feature = "std")]
+pub mod day17;
+#[cfg(feature = "std")]
+pub mod day17_2;
+#[cfg(feature = "std")]
+pub mod day18;
+#[cfg(feature = "std")]
+pub mod day18_2;
+#[cfg(feature = "std")]
+pub mod day19;
+#[cfg(feature = "std")]
+pub mod day20;
+#[cfg(feature = "std")]
+pub mod day21;
+#[cfg(feature = "std")]
+pub mod day22;
+#[cfg(feature = "std")]
+pub mod day23;
+#[cfg(feature = "std")]
+pub mod day24;
+#[cfg(feature = "std")]
+pub mod day25;
+
+/// The answer to one part of a puzzle, as the puzzle would want it entered.
+#[cfg(feature = "std")]
+#[derive(Debug, Clone)]
+#[derive(PartialEq, Eq)]
+#[derive(Hash)]
+pub struct Answer(String);
+
+#[cfg(feature = "std")]
+impl Answer {
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// The answer as a number, or whatever other type it parses as.
+    pub fn parse<T: FromStr>(&self) -> Result<T, T::Err> {
+        self.0.parse()
+    }
+}
+
+#[cfg(feature = "std")]
+impl fmt::Display for Answer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+#[cfg(feature = "std")]
+impl From<Answer> for String {
+    fn from(answer: Answer) -> String {
+        answer.0
+    }
+}
+
+#[cfg(feature = "std")]
+#[derive(Debug, Error)]
+pub enum Error {
+    #[error("No solution for day {day} part {part}")]
+    UnknownPuzzle { day: u8, part: u8 },
+    #[error(transparent)]
+    Run(#[from] registry::RunError),
+}
+
+#[cfg(feature = "std")]
+pub type Result<T, E = Error> = std::result::Result<T, E>;
+
+/// Solves `part` of `day` for `input` with its main solution.
+#[cfg(feature = "std")]
+pub fn solve(day: u8, part: u8, input: &str) -> Result<Answer> {
+    let solution = registry::find(day.into(), part.into(), None)
+        .ok_or(Error::UnknownPuzzle { day, part })?;
+    let run = (solution.run)(input)?;
+    Ok(Answer(run.answer))
+}
+
+#[cfg(feature = "std")]
+aoc_lib!{ year = 2023 }
+
+#[cfg(all(test, feature = "std"))]
+mod test {
+    const DAY9_INPUT: &str = "0 3 6 9 12 15\n1 3 6 10 15 21\n10 13 16 21 30 45\n";
+
+    #[test]
+    fn test_solve() {
+        let answer = super::solve(9, 1, DAY9_INPUT).unwrap();
+        assert_eq!(answer.as_str(), "114");
+        assert_eq!(answer.parse::<i64>().unwrap(), 114);
+
+        assert!(matches!(super::solve(26, 1, ""), Err(super::Error::UnknownPuzzle { day: 26, part: 1 })));
+        assert!(matches!(super::solve(9, 1, "0 3 x\n"), Err(super::Error::Run(super::registry::RunError::Parse(_)))));
+    }
+
+    #[test]
+    fn test_parsed_input() {
+        use super::day19;
+
+        let input = day19::input_generator("in{x<10:A,R}\n\n{x=1,m=2,a=3,s=4}\n").unwrap();
+        let workflow = &input.0[&day19::WorkflowName("in".to_owned())];
+        assert_eq!(workflow.rules.len(), 2);
+        assert_eq!(input.1[0].x, 1);
 
-mod day1;
-mod day2;
-mod day3;
-mod day4;
-mod day5;
-mod day6;
-mod day7;
-mod day8;
-mod day9;
-mod day10;
-mod day11;
-mod day12;
-mod day13;
-mod day14;
-mod day15;
-mod day16;
-mod day17;
-mod day17_2;
-mod day18;
-mod day18_2;
-mod day19;
-mod day20;
-mod day21;
-mod day22;
-mod day23;
-
-aoc_lib!{ year = 2023 }
\ No newline at end of file
+        assert_eq!(day19::solve_part1(&input).unwrap(), 10);
+    }
+}
@@ -0,0 +1,51 @@
+//! Runs independent pieces of work, spread across threads when the `parallel`
+//! feature is on and one after another otherwise. Results always come back in
+//! the order of the inputs, so answers never depend on the feature or on
+//! thread scheduling.
+
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+
+/// `f` applied to each of `items`, in order.
+#[cfg(feature = "parallel")]
+pub fn map<T: Send, R: Send>(items: Vec<T>, f: impl Fn(T) -> R + Sync + Send) -> Vec<R> {
+    items.into_par_iter().map(f).collect()
+}
+
+/// `f` applied to each of `items`, in order.
+#[cfg(not(feature = "parallel"))]
+pub fn map<T: Send, R: Send>(items: Vec<T>, f: impl Fn(T) -> R + Sync + Send) -> Vec<R> {
+    items.into_iter().map(f).collect()
+}
+
+#[cfg(test)]
+mod test {
+    use crate::gen;
+    use crate::registry;
+
+    /// Answers for generated inputs to every day that uses `map`, recorded
+    /// without the `parallel` feature. `cargo test --features parallel`
+    /// checks that the threaded versions agree.
+    const EXPECTED: &[(u32, u64, usize, &str)] = &[
+        (12, 1, 200, "454137669720"),
+        (16, 1, 40, "347"),
+        (21, 1, 21, "606768334561202"),
+        (22, 1, 300, "1140"),
+    ];
+
+    #[test]
+    fn test_same_answers_with_and_without_threads() {
+        for &(day, seed, size, expected) in EXPECTED {
+            let input = gen::find(day).unwrap().run(seed, size);
+            let run = (registry::find(day, 2, None).unwrap().run)(&input).unwrap();
+            assert_eq!(run.answer, expected, "day {}", day);
+        }
+    }
+
+    #[test]
+    fn test_order() {
+        let items: Vec<u64> = (0..1000).collect();
+        let squares = super::map(items, |x| x * x);
+        assert!(squares.iter().enumerate().all(|(i, &square)| square == (i * i) as u64));
+    }
+}
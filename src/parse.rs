@@ -0,0 +1,236 @@
+use std::io::BufRead;
+
+use anyhow::Context;
+use anyhow::Result;
+use anyhow::anyhow;
+
+use ndarray::Array2;
+
+use nom::bytes::complete::tag;
+use nom::character::complete::digit1;
+use nom::character::complete::one_of;
+use nom::character::complete::space0;
+use nom::character::complete::space1;
+use nom::combinator::map_res;
+use nom::combinator::opt;
+use nom::combinator::recognize;
+use nom::error::ErrorKind;
+use nom::error::VerboseError;
+use nom::error::VerboseErrorKind;
+use nom::multi::many1;
+use nom::multi::separated_list1;
+use nom::sequence::pair;
+use nom::sequence::tuple;
+use nom::Parser;
+
+pub type IResult<I, T> = nom::IResult<I, T, VerboseError<I>>;
+
+/// Runs `parser` over a whole puzzle input.
+///
+/// A single leading and trailing newline are accepted, so inputs written as
+/// raw string literals parse the same as files. Anything else left over is
+/// an error.
+pub fn run_parser<'a, T>(input: &'a str, mut parser: impl Parser<&'a str, T, VerboseError<&'a str>>) -> Result<T> {
+    let body = input.strip_prefix('\n').unwrap_or(input);
+
+    let (rest, result) = parser.parse(body)
+        .map_err(|err| match err {
+            nom::Err::Error(err) | nom::Err::Failure(err) => anyhow!("Failed to parse input:\n{}", nom::error::convert_error(input, err)),
+            nom::Err::Incomplete(_) => anyhow!("Failed to parse input: unexpected end of input"),
+        })?;
+
+    let rest = rest.strip_suffix('\n').unwrap_or(rest);
+    if !rest.is_empty() {
+        let rest = rest.trim_start_matches('\n');
+        let (line, column) = location(input, rest);
+        let unparsed = rest.lines().next().unwrap_or_default();
+        return Err(anyhow!("Had unparsed input after parsing at line {}, column {}: {:?}", line, column, unparsed));
+    }
+
+    Ok(result)
+}
+
+/// The 1-based line and column at which `rest`, a subslice of `input`, starts.
+fn location(input: &str, rest: &str) -> (usize, usize) {
+    let consumed = &input[..rest.as_ptr() as usize - input.as_ptr() as usize];
+    let line = consumed.matches('\n').count() + 1;
+    let column = consumed.len() - consumed.rfind('\n').map_or(0, |i| i + 1) + 1;
+
+    (line, column)
+}
+
+/// An unsigned decimal integer.
+pub fn unsigned<T: std::str::FromStr>(input: &str) -> IResult<&str, T> {
+    map_res(digit1, str::parse)(input)
+}
+
+/// A decimal integer with an optional leading sign.
+pub fn signed<T: std::str::FromStr>(input: &str) -> IResult<&str, T> {
+    map_res(recognize(pair(opt(one_of("+-")), digit1)), str::parse)(input)
+}
+
+/// One or more `item`s separated by commas, with optional spaces after each comma.
+pub fn comma_separated<'a, T>(item: impl Parser<&'a str, T, VerboseError<&'a str>>) -> impl FnMut(&'a str) -> IResult<&'a str, Vec<T>> {
+    separated_list1(tuple((tag(","), space0)), item)
+}
+
+/// One or more `item`s separated by runs of spaces or tabs.
+pub fn space_separated<'a, T>(item: impl Parser<&'a str, T, VerboseError<&'a str>>) -> impl FnMut(&'a str) -> IResult<&'a str, Vec<T>> {
+    separated_list1(space1, item)
+}
+
+/// One `item` per line.
+pub fn lines<'a, T>(item: impl Parser<&'a str, T, VerboseError<&'a str>>) -> impl FnMut(&'a str) -> IResult<&'a str, Vec<T>> {
+    separated_list1(tag("\n"), item)
+}
+
+/// Blocks separated by blank lines.
+pub fn blocks<'a, T>(block: impl Parser<&'a str, T, VerboseError<&'a str>>) -> impl FnMut(&'a str) -> IResult<&'a str, Vec<T>> {
+    separated_list1(tag("\n\n"), block)
+}
+
+/// A rectangular character map, one row per line, with `cell` parsing each character.
+pub fn grid<'a, T>(cell: impl Parser<&'a str, T, VerboseError<&'a str>>) -> impl FnMut(&'a str) -> IResult<&'a str, Array2<T>> {
+    let mut rows = lines(many1(cell));
+
+    move |input: &'a str| {
+        let (rest, row_data) = rows(input)?;
+
+        let cols = row_data[0].len();
+        if let Some(ragged) = row_data.iter().position(|row| row.len() != cols) {
+            let row_start = input.lines().nth(ragged).unwrap_or(input);
+            return Err(nom::Err::Failure(VerboseError {
+                errors: vec![
+                    (row_start, VerboseErrorKind::Context("grid rows must all have the same width")),
+                    (row_start, VerboseErrorKind::Nom(ErrorKind::Verify)),
+                ],
+            }));
+        }
+
+        let shape = (row_data.len(), cols);
+        let data = row_data.into_iter().flatten().collect();
+
+        Ok((rest, Array2::from_shape_vec(shape, data).unwrap()))
+    }
+}
+
+/// Reads an input one record at a time, so that memory use is bounded by the
+/// longest record rather than the whole input. Empty records are skipped, and
+/// each record comes with its 1-based position among all of them.
+pub struct Records<R> {
+    input: R,
+    delimiter: u8,
+    /// What a record is called in errors.
+    noun: &'static str,
+    buffer: Vec<u8>,
+    index: usize,
+}
+
+/// The lines of `input`.
+pub fn stream_lines<R: BufRead>(input: R) -> Records<R> {
+    Records { input, delimiter: b'\n', noun: "line", buffer: Vec::new(), index: 0 }
+}
+
+/// The `delimiter`-separated records of `input`, with any newlines around
+/// each one trimmed off.
+pub fn stream_records<R: BufRead>(input: R, delimiter: u8) -> Records<R> {
+    Records { input, delimiter, noun: "record", buffer: Vec::new(), index: 0 }
+}
+
+impl<R: BufRead> Records<R> {
+    /// Runs `parser` over each record, which it has to consume entirely.
+    pub fn parse<T, P>(self, mut parser: P) -> impl Iterator<Item = Result<(usize, T)>>
+        where P: for<'a> FnMut(&'a str) -> IResult<&'a str, T>
+    {
+        let noun = self.noun;
+        self.map(move |record| {
+            let (index, text) = record?;
+            let value = run_parser(&text, &mut parser).with_context(|| format!("In {} {}", noun, index))?;
+            Ok((index, value))
+        })
+    }
+}
+
+impl<R: BufRead> Iterator for Records<R> {
+    type Item = Result<(usize, String)>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            self.buffer.clear();
+            self.index += 1;
+            match self.input.read_until(self.delimiter, &mut self.buffer) {
+                Ok(0) => return None,
+                Ok(_) => {},
+                Err(e) => return Some(Err(e).with_context(|| format!("Couldn't read {} {}", self.noun, self.index))),
+            }
+
+            let record = self.buffer.strip_suffix(&[self.delimiter]).unwrap_or(&self.buffer);
+            let record = match std::str::from_utf8(record) {
+                Ok(record) => record.trim_matches(['\n', '\r']),
+                Err(e) => return Some(Err(e).with_context(|| format!("Invalid UTF-8 in {} {}", self.noun, self.index))),
+            };
+            if !record.is_empty() {
+                return Some(Ok((self.index, record.to_owned())));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use nom::character::complete::one_of;
+
+    #[test]
+    fn test_integers() {
+        assert_eq!(super::unsigned::<u32>("123 rest"), Ok((" rest", 123)));
+        assert_eq!(super::signed::<i64>("-45,"), Ok((",", -45)));
+        assert_eq!(super::signed::<i64>("+7"), Ok(("", 7)));
+        assert!(super::unsigned::<u8>("300").is_err());
+        assert!(super::unsigned::<u32>("-1").is_err());
+    }
+
+    #[test]
+    fn test_lists() {
+        let result = super::run_parser("\n1, 2,3\n", super::comma_separated(super::unsigned::<u32>)).unwrap();
+        assert_eq!(result, vec![1, 2, 3]);
+
+        let result = super::run_parser("1  2\n3", super::lines(super::space_separated(super::unsigned::<u32>))).unwrap();
+        assert_eq!(result, vec![vec![1, 2], vec![3]]);
+
+        let result = super::run_parser("1\n2\n\n3", super::blocks(super::lines(super::unsigned::<u32>))).unwrap();
+        assert_eq!(result, vec![vec![1, 2], vec![3]]);
+    }
+
+    #[test]
+    fn test_grid() {
+        let result = super::run_parser("\n#.\n.#\n", super::grid(one_of("#."))).unwrap();
+        assert_eq!(result.dim(), (2, 2));
+        assert_eq!(result[(1, 0)], '.');
+
+        assert!(super::run_parser("#.\n#\n", super::grid(one_of("#."))).is_err());
+    }
+
+    #[test]
+    fn test_stream() {
+        let lines: Vec<_> = super::stream_lines("\nab\r\n\ncd".as_bytes()).map(Result::unwrap).collect();
+        assert_eq!(lines, [(2, "ab".to_owned()), (4, "cd".to_owned())]);
+
+        let records: Vec<_> = super::stream_records("a,b,,c\n".as_bytes(), b',').map(Result::unwrap).collect();
+        assert_eq!(records, [(1, "a".to_owned()), (2, "b".to_owned()), (4, "c".to_owned())]);
+
+        let numbers: Vec<u32> = super::stream_lines("1\n2\n".as_bytes()).parse(super::unsigned).map(|n| n.unwrap().1).collect();
+        assert_eq!(numbers, [1, 2]);
+
+        let err = super::stream_lines("1\nx\n".as_bytes()).parse(super::unsigned::<u32>).nth(1).unwrap().unwrap_err();
+        assert!(format!("{:#}", err).starts_with("In line 2: "), "{:#}", err);
+    }
+
+    #[test]
+    fn test_errors() {
+        let err = super::run_parser("1\n2\nx\n", super::lines(super::unsigned::<u32>)).unwrap_err();
+        assert!(err.to_string().contains("line 3, column 1"), "{}", err);
+
+        let err = super::run_parser("12x\n", super::unsigned::<u32>).unwrap_err();
+        assert!(err.to_string().contains("line 1, column 3"), "{}", err);
+    }
+}
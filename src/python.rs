@@ -0,0 +1,313 @@
+//! A Python extension module, so the solutions can be called from Python.
+//!
+//! Besides `solve`, it has helpers for the days whose workings are worth
+//! poking at on their own, with parsed structures returned as dicts and lists.
+//! Ranges are `(start, end)` tuples that include `start` but not `end`.
+
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+use pyo3::types::PyList;
+
+use crate::day19;
+use crate::day20;
+use crate::day5;
+use crate::ranges::RangeSet;
+
+/// Category names for day 19, in the order of its rating axes.
+const CATEGORIES: [&str; 4] = ["x", "m", "a", "s"];
+
+fn value_error(e: impl Into<anyhow::Error>) -> PyErr {
+    PyValueError::new_err(format!("{:#}", e.into()))
+}
+
+/// Solves `part` of `day` for `input` with its main solution.
+#[pyfunction]
+fn solve(day: u8, part: u8, input: &str) -> PyResult<String> {
+    crate::solve(day, part, input)
+        .map(String::from)
+        .map_err(value_error)
+}
+
+/// The seeds and maps of a day 5 almanac, with each map keyed by the type it
+/// maps from.
+#[pyfunction]
+fn day5_almanac<'py>(py: Python<'py>, input: &str) -> PyResult<Bound<'py, PyDict>> {
+    let almanac = day5::input_generator(input).map_err(value_error)?;
+
+    let maps = PyDict::new(py);
+    for (source, map) in &almanac.maps {
+        let ranges = map.ranges.iter()
+            .map(|range| {
+                let dict = PyDict::new(py);
+                dict.set_item("destination_start", range.dest_start)?;
+                dict.set_item("source_start", range.source_start)?;
+                dict.set_item("length", range.length)?;
+                Ok(dict)
+            })
+            .collect::<PyResult<Vec<_>>>()?;
+
+        let dict = PyDict::new(py);
+        dict.set_item("destination", &map.result_type.0)?;
+        dict.set_item("ranges", ranges)?;
+        maps.set_item(&source.0, dict)?;
+    }
+
+    let dict = PyDict::new(py);
+    dict.set_item("seeds", almanac.seeds.iter().map(|seed| seed.0).collect::<Vec<_>>())?;
+    dict.set_item("start", &almanac.starting_type.0)?;
+    dict.set_item("maps", maps)?;
+    Ok(dict)
+}
+
+/// Maps `ranges` of `source` values through the almanac's maps to the
+/// `destination` values they correspond to, merging any that overlap.
+#[pyfunction]
+#[pyo3(signature = (input, ranges, source = "seed", destination = "location"))]
+fn day5_map_ranges(input: &str, ranges: Vec<(usize, usize)>, source: &str, destination: &str) -> PyResult<Vec<(usize, usize)>> {
+    let almanac = day5::input_generator(input).map_err(value_error)?;
+
+    let ranges: RangeSet<usize> = ranges.into_iter().map(|(start, end)| start..end).collect();
+    let source = day5::ItemType(source.to_owned());
+    let destination = day5::ItemType(destination.to_owned());
+
+    let mapped = day5::map_ranges(&almanac, &source, &destination, ranges).map_err(value_error)?;
+    Ok(mapped.ranges().iter().map(|range| (range.start, range.end)).collect())
+}
+
+fn jump_target(target: &day19::JumpTarget) -> &str {
+    match target {
+        day19::JumpTarget::Accept => "A",
+        day19::JumpTarget::Reject => "R",
+        day19::JumpTarget::Jump(name) => &name.0,
+    }
+}
+
+/// The workflows of a day 19 system, keyed by name, and its parts. Each rule
+/// has a `target`, which is `"A"`, `"R"` or a workflow name, and rules with a
+/// condition also have a `category`, a `comparison` of `"<"` or `">"` and a
+/// `value`.
+#[pyfunction]
+fn day19_system<'py>(py: Python<'py>, input: &str) -> PyResult<Bound<'py, PyDict>> {
+    let (workflows, parts) = day19::input_generator(input).map_err(value_error)?;
+
+    let workflows_dict = PyDict::new(py);
+    for (name, workflow) in &workflows {
+        let rules = workflow.rules.iter()
+            .map(|rule| {
+                let dict = PyDict::new(py);
+                match rule {
+                    day19::Rule::Comparison(rule) => {
+                        let comparison = match rule.comp {
+                            day19::ComparisonDirection::Less => "<",
+                            day19::ComparisonDirection::Greater => ">",
+                        };
+                        dict.set_item("category", CATEGORIES[rule.category as usize])?;
+                        dict.set_item("comparison", comparison)?;
+                        dict.set_item("value", rule.value)?;
+                        dict.set_item("target", jump_target(&rule.jump_target))?;
+                    },
+                    day19::Rule::Jump(target) => dict.set_item("target", jump_target(target))?,
+                }
+                Ok(dict)
+            })
+            .collect::<PyResult<Vec<_>>>()?;
+        workflows_dict.set_item(&name.0, rules)?;
+    }
+
+    let parts = parts.iter()
+        .map(|part| {
+            let dict = PyDict::new(py);
+            for (category, value) in CATEGORIES.iter().zip([part.x, part.m, part.a, part.s]) {
+                dict.set_item(category, value)?;
+            }
+            Ok(dict)
+        })
+        .collect::<PyResult<Vec<_>>>()?;
+
+    let dict = PyDict::new(py);
+    dict.set_item("workflows", workflows_dict)?;
+    dict.set_item("parts", parts)?;
+    Ok(dict)
+}
+
+/// The ratings a day 19 system accepts, as its total `volume` and the
+/// disjoint `boxes` making it up, each with a range per category.
+#[pyfunction]
+fn day19_accepted<'py>(py: Python<'py>, input: &str) -> PyResult<Bound<'py, PyDict>> {
+    let system = day19::input_generator(input).map_err(value_error)?;
+    let accepted = day19::accepted_ratings(&system).map_err(value_error)?;
+
+    let boxes = accepted.boxes().iter()
+        .map(|accepted_box| {
+            let dict = PyDict::new(py);
+            for (category, axis) in CATEGORIES.iter().zip(&accepted_box.axes) {
+                dict.set_item(category, (axis.start, axis.end))?;
+            }
+            Ok(dict)
+        })
+        .collect::<PyResult<Vec<_>>>()?;
+
+    let dict = PyDict::new(py);
+    dict.set_item("volume", accepted.volume())?;
+    dict.set_item("boxes", boxes)?;
+    Ok(dict)
+}
+
+/// The modules of a day 20 network, keyed by name, each with its `type` of
+/// `"flip-flop"`, `"conjunction"` or `"broadcast"` and its `connections`.
+#[pyfunction]
+fn day20_modules<'py>(py: Python<'py>, input: &str) -> PyResult<Bound<'py, PyDict>> {
+    let (names, modules) = day20::input_generator(input).map_err(value_error)?;
+
+    let dict = PyDict::new(py);
+    for module in &modules {
+        let module_type = match module.module_type {
+            day20::ModuleType::FlipFlop => "flip-flop",
+            day20::ModuleType::Conjunction => "conjunction",
+            day20::ModuleType::Broadcast => "broadcast",
+        };
+        let connections: Vec<_> = module.connections.iter().map(|connection| &names[connection.0].0).collect();
+
+        let module_dict = PyDict::new(py);
+        module_dict.set_item("type", module_type)?;
+        module_dict.set_item("connections", connections)?;
+        dict.set_item(&names[module.name.0].0, module_dict)?;
+    }
+    Ok(dict)
+}
+
+/// A day 20 network that keeps its state between button presses.
+#[pyclass(name = "Day20Simulation")]
+struct Day20Simulation {
+    simulation: day20::Simulation,
+    presses: usize,
+}
+
+#[pymethods]
+impl Day20Simulation {
+    #[new]
+    fn new(input: &str) -> PyResult<Self> {
+        let input = day20::input_generator(input).map_err(value_error)?;
+        let simulation = day20::Simulation::new(input).map_err(value_error)?;
+
+        Ok(Day20Simulation {
+            simulation,
+            presses: 0,
+        })
+    }
+
+    /// How many times the button has been pressed.
+    #[getter]
+    fn presses(&self) -> usize {
+        self.presses
+    }
+
+    /// Presses the button once, returning every pulse it sets off in the
+    /// order they're delivered, each with a `source`, a `pulse` of `"low"` or
+    /// `"high"` and a `destination`.
+    fn press<'py>(&mut self, py: Python<'py>) -> PyResult<Bound<'py, PyList>> {
+        self.presses += 1;
+
+        let pulses = self.simulation.press().into_iter()
+            .map(|(source, pulse, dest)| {
+                let pulse = match pulse {
+                    day20::Pulse::Low => "low",
+                    day20::Pulse::High => "high",
+                };
+
+                let dict = PyDict::new(py);
+                dict.set_item("source", self.simulation.name(source))?;
+                dict.set_item("pulse", pulse)?;
+                dict.set_item("destination", self.simulation.name(dest))?;
+                Ok(dict)
+            })
+            .collect::<PyResult<Vec<_>>>()?;
+
+        PyList::new(py, pulses)
+    }
+}
+
+#[pymodule]
+fn aoc_2023(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_function(wrap_pyfunction!(solve, m)?)?;
+    m.add_function(wrap_pyfunction!(day5_almanac, m)?)?;
+    m.add_function(wrap_pyfunction!(day5_map_ranges, m)?)?;
+    m.add_function(wrap_pyfunction!(day19_system, m)?)?;
+    m.add_function(wrap_pyfunction!(day19_accepted, m)?)?;
+    m.add_function(wrap_pyfunction!(day20_modules, m)?)?;
+    m.add_class::<Day20Simulation>()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use pyo3::prelude::*;
+    use pyo3::types::PyDict;
+
+    /// Runs `code` with the extension module imported as `aoc`.
+    fn run(code: &str) {
+        Python::initialize();
+        Python::attach(|py| {
+            let module = PyModule::new(py, "aoc_2023").unwrap();
+            super::aoc_2023(&module).unwrap();
+
+            let globals = PyDict::new(py);
+            globals.set_item("aoc", module).unwrap();
+            let code = std::ffi::CString::new(code).unwrap();
+            py.run(&code, Some(&globals), None).unwrap();
+        });
+    }
+
+    #[test]
+    fn test_solve() {
+        run(r#"
+assert aoc.solve(9, 1, "0 3 6 9 12 15\n1 3 6 10 15 21\n10 13 16 21 30 45\n") == "114"
+try:
+    aoc.solve(26, 1, "")
+    assert False
+except ValueError as e:
+    assert str(e) == "No solution for day 26 part 1"
+"#);
+    }
+
+    #[test]
+    fn test_day5() {
+        run(r#"
+almanac = "seeds: 79 14 55 13\n\nseed-to-soil map:\n50 98 2\n52 50 48\n\nsoil-to-location map:\n0 15 37\n"
+parsed = aoc.day5_almanac(almanac)
+assert parsed["seeds"] == [79, 14, 55, 13]
+assert parsed["start"] == "seed"
+assert parsed["maps"]["seed"]["destination"] == "soil"
+assert parsed["maps"]["seed"]["ranges"][1] == {"destination_start": 52, "source_start": 50, "length": 48}
+assert aoc.day5_map_ranges(almanac, [(98, 100)], destination="soil") == [(50, 52)]
+assert aoc.day5_map_ranges(almanac, [(98, 100)]) == [(35, 37)]
+"#);
+    }
+
+    #[test]
+    fn test_day19() {
+        run(r#"
+system = aoc.day19_system("in{x<10:A,R}\n\n{x=1,m=2,a=3,s=4}\n")
+assert system["workflows"]["in"] == [{"category": "x", "comparison": "<", "value": 10, "target": "A"}, {"target": "R"}]
+assert system["parts"] == [{"x": 1, "m": 2, "a": 3, "s": 4}]
+accepted = aoc.day19_accepted("in{x<10:A,R}\n\n{x=1,m=2,a=3,s=4}\n")
+assert accepted["volume"] == 9 * 4000 ** 3
+assert accepted["boxes"] == [{"x": (1, 10), "m": (1, 4001), "a": (1, 4001), "s": (1, 4001)}]
+"#);
+    }
+
+    #[test]
+    fn test_day20() {
+        run(r#"
+network = "broadcaster -> a\n%a -> inv, con\n&inv -> b\n%b -> con\n&con -> output\n"
+assert aoc.day20_modules(network)["a"] == {"type": "flip-flop", "connections": ["inv", "con"]}
+simulation = aoc.Day20Simulation(network)
+pulses = simulation.press()
+assert pulses[0] == {"source": "button", "pulse": "low", "destination": "broadcaster"}
+assert [p["pulse"] for p in pulses] == ["low", "low", "high", "high", "low", "high", "high", "low"]
+assert len(simulation.press()) == 6
+assert simulation.presses == 2
+"#);
+    }
+}
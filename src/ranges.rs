@@ -0,0 +1,307 @@
+use std::ops::Range;
+
+use num::PrimInt;
+
+/// A set of integers stored as sorted, disjoint, non-touching half-open ranges.
+#[derive(Debug, Clone)]
+#[derive(PartialEq, Eq)]
+#[derive(Hash)]
+pub struct RangeSet<T> {
+    ranges: Vec<Range<T>>,
+}
+
+impl<T: PrimInt> Default for RangeSet<T> {
+    fn default() -> Self {
+        RangeSet { ranges: Vec::new() }
+    }
+}
+
+impl<T: PrimInt> RangeSet<T> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn ranges(&self) -> &[Range<T>] {
+        &self.ranges
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ranges.is_empty()
+    }
+
+    /// The number of values in the set.
+    pub fn measure(&self) -> T {
+        self.ranges.iter().fold(T::zero(), |total, range| total + (range.end - range.start))
+    }
+
+    pub fn min(&self) -> Option<T> {
+        self.ranges.first().map(|range| range.start)
+    }
+
+    pub fn contains(&self, value: T) -> bool {
+        let index = self.ranges.partition_point(|range| range.end <= value);
+        self.ranges.get(index).is_some_and(|range| range.start <= value)
+    }
+
+    pub fn insert(&mut self, range: Range<T>) {
+        *self = self.union(&RangeSet::from(range));
+    }
+
+    pub fn union(&self, other: &Self) -> Self {
+        self.ranges.iter().chain(other.ranges.iter()).cloned().collect()
+    }
+
+    pub fn intersection(&self, other: &Self) -> Self {
+        let mut ranges = Vec::new();
+
+        let mut i = 0;
+        let mut j = 0;
+        while i < self.ranges.len() && j < other.ranges.len() {
+            let a = &self.ranges[i];
+            let b = &other.ranges[j];
+
+            let start = a.start.max(b.start);
+            let end = a.end.min(b.end);
+            if start < end {
+                ranges.push(start..end);
+            }
+
+            if a.end < b.end {
+                i += 1;
+            } else {
+                j += 1;
+            }
+        }
+
+        RangeSet { ranges }
+    }
+
+    /// The values in `self` that are not in `other`.
+    pub fn difference(&self, other: &Self) -> Self {
+        let mut ranges = Vec::new();
+
+        let mut j = 0;
+        for range in &self.ranges {
+            let mut start = range.start;
+
+            while j < other.ranges.len() && other.ranges[j].end <= start {
+                j += 1;
+            }
+
+            let mut k = j;
+            while k < other.ranges.len() && other.ranges[k].start < range.end {
+                let cut = &other.ranges[k];
+                if start < cut.start {
+                    ranges.push(start..cut.start);
+                }
+                start = start.max(cut.end);
+                k += 1;
+            }
+
+            if start < range.end {
+                ranges.push(start..range.end);
+            }
+        }
+
+        RangeSet { ranges }
+    }
+
+    /// Splits the set into the values below `at` and the values from `at` upwards.
+    pub fn split_at(&self, at: T) -> (Self, Self) {
+        let mut below = Vec::new();
+        let mut above = Vec::new();
+
+        for range in &self.ranges {
+            if range.end <= at {
+                below.push(range.clone());
+            } else if range.start >= at {
+                above.push(range.clone());
+            } else {
+                below.push(range.start..at);
+                above.push(at..range.end);
+            }
+        }
+
+        (RangeSet { ranges: below }, RangeSet { ranges: above })
+    }
+}
+
+impl<T: PrimInt> From<Range<T>> for RangeSet<T> {
+    fn from(range: Range<T>) -> Self {
+        std::iter::once(range).collect()
+    }
+}
+
+impl<T: PrimInt> FromIterator<Range<T>> for RangeSet<T> {
+    fn from_iter<I: IntoIterator<Item = Range<T>>>(iter: I) -> Self {
+        let mut sorted: Vec<_> = iter.into_iter().filter(|range| range.start < range.end).collect();
+        sorted.sort_by_key(|range| range.start);
+
+        let mut ranges: Vec<Range<T>> = Vec::with_capacity(sorted.len());
+        for range in sorted {
+            match ranges.last_mut() {
+                Some(last) if range.start <= last.end => {
+                    last.end = last.end.max(range.end);
+                },
+                _ => ranges.push(range),
+            }
+        }
+
+        RangeSet { ranges }
+    }
+}
+
+/// An axis-aligned box in `N` dimensions, half-open along each axis.
+#[derive(Debug, Clone)]
+#[derive(PartialEq, Eq)]
+#[derive(Hash)]
+pub struct AxisBox<T, const N: usize> {
+    pub axes: [Range<T>; N],
+}
+
+impl<T: PrimInt, const N: usize> AxisBox<T, N> {
+    pub fn is_empty(&self) -> bool {
+        self.axes.iter().any(|range| range.start >= range.end)
+    }
+
+    pub fn volume(&self) -> T {
+        if self.is_empty() {
+            return T::zero();
+        }
+
+        self.axes.iter().fold(T::one(), |volume, range| volume * (range.end - range.start))
+    }
+}
+
+/// A union of possibly overlapping boxes.
+#[derive(Debug, Clone)]
+#[derive(PartialEq, Eq)]
+pub struct BoxSet<T, const N: usize> {
+    boxes: Vec<AxisBox<T, N>>,
+}
+
+impl<T: PrimInt, const N: usize> Default for BoxSet<T, N> {
+    fn default() -> Self {
+        BoxSet { boxes: Vec::new() }
+    }
+}
+
+impl<T: PrimInt, const N: usize> BoxSet<T, N> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn insert(&mut self, new_box: AxisBox<T, N>) {
+        if !new_box.is_empty() {
+            self.boxes.push(new_box);
+        }
+    }
+
+    pub fn boxes(&self) -> &[AxisBox<T, N>] {
+        &self.boxes
+    }
+
+    /// The exact volume covered by at least one box, counting overlaps once.
+    pub fn volume(&self) -> T {
+        let boxes: Vec<_> = self.boxes.iter().collect();
+        union_volume(&boxes, 0)
+    }
+}
+
+impl<T: PrimInt, const N: usize> FromIterator<AxisBox<T, N>> for BoxSet<T, N> {
+    fn from_iter<I: IntoIterator<Item = AxisBox<T, N>>>(iter: I) -> Self {
+        let mut set = BoxSet::new();
+        for new_box in iter {
+            set.insert(new_box);
+        }
+        set
+    }
+}
+
+/// Sweeps `axis` in slabs between consecutive box boundaries, measuring the
+/// cross-section of each slab over the remaining axes.
+fn union_volume<T: PrimInt, const N: usize>(boxes: &[&AxisBox<T, N>], axis: usize) -> T {
+    if boxes.is_empty() {
+        return T::zero();
+    }
+
+    if axis + 1 == N {
+        return boxes.iter().map(|b| b.axes[axis].clone()).collect::<RangeSet<T>>().measure();
+    }
+
+    let mut boundaries: Vec<_> = boxes.iter()
+        .flat_map(|b| [b.axes[axis].start, b.axes[axis].end])
+        .collect();
+    boundaries.sort();
+    boundaries.dedup();
+
+    boundaries.windows(2)
+        .map(|slab| {
+            let covering: Vec<_> = boxes.iter()
+                .filter(|b| b.axes[axis].start <= slab[0] && slab[1] <= b.axes[axis].end)
+                .cloned()
+                .collect();
+
+            (slab[1] - slab[0]) * union_volume(&covering, axis + 1)
+        })
+        .fold(T::zero(), |total, volume| total + volume)
+}
+
+#[cfg(test)]
+mod test {
+    use super::AxisBox;
+    use super::BoxSet;
+    use super::RangeSet;
+
+    #[test]
+    fn test_normalize() {
+        let set: RangeSet<u32> = [5..8, 0..2, 2..3, 7..10, 12..12].into_iter().collect();
+        assert_eq!(set.ranges(), &[0..3, 5..10]);
+        assert_eq!(set.measure(), 8);
+        assert_eq!(set.min(), Some(0));
+
+        assert!(set.contains(2));
+        assert!(!set.contains(3));
+        assert!(set.contains(9));
+        assert!(!set.contains(10));
+    }
+
+    #[test]
+    fn test_set_operations() {
+        let a: RangeSet<u32> = [0..10, 20..30].into_iter().collect();
+        let b: RangeSet<u32> = [5..25, 28..40].into_iter().collect();
+
+        assert_eq!(a.union(&b), RangeSet::from(0..40));
+        assert_eq!(a.intersection(&b).ranges(), &[5..10, 20..25, 28..30]);
+        assert_eq!(a.difference(&b).ranges(), &[0..5, 25..28]);
+        assert_eq!(b.difference(&a).ranges(), &[10..20, 30..40]);
+
+        let (below, above) = a.split_at(25);
+        assert_eq!(below.ranges(), &[0..10, 20..25]);
+        assert_eq!(above, RangeSet::from(25..30));
+
+        let mut c = RangeSet::from(0..5u32);
+        c.insert(5..7);
+        assert_eq!(c, RangeSet::from(0..7));
+    }
+
+    #[test]
+    fn test_box_union() {
+        let boxes: BoxSet<u64, 2> = [
+            AxisBox { axes: [0..4, 0..4] },
+            AxisBox { axes: [2..6, 2..6] },
+            AxisBox { axes: [10..11, 0..1] },
+            AxisBox { axes: [0..0, 0..100] },
+        ].into_iter().collect();
+
+        assert_eq!(boxes.volume(), 16 + 16 - 4 + 1);
+
+        let cube: BoxSet<u64, 3> = [
+            AxisBox { axes: [0..2, 0..2, 0..2] },
+            AxisBox { axes: [0..2, 0..2, 0..2] },
+            AxisBox { axes: [1..3, 1..3, 1..3] },
+        ].into_iter().collect();
+
+        assert_eq!(cube.volume(), 8 + 8 - 1);
+    }
+}
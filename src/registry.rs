@@ -0,0 +1,265 @@
+use std::borrow::Borrow;
+use std::fmt;
+use std::io::BufRead;
+use std::time::Duration;
+
+/// A single registered solver, with its input parsing and answer formatting
+/// erased so that every day can share one table.
+#[derive(Clone, Copy)]
+pub struct Solution {
+    pub day: u32,
+    pub part: u32,
+    /// `None` for the main solution, or the name of an alternate implementation.
+    pub name: Option<&'static str>,
+    pub run: fn(&str) -> Result<Run, RunError>,
+}
+
+impl fmt::Debug for Solution {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Solution")
+            .field("day", &self.day)
+            .field("part", &self.part)
+            .field("name", &self.name)
+            .finish()
+    }
+}
+
+/// An answer and how long it took to get. The times are zero on
+/// `wasm32-unknown-unknown`, which has no clock.
+#[derive(Debug, Clone)]
+#[derive(PartialEq, Eq)]
+pub struct Run {
+    pub answer: String,
+    pub parse_time: Duration,
+    pub solve_time: Duration,
+}
+
+#[derive(Debug)]
+pub enum RunError {
+    Parse(anyhow::Error),
+    Solve(anyhow::Error),
+}
+
+impl fmt::Display for RunError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RunError::Parse(e) => write!(f, "Failed to parse input: {:#}", e),
+            RunError::Solve(e) => write!(f, "Failed to solve: {:#}", e),
+        }
+    }
+}
+
+impl std::error::Error for RunError {}
+
+/// Anything a solver can return.
+pub trait IntoAnswer {
+    fn into_answer(self) -> anyhow::Result<String>;
+}
+
+macro_rules! display_answer {
+    ($($t:ty),*) => {
+        $(
+            impl IntoAnswer for $t {
+                fn into_answer(self) -> anyhow::Result<String> {
+                    Ok(self.to_string())
+                }
+            }
+        )*
+    };
+}
+
+display_answer!(u32, u64, usize, i32, i64, String);
+
+impl<T: IntoAnswer, E: Into<anyhow::Error>> IntoAnswer for Result<T, E> {
+    fn into_answer(self) -> anyhow::Result<String> {
+        self.map_err(Into::into)?.into_answer()
+    }
+}
+
+#[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+fn timed<R>(f: impl FnOnce() -> R) -> (R, Duration) {
+    let start = std::time::Instant::now();
+    let result = f();
+    (result, start.elapsed())
+}
+
+/// `Instant::now` panics without a clock, so nothing is timed.
+#[cfg(all(target_arch = "wasm32", target_os = "unknown"))]
+fn timed<R>(f: impl FnOnce() -> R) -> (R, Duration) {
+    (f(), Duration::ZERO)
+}
+
+/// Parses `input` and solves it, timing each step separately.
+pub fn run<I, T, A>(generator: impl Fn(&str) -> anyhow::Result<I>, solver: impl Fn(&T) -> A, input: &str) -> Result<Run, RunError>
+    where I: Borrow<T>, T: ?Sized, A: IntoAnswer
+{
+    let (parsed, parse_time) = timed(|| generator(input));
+    let parsed = parsed.map_err(RunError::Parse)?;
+
+    let (answer, solve_time) = timed(|| solver(parsed.borrow()).into_answer());
+    let answer = answer.map_err(RunError::Solve)?;
+
+    Ok(Run {
+        answer,
+        parse_time,
+        solve_time,
+    })
+}
+
+/// A solver that reads its input a line or record at a time instead of
+/// needing it all in memory, for inputs too big to load. It gives the same
+/// answer as the main solution for its day and part.
+#[derive(Clone, Copy)]
+pub struct Streaming {
+    pub day: u32,
+    pub part: u32,
+    pub run: fn(&mut dyn BufRead) -> anyhow::Result<String>,
+}
+
+impl fmt::Debug for Streaming {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Streaming")
+            .field("day", &self.day)
+            .field("part", &self.part)
+            .finish()
+    }
+}
+
+/// Declares a day's `SOLUTIONS` table from `(day, part, [name,] generator, solver)` entries.
+macro_rules! solutions {
+    ($(($day:literal, $part:literal, $($name:literal,)? $generator:path, $solver:path)),* $(,)?) => {
+        pub (crate) const SOLUTIONS: &[$crate::registry::Solution] = &[
+            $(
+                $crate::registry::Solution {
+                    day: $day,
+                    part: $part,
+                    name: $crate::registry::solutions!(@name $($name)?),
+                    run: |input| $crate::registry::run($generator, $solver, input),
+                },
+            )*
+        ];
+    };
+    (@name) => { None };
+    (@name $name:literal) => { Some($name) };
+}
+
+pub(crate) use solutions;
+
+/// Declares a day's `STREAMING` table from `(day, part, solver)` entries,
+/// where each solver takes an `impl BufRead`.
+macro_rules! streaming_solutions {
+    ($(($day:literal, $part:literal, $solver:path)),* $(,)?) => {
+        pub (crate) const STREAMING: &[$crate::registry::Streaming] = &[
+            $(
+                $crate::registry::Streaming {
+                    day: $day,
+                    part: $part,
+                    run: |input| $crate::registry::IntoAnswer::into_answer($solver(input)),
+                },
+            )*
+        ];
+    };
+}
+
+pub(crate) use streaming_solutions;
+
+/// Every registered solution, ordered by day and part.
+pub fn all() -> impl Iterator<Item = &'static Solution> {
+    use crate::*;
+
+    [
+        day1::SOLUTIONS,
+        day2::SOLUTIONS,
+        day3::SOLUTIONS,
+        day4::SOLUTIONS,
+        day5::SOLUTIONS,
+        day6::SOLUTIONS,
+        day7::SOLUTIONS,
+        day8::SOLUTIONS,
+        day9::SOLUTIONS,
+        day10::SOLUTIONS,
+        day11::SOLUTIONS,
+        day12::SOLUTIONS,
+        day13::SOLUTIONS,
+        day14::SOLUTIONS,
+        day15::SOLUTIONS,
+        day16::SOLUTIONS,
+        day17::SOLUTIONS,
+        day17_2::SOLUTIONS,
+        day18::SOLUTIONS,
+        day18_2::SOLUTIONS,
+        day19::SOLUTIONS,
+        day20::SOLUTIONS,
+        day21::SOLUTIONS,
+        day22::SOLUTIONS,
+        day23::SOLUTIONS,
+        day24::SOLUTIONS,
+        day25::SOLUTIONS,
+    ].into_iter().flatten()
+}
+
+/// Looks up a solution by day and part, picking an alternate implementation if `name` is given.
+pub fn find(day: u32, part: u32, name: Option<&str>) -> Option<&'static Solution> {
+    all().find(|solution| solution.day == day && solution.part == part && solution.name == name)
+}
+
+/// Every streaming solver, ordered by day and part.
+pub fn streaming() -> impl Iterator<Item = &'static Streaming> {
+    use crate::*;
+
+    [
+        day1::STREAMING,
+        day2::STREAMING,
+        day4::STREAMING,
+        day6::STREAMING,
+        day7::STREAMING,
+        day9::STREAMING,
+        day12::STREAMING,
+        day15::STREAMING,
+        day22::STREAMING,
+    ].into_iter().flatten()
+}
+
+pub fn find_streaming(day: u32, part: u32) -> Option<&'static Streaming> {
+    streaming().find(|solution| solution.day == day && solution.part == part)
+}
+
+#[cfg(test)]
+mod test {
+    #[test]
+    fn test_registry_is_complete() {
+        for day in 1..=25 {
+            let parts = if day == 25 { 1 } else { 2 };
+            for part in 1..=parts {
+                assert!(super::find(day, part, None).is_some(), "missing day {} part {}", day, part);
+            }
+        }
+
+        assert!(super::find(5, 2, Some("memory_hog")).is_some());
+        assert!(super::find(26, 1, None).is_none());
+    }
+
+    #[test]
+    fn test_streaming_matches() {
+        for streaming in super::streaming() {
+            let solution = super::find(streaming.day, streaming.part, None).unwrap();
+            let generator = crate::gen::find(streaming.day).unwrap();
+            for seed in 0..3 {
+                let input = generator.run(seed, 20);
+                let expected = (solution.run)(&input).unwrap().answer;
+                let answer = (streaming.run)(&mut input.as_bytes()).unwrap();
+                assert_eq!(answer, expected, "day {} part {} seed {}", streaming.day, streaming.part, seed);
+            }
+        }
+    }
+
+    #[test]
+    fn test_run() {
+        let solution = super::find(9, 1, None).unwrap();
+        let run = (solution.run)("0 3 6 9 12 15\n1 3 6 10 15 21\n10 13 16 21 30 45\n").unwrap();
+        assert_eq!(run.answer, "114");
+
+        let error = (solution.run)("0 3 x\n").unwrap_err();
+        assert!(matches!(error, super::RunError::Parse(_)));
+    }
+}
@@ -0,0 +1,311 @@
+//! Headless images of grids, for looking at puzzle state without scraping
+//! stdout.
+//!
+//! Days call `dump` with a closure that builds a `Frame`. Nothing is built or
+//! written unless `AOC_RENDER_DIR` is set, in which case each frame is saved
+//! there as `<name>.<format>`. `AOC_RENDER_FORMAT` picks the format: `ppm`
+//! (the default), `pgm` or `svg`.
+
+use std::io;
+use std::io::Write;
+use std::path::Path;
+use std::path::PathBuf;
+
+use crate::grid::Grid;
+use crate::grid::Pos;
+
+pub const RENDER_DIR_VAR: &str = "AOC_RENDER_DIR";
+pub const RENDER_FORMAT_VAR: &str = "AOC_RENDER_FORMAT";
+
+#[derive(Debug, Clone, Copy)]
+#[derive(PartialEq, Eq)]
+#[derive(Hash)]
+pub struct Rgb(pub u8, pub u8, pub u8);
+
+impl Rgb {
+    pub const BLACK: Rgb = Rgb(0, 0, 0);
+    pub const WHITE: Rgb = Rgb(255, 255, 255);
+    pub const GREY: Rgb = Rgb(128, 128, 128);
+    pub const RED: Rgb = Rgb(220, 40, 40);
+    pub const ORANGE: Rgb = Rgb(255, 150, 0);
+    pub const YELLOW: Rgb = Rgb(255, 220, 40);
+    pub const BLUE: Rgb = Rgb(40, 90, 220);
+
+    /// Blends towards `other`, with `t` = 0 giving `self` and `t` = 1 giving `other`.
+    pub fn lerp(self, other: Rgb, t: f64) -> Rgb {
+        let t = t.clamp(0.0, 1.0);
+        let mix = |a: u8, b: u8| (a as f64 + (b as f64 - a as f64) * t).round() as u8;
+
+        Rgb(mix(self.0, other.0), mix(self.1, other.1), mix(self.2, other.2))
+    }
+
+    /// Rec. 601 luma, used for greyscale output.
+    pub fn luma(self) -> u8 {
+        ((299 * self.0 as u32 + 587 * self.1 as u32 + 114 * self.2 as u32) / 1000) as u8
+    }
+
+    fn hex(self) -> String {
+        format!("#{:02x}{:02x}{:02x}", self.0, self.1, self.2)
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+#[derive(PartialEq, Eq)]
+pub enum Format {
+    Ppm,
+    Pgm,
+    Svg,
+}
+
+impl Format {
+    pub fn extension(self) -> &'static str {
+        match self {
+            Format::Ppm => "ppm",
+            Format::Pgm => "pgm",
+            Format::Svg => "svg",
+        }
+    }
+
+    fn from_extension(extension: &str) -> Option<Format> {
+        match extension {
+            "ppm" => Some(Format::Ppm),
+            "pgm" => Some(Format::Pgm),
+            "svg" => Some(Format::Svg),
+            _ => None,
+        }
+    }
+}
+
+/// A path drawn over the cells, through the centre of each position in turn.
+#[derive(Debug, Clone)]
+struct Overlay {
+    positions: Vec<Pos>,
+    colour: Rgb,
+}
+
+/// A coloured grid with optional path overlays, ready to be written out.
+#[derive(Debug, Clone)]
+pub struct Frame {
+    cells: Grid<Rgb>,
+    overlays: Vec<Overlay>,
+    cell_size: usize,
+}
+
+impl Frame {
+    pub fn new<T>(grid: &Grid<T>, colour: impl FnMut(&T) -> Rgb) -> Self {
+        Frame {
+            cells: grid.map(colour),
+            overlays: Vec::new(),
+            cell_size: 4,
+        }
+    }
+
+    /// Draws a path over the frame. Consecutive positions don't need to be
+    /// adjacent, but they should share a row or column.
+    pub fn with_path(mut self, positions: impl IntoIterator<Item = Pos>, colour: Rgb) -> Self {
+        self.overlays.push(Overlay {
+            positions: positions.into_iter().collect(),
+            colour,
+        });
+        self
+    }
+
+    /// The width and height of one cell, in pixels for raster images and
+    /// user units for SVG. Defaults to 4.
+    pub fn with_cell_size(mut self, cell_size: usize) -> Self {
+        self.cell_size = cell_size.max(1);
+        self
+    }
+
+    /// The cell colours with every path painted on top, for raster output.
+    fn flatten(&self) -> Grid<Rgb> {
+        let mut cells = self.cells.clone();
+
+        for overlay in &self.overlays {
+            for window in overlay.positions.windows(2) {
+                let (from, to) = (window[0], window[1]);
+                let rows = from.row.min(to.row)..=from.row.max(to.row);
+                let cols = from.col.min(to.col)..=from.col.max(to.col);
+
+                for row in rows {
+                    for col in cols.clone() {
+                        if let Some(cell) = cells.get_mut(Pos::new(row, col)) {
+                            *cell = overlay.colour;
+                        }
+                    }
+                }
+            }
+            if let [pos] = overlay.positions[..] {
+                if let Some(cell) = cells.get_mut(pos) {
+                    *cell = overlay.colour;
+                }
+            }
+        }
+
+        cells
+    }
+
+    fn write_raster(&self, mut writer: impl Write, magic: &str, mut pixel: impl FnMut(Rgb, &mut Vec<u8>)) -> io::Result<()> {
+        let cells = self.flatten();
+        let size = self.cell_size;
+
+        write!(writer, "{}\n{} {}\n255\n", magic, cells.cols() * size, cells.rows() * size)?;
+
+        let mut row_bytes = Vec::new();
+        for row in 0..cells.rows() {
+            row_bytes.clear();
+            for col in 0..cells.cols() {
+                for _ in 0..size {
+                    pixel(cells[Pos::new(row, col)], &mut row_bytes);
+                }
+            }
+            for _ in 0..size {
+                writer.write_all(&row_bytes)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Writes a binary (P6) PPM.
+    pub fn write_ppm(&self, writer: impl Write) -> io::Result<()> {
+        self.write_raster(writer, "P6", |colour, bytes| bytes.extend([colour.0, colour.1, colour.2]))
+    }
+
+    /// Writes a binary (P5) PGM, converting each colour to its luma.
+    pub fn write_pgm(&self, writer: impl Write) -> io::Result<()> {
+        self.write_raster(writer, "P5", |colour, bytes| bytes.push(colour.luma()))
+    }
+
+    /// Writes an SVG with one rectangle per cell and a polyline per path.
+    pub fn write_svg(&self, mut writer: impl Write) -> io::Result<()> {
+        let size = self.cell_size;
+        let (rows, cols) = self.cells.bounds();
+
+        writeln!(writer, r#"<svg xmlns="http://www.w3.org/2000/svg" width="{}" height="{}" viewBox="0 0 {} {}" shape-rendering="crispEdges">"#,
+            cols * size, rows * size, cols * size, rows * size)?;
+
+        for (pos, colour) in self.cells.indexed_iter() {
+            writeln!(writer, r#"<rect x="{}" y="{}" width="{}" height="{}" fill="{}"/>"#,
+                pos.col * size, pos.row * size, size, size, colour.hex())?;
+        }
+
+        let centre = |n: usize| n as f64 * size as f64 + size as f64 / 2.0;
+        for overlay in &self.overlays {
+            let points: Vec<_> = overlay.positions.iter()
+                .map(|pos| format!("{},{}", centre(pos.col), centre(pos.row)))
+                .collect();
+            writeln!(writer, r#"<polyline points="{}" fill="none" stroke="{}" stroke-width="{}" stroke-linejoin="round"/>"#,
+                points.join(" "), overlay.colour.hex(), size as f64 / 4.0)?;
+        }
+
+        writeln!(writer, "</svg>")
+    }
+
+    pub fn write(&self, writer: impl Write, format: Format) -> io::Result<()> {
+        match format {
+            Format::Ppm => self.write_ppm(writer),
+            Format::Pgm => self.write_pgm(writer),
+            Format::Svg => self.write_svg(writer),
+        }
+    }
+
+    /// Saves the frame, choosing the format from the file extension.
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        let path = path.as_ref();
+        let format = path.extension()
+            .and_then(|extension| extension.to_str())
+            .and_then(Format::from_extension)
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, format!("Can't tell the image format of {}", path.display())))?;
+
+        let file = std::fs::File::create(path)?;
+        let mut writer = io::BufWriter::new(file);
+        self.write(&mut writer, format)?;
+        writer.flush()
+    }
+}
+
+/// Where frames should go, if rendering has been turned on.
+fn target() -> io::Result<Option<(PathBuf, Format)>> {
+    let Some(dir) = std::env::var_os(RENDER_DIR_VAR) else {
+        return Ok(None);
+    };
+
+    let format = match std::env::var(RENDER_FORMAT_VAR) {
+        Ok(name) => Format::from_extension(&name.to_ascii_lowercase())
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, format!("Unknown {} {:?}", RENDER_FORMAT_VAR, name)))?,
+        Err(_) => Format::Ppm,
+    };
+
+    Ok(Some((PathBuf::from(dir), format)))
+}
+
+/// Saves the frame built by `frame` as `<name>.<format>` if rendering is
+/// turned on, and does nothing otherwise.
+pub fn dump(name: &str, frame: impl FnOnce() -> Frame) -> io::Result<()> {
+    let Some((dir, format)) = target()? else {
+        return Ok(());
+    };
+
+    std::fs::create_dir_all(&dir)?;
+    frame().save(dir.join(format!("{}.{}", name, format.extension())))
+}
+
+#[cfg(test)]
+mod test {
+    use super::Frame;
+    use super::Rgb;
+    use crate::grid::Grid;
+    use crate::grid::Pos;
+
+    fn checkerboard() -> Frame {
+        let grid = Grid::from_fn((2, 3), |pos| (pos.row + pos.col) % 2 == 0);
+        Frame::new(&grid, |&on| if on { Rgb::WHITE } else { Rgb::BLACK }).with_cell_size(1)
+    }
+
+    #[test]
+    fn test_ppm() {
+        let mut bytes = Vec::new();
+        checkerboard().write_ppm(&mut bytes).unwrap();
+
+        let mut expected = b"P6\n3 2\n255\n".to_vec();
+        for on in [true, false, true, false, true, false] {
+            expected.extend(if on { [255; 3] } else { [0; 3] });
+        }
+        assert_eq!(bytes, expected);
+    }
+
+    #[test]
+    fn test_pgm_with_path() {
+        let frame = checkerboard()
+            .with_cell_size(2)
+            .with_path([Pos::new(0, 0), Pos::new(0, 2)], Rgb::GREY);
+
+        let mut bytes = Vec::new();
+        frame.write_pgm(&mut bytes).unwrap();
+
+        let header = b"P5\n6 4\n255\n";
+        assert_eq!(&bytes[..header.len()], header);
+
+        let pixels = &bytes[header.len()..];
+        assert_eq!(pixels.len(), 24);
+        // The path covers the whole top row of cells
+        assert!(pixels[..12].iter().all(|&p| p == Rgb::GREY.luma()));
+        assert_eq!(&pixels[12..18], &[0, 0, 255, 255, 0, 0]);
+    }
+
+    #[test]
+    fn test_svg() {
+        let frame = checkerboard().with_path([Pos::new(0, 0), Pos::new(1, 0)], Rgb::RED);
+
+        let mut bytes = Vec::new();
+        frame.write_svg(&mut bytes).unwrap();
+        let svg = String::from_utf8(bytes).unwrap();
+
+        assert!(svg.starts_with("<svg"));
+        assert!(svg.trim_end().ends_with("</svg>"));
+        assert_eq!(svg.matches("<rect").count(), 6);
+        assert!(svg.contains(r##"<rect x="1" y="0" width="1" height="1" fill="#000000"/>"##));
+        assert!(svg.contains(r##"points="0.5,0.5 0.5,1.5" fill="none" stroke="#dc2828""##));
+    }
+}
@@ -0,0 +1,233 @@
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+use std::collections::HashMap;
+use std::collections::VecDeque;
+use std::collections::hash_map::Entry;
+use std::hash::Hash;
+use std::ops::Add;
+
+/// A graph to be searched, described implicitly by its transitions.
+pub trait Problem {
+    type State: Clone + Eq + Hash;
+    type Cost: Copy + Ord + Default + Add<Output = Self::Cost>;
+
+    /// States reachable in one step from `state`, with the cost of each step.
+    fn successors(&self, state: &Self::State) -> impl IntoIterator<Item = (Self::State, Self::Cost)>;
+
+    fn is_goal(&self, state: &Self::State) -> bool;
+
+    /// A lower bound on the remaining cost from `state` to any goal. The
+    /// default of zero turns A* into Dijkstra.
+    fn heuristic(&self, _state: &Self::State) -> Self::Cost {
+        Self::Cost::default()
+    }
+}
+
+/// The cheapest route found to a goal, including the starting state.
+#[derive(Debug, Clone)]
+#[derive(PartialEq, Eq)]
+pub struct Path<S, C> {
+    pub cost: C,
+    pub states: Vec<S>,
+}
+
+impl<S, C> Path<S, C> {
+    pub fn goal(&self) -> &S {
+        self.states.last().unwrap()
+    }
+}
+
+struct Node<S, C> {
+    state: S,
+    cost: C,
+    parent: Option<usize>,
+}
+
+#[derive(PartialEq, Eq)]
+struct Frontier<C> {
+    estimate: C,
+    cost: C,
+    index: usize,
+}
+
+impl<C: Ord> Ord for Frontier<C> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        // Reversed so that `BinaryHeap` pops the lowest estimate first,
+        // preferring deeper nodes on ties.
+        other.estimate.cmp(&self.estimate)
+            .then_with(|| self.cost.cmp(&other.cost))
+    }
+}
+
+impl<C: Ord> PartialOrd for Frontier<C> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// Finds a cheapest path from any of `starts` to a goal, guided by `Problem::heuristic`.
+///
+/// The heuristic must never overestimate. Stale heap entries are skipped when
+/// popped rather than removed when a cheaper route is found.
+pub fn astar<P: Problem>(problem: &P, starts: impl IntoIterator<Item = P::State>) -> Option<Path<P::State, P::Cost>> {
+    best_first(problem, starts, |state| problem.heuristic(state))
+}
+
+/// Finds a cheapest path from any of `starts` to a goal, ignoring `Problem::heuristic`.
+pub fn dijkstra<P: Problem>(problem: &P, starts: impl IntoIterator<Item = P::State>) -> Option<Path<P::State, P::Cost>> {
+    best_first(problem, starts, |_| P::Cost::default())
+}
+
+fn best_first<P: Problem>(problem: &P, starts: impl IntoIterator<Item = P::State>, heuristic: impl Fn(&P::State) -> P::Cost) -> Option<Path<P::State, P::Cost>> {
+    let mut nodes: Vec<Node<P::State, P::Cost>> = Vec::new();
+    let mut indices: HashMap<P::State, usize> = HashMap::new();
+    let mut frontier = BinaryHeap::new();
+
+    for state in starts {
+        if let Entry::Vacant(entry) = indices.entry(state.clone()) {
+            let index = nodes.len();
+            entry.insert(index);
+            frontier.push(Frontier { estimate: heuristic(&state), cost: P::Cost::default(), index });
+            nodes.push(Node { state, cost: P::Cost::default(), parent: None });
+        }
+    }
+
+    while let Some(Frontier { cost, index, .. }) = frontier.pop() {
+        if cost > nodes[index].cost { continue; }
+
+        if problem.is_goal(&nodes[index].state) {
+            return Some(reconstruct(&nodes, index));
+        }
+
+        let successors: Vec<_> = problem.successors(&nodes[index].state).into_iter().collect();
+        for (next_state, step_cost) in successors {
+            let next_cost = cost + step_cost;
+
+            let next_index = match indices.entry(next_state) {
+                Entry::Occupied(entry) => {
+                    let next_index = *entry.get();
+                    if nodes[next_index].cost <= next_cost { continue; }
+
+                    nodes[next_index].cost = next_cost;
+                    nodes[next_index].parent = Some(index);
+                    next_index
+                },
+                Entry::Vacant(entry) => {
+                    let next_index = nodes.len();
+                    nodes.push(Node { state: entry.key().clone(), cost: next_cost, parent: Some(index) });
+                    entry.insert(next_index);
+                    next_index
+                },
+            };
+
+            let estimate = next_cost + heuristic(&nodes[next_index].state);
+            frontier.push(Frontier { estimate, cost: next_cost, index: next_index });
+        }
+    }
+
+    None
+}
+
+/// Finds a path with the fewest steps from any of `starts` to a goal,
+/// ignoring step costs. The path cost is the number of steps taken.
+pub fn bfs<P: Problem>(problem: &P, starts: impl IntoIterator<Item = P::State>) -> Option<Path<P::State, usize>> {
+    let mut nodes: Vec<Node<P::State, usize>> = Vec::new();
+    let mut indices: HashMap<P::State, usize> = HashMap::new();
+    let mut frontier = VecDeque::new();
+
+    for state in starts {
+        if let Entry::Vacant(entry) = indices.entry(state.clone()) {
+            entry.insert(nodes.len());
+            frontier.push_back(nodes.len());
+            nodes.push(Node { state, cost: 0, parent: None });
+        }
+    }
+
+    while let Some(index) = frontier.pop_front() {
+        if problem.is_goal(&nodes[index].state) {
+            return Some(reconstruct(&nodes, index));
+        }
+
+        let cost = nodes[index].cost + 1;
+        let successors: Vec<_> = problem.successors(&nodes[index].state).into_iter().collect();
+        for (next_state, _) in successors {
+            if let Entry::Vacant(entry) = indices.entry(next_state) {
+                let next_index = nodes.len();
+                nodes.push(Node { state: entry.key().clone(), cost, parent: Some(index) });
+                entry.insert(next_index);
+                frontier.push_back(next_index);
+            }
+        }
+    }
+
+    None
+}
+
+fn reconstruct<S: Clone, C: Copy>(nodes: &[Node<S, C>], goal: usize) -> Path<S, C> {
+    let mut states = Vec::new();
+    let mut current = Some(goal);
+    while let Some(index) = current {
+        states.push(nodes[index].state.clone());
+        current = nodes[index].parent;
+    }
+    states.reverse();
+
+    Path {
+        cost: nodes[goal].cost,
+        states,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::Problem;
+
+    /// A number line where stepping by one costs 1 and jumping up by 5 costs 3.
+    struct NumberLine {
+        goal: i32,
+    }
+
+    impl Problem for NumberLine {
+        type State = i32;
+        type Cost = u32;
+
+        fn successors(&self, &state: &i32) -> impl IntoIterator<Item = (i32, u32)> {
+            [(state + 1, 1), (state - 1, 1), (state + 5, 3)]
+        }
+
+        fn is_goal(&self, &state: &i32) -> bool {
+            state == self.goal
+        }
+
+        fn heuristic(&self, &state: &i32) -> u32 {
+            (self.goal - state).unsigned_abs() * 3 / 5
+        }
+    }
+
+    #[test]
+    fn test_weighted() {
+        let problem = NumberLine { goal: 11 };
+
+        let path = super::dijkstra(&problem, [0]).unwrap();
+        assert_eq!(path.cost, 7);
+        assert_eq!(path.states.first(), Some(&0));
+        assert_eq!(path.goal(), &11);
+
+        let path = super::astar(&problem, [0]).unwrap();
+        assert_eq!(path.cost, 7);
+        assert_eq!(path.states.len(), 4);
+    }
+
+    #[test]
+    fn test_bfs() {
+        let problem = NumberLine { goal: 11 };
+
+        let path = super::bfs(&problem, [0]).unwrap();
+        assert_eq!(path.cost, 3);
+        assert_eq!(path.states.len(), 4);
+        assert_eq!(path.goal(), &11);
+
+        let path = super::bfs(&problem, [10, 0]).unwrap();
+        assert_eq!(path.cost, 1);
+    }
+}
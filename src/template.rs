@@ -4,8 +4,14 @@ use aoc_runner_derive::aoc;
 use anyhow::Result;
 use anyhow::anyhow;
 
+use thiserror::Error;
+
 type Data = usize;
 
+#[derive(Debug, Error)]
+enum DayXError {
+}
+
 
 #[aoc_generator(dayX)]
 fn input_generator(input: &str) -> Result<Vec<Data>> {
@@ -13,15 +19,20 @@ fn input_generator(input: &str) -> Result<Vec<Data>> {
 }
 
 #[aoc(dayX, part1)]
-fn solve_part1(input: &[Data]) -> usize {
+fn solve_part1(input: &[Data]) -> Result<usize, DayXError> {
     unimplemented!()
 }
 
 #[aoc(dayX, part2)]
-fn solve_part2(input: &[Data]) -> usize {
+fn solve_part2(input: &[Data]) -> Result<usize, DayXError> {
     unimplemented!()
 }
 
+crate::registry::solutions! {
+    (X, 1, input_generator, solve_part1),
+    (X, 2, input_generator, solve_part2),
+}
+
 #[cfg(test)]
 mod test {
     const TEST_INPUT: &'static str =
@@ -30,7 +41,7 @@ r#"
     #[test]
     fn test_part1_example() {
         let input = super::input_generator(TEST_INPUT).unwrap();
-        let result = super::solve_part1(&input);
+        let result = super::solve_part1(&input).unwrap();
 
         //assert_eq!(result, None);
         assert!(false);
@@ -39,7 +50,7 @@ r#"
     #[test]
     fn test_part2_example() {
         let input = super::input_generator(TEST_INPUT).unwrap();
-        let result = super::solve_part2(&input);
+        let result = super::solve_part2(&input).unwrap();
 
         //assert_eq!(result, None);
         assert!(false);
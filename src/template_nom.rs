@@ -2,60 +2,52 @@ use aoc_runner_derive::aoc_generator;
 use aoc_runner_derive::aoc;
 
 use anyhow::Result;
-use anyhow::anyhow;
+
+use thiserror::Error;
 
 type Input = Vec<Data>;
 type InputRef = [Data];
 type Data = usize;
 
+#[derive(Debug, Error)]
+enum DayXError {
+}
+
 
 #[aoc_generator(dayX)]
 fn input_generator(input: &str) -> Result<Input> {
     unimplemented!();
-    let (input, result) = parse_input(input)
-        .map_err(|err| err.map(|err| anyhow!(nom::error::convert_error(input, err))))?;
-    if !input.is_empty() {
-        return Err(anyhow!("Had unparsed input after parsing: {}", input));
-    }
-    Ok(result)
+    run_parser(input, parse_input)
 }
 
-type IResult<I, T> = nom::IResult<I, T, nom::error::VerboseError<I>>;
-use nom::bytes::complete::take_while1;
-use nom::combinator::opt;
-use nom::multi::separated_list1;
 use nom::bytes::complete::tag;
 use nom::branch::alt;
 use nom::sequence::separated_pair;
 
-fn parse_input(input: &str) -> IResult<&str, Input> {
-    let (input, _) = opt(tag("\n"))(input)?;
-    let (input, result) = separated_list1(tag("\n"), parse_game)(input)?;
-    let (input, _) = opt(tag("\n"))(input)?;
-
-    Ok((input, result))
-}
-
-fn parse_u32(input: &str) -> IResult<&str, u32> {
-    let (input, number_string) = take_while1(is_number)(input)?;
-    let number = number_string.parse().unwrap();
+use crate::parse::IResult;
+use crate::parse::lines;
+use crate::parse::run_parser;
+use crate::parse::unsigned;
 
-    Ok((input, number))
-}
-fn is_number(c: char) -> bool {
-    ('0'..='9').contains(&c)
+fn parse_input(input: &str) -> IResult<&str, Input> {
+    lines(parse_game)(input)
 }
 
 #[aoc(dayX, part1)]
-fn solve_part1(input: &InputRef) -> usize {
+fn solve_part1(input: &InputRef) -> Result<usize, DayXError> {
     unimplemented!()
 }
 
 #[aoc(dayX, part2)]
-fn solve_part2(input: &InputRef) -> usize {
+fn solve_part2(input: &InputRef) -> Result<usize, DayXError> {
     unimplemented!()
 }
 
+crate::registry::solutions! {
+    (X, 1, input_generator, solve_part1),
+    (X, 2, input_generator, solve_part2),
+}
+
 #[cfg(test)]
 mod test {
     const TEST_INPUT: &'static str =
@@ -64,7 +56,7 @@ r#"
     #[test]
     fn test_part1_example() {
         let input = super::input_generator(TEST_INPUT).unwrap();
-        let result = super::solve_part1(&input);
+        let result = super::solve_part1(&input).unwrap();
 
         //assert_eq!(result, None);
         assert!(false);
@@ -73,7 +65,7 @@ r#"
     #[test]
     fn test_part2_example() {
         let input = super::input_generator(TEST_INPUT).unwrap();
-        let result = super::solve_part2(&input);
+        let result = super::solve_part2(&input).unwrap();
 
         //assert_eq!(result, None);
         assert!(false);
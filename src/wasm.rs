@@ -0,0 +1,41 @@
+//! JavaScript bindings, so the solutions can run in a browser or under node
+//! from the same registry as the `aoc` runner.
+
+use wasm_bindgen::prelude::*;
+
+use crate::registry;
+
+/// Solves `part` of `day` for `input` with its main solution, returning the
+/// answer, or a message saying why there isn't one. Errors become exceptions
+/// on the JavaScript side.
+#[wasm_bindgen]
+pub fn solve(day: u32, part: u32, input: &str) -> Result<String, String> {
+    let solution = registry::find(day, part, None)
+        .ok_or_else(|| format!("No solution registered for day {} part {}", day, part))?;
+
+    (solution.run)(input)
+        .map(|run| run.answer)
+        .map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod test {
+    #[cfg(target_arch = "wasm32")]
+    use wasm_bindgen_test::wasm_bindgen_test as test;
+
+    const DAY9_INPUT: &str = "0 3 6 9 12 15\n1 3 6 10 15 21\n10 13 16 21 30 45\n";
+
+    #[test]
+    fn test_solve() {
+        assert_eq!(super::solve(9, 1, DAY9_INPUT), Ok("114".to_string()));
+        assert_eq!(super::solve(9, 2, DAY9_INPUT), Ok("2".to_string()));
+    }
+
+    #[test]
+    fn test_solve_errors() {
+        assert_eq!(super::solve(26, 1, ""), Err("No solution registered for day 26 part 1".to_string()));
+
+        let error = super::solve(9, 1, "0 3 x\n").unwrap_err();
+        assert!(error.starts_with("Failed to parse input"), "{}", error);
+    }
+}